@@ -0,0 +1,131 @@
+//! Compares the cost of creating a channel, and of a round trip between two threads,
+//! for `channel()`, `compact_channel()` and a pair of raw `std::sync::mpsc` channels.
+//!
+//! Run with `cargo bench`, adding `--features crossbeam` to measure that backend.
+
+use std::alloc::{GlobalAlloc, Layout, System};
+use std::hint::black_box;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::mpsc;
+use std::thread;
+use std::time::{Duration, Instant};
+
+/// Counts allocations, so that construction can be reported in allocations as well as
+/// time.
+struct Counting;
+
+static ALLOCATIONS: AtomicUsize = AtomicUsize::new(0);
+
+unsafe impl GlobalAlloc for Counting {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        ALLOCATIONS.fetch_add(1, Ordering::Relaxed);
+        System.alloc(layout)
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        System.dealloc(ptr, layout)
+    }
+}
+
+#[global_allocator]
+static GLOBAL: Counting = Counting;
+
+const CONSTRUCTIONS: u32 = 100_000;
+const ROUND_TRIPS: u32 = 20_000;
+
+fn report(name: &str, iterations: u32, elapsed: Duration, allocations: usize) {
+    println!(
+        "{:<32} {:>10.1?} per iteration, {:>3} allocations",
+        name,
+        elapsed / iterations,
+        allocations / iterations as usize,
+    );
+}
+
+fn measure<T>(name: &str, iterations: u32, mut f: impl FnMut() -> T) {
+    let allocations = ALLOCATIONS.load(Ordering::Relaxed);
+    let start = Instant::now();
+    for _ in 0..iterations {
+        black_box(f());
+    }
+    report(
+        name,
+        iterations,
+        start.elapsed(),
+        ALLOCATIONS.load(Ordering::Relaxed) - allocations,
+    );
+}
+
+fn round_trip_channel() {
+    let (left, right) = bichannel::channel::<u32, u32>();
+    let echo = thread::spawn(move || {
+        for n in right.iter() {
+            if right.send(n).is_err() {
+                break;
+            }
+        }
+    });
+
+    measure("channel() round trip", ROUND_TRIPS, || {
+        left.send(1).unwrap();
+        left.recv().unwrap()
+    });
+
+    drop(left);
+    echo.join().unwrap();
+}
+
+fn round_trip_compact_channel() {
+    let (left, right) = bichannel::compact_channel::<u32, u32>();
+    let echo = thread::spawn(move || {
+        for n in right.iter() {
+            if right.send(n).is_err() {
+                break;
+            }
+        }
+    });
+
+    measure("compact_channel() round trip", ROUND_TRIPS, || {
+        left.send(1).unwrap();
+        left.recv().unwrap()
+    });
+
+    drop(left);
+    echo.join().unwrap();
+}
+
+fn round_trip_mpsc() {
+    let (to_echo, from_main) = mpsc::channel::<u32>();
+    let (to_main, from_echo) = mpsc::channel::<u32>();
+    let echo = thread::spawn(move || {
+        for n in from_main.iter() {
+            if to_main.send(n).is_err() {
+                break;
+            }
+        }
+    });
+
+    measure("mpsc pair round trip", ROUND_TRIPS, || {
+        to_echo.send(1).unwrap();
+        from_echo.recv().unwrap()
+    });
+
+    drop(to_echo);
+    echo.join().unwrap();
+}
+
+fn main() {
+    measure("channel() construction", CONSTRUCTIONS, || {
+        bichannel::channel::<u32, u32>()
+    });
+    measure("compact_channel() construction", CONSTRUCTIONS, || {
+        bichannel::compact_channel::<u32, u32>()
+    });
+    measure("mpsc pair construction", CONSTRUCTIONS, || {
+        (mpsc::channel::<u32>(), mpsc::channel::<u32>())
+    });
+
+    round_trip_channel();
+    round_trip_compact_channel();
+    round_trip_mpsc();
+}
@@ -0,0 +1,142 @@
+//! Tracking whether a side of a [`Channel`](crate::Channel) can still send.
+
+use std::mem;
+use std::sync::atomic::Ordering;
+use std::sync::{Arc, Weak};
+
+use crate::primitive::{AtomicBool, AtomicUsize};
+
+/// The state shared by the handles of one side, watched by the other side.
+#[derive(Debug, Default)]
+pub(crate) struct Side {
+    /// The number of handles whose sender has been neither closed nor dropped.
+    open: AtomicUsize,
+    /// Set by the first send scheduled from this side, which is cancelled along with
+    /// the others still pending once no handle is open.
+    scheduled: AtomicBool,
+}
+
+/// A handle's share of its side, counted as open until the handle closes its sender or
+/// is dropped. The shared allocation also identifies the side.
+#[derive(Debug)]
+pub(crate) struct Alive {
+    side: Arc<Side>,
+    open: bool,
+}
+
+impl Alive {
+    pub(crate) fn new() -> Self {
+        Alive::open(Arc::default())
+    }
+
+    fn open(side: Arc<Side>) -> Self {
+        side.open.fetch_add(1, Ordering::Relaxed);
+        Alive { side, open: true }
+    }
+
+    /// Stops counting this share, once the handle's sender has been closed.
+    pub(crate) fn close(&mut self) {
+        if mem::replace(&mut self.open, false)
+            && self.side.open.fetch_sub(1, Ordering::Release) == 1
+            && self.side.scheduled.load(Ordering::Relaxed)
+        {
+            crate::timer::cancel();
+        }
+    }
+
+    /// Returns a share of the same side that is not counted, for a handle that cannot
+    /// send.
+    pub(crate) fn closed(&self) -> Self {
+        Alive {
+            side: self.side.clone(),
+            open: false,
+        }
+    }
+
+    /// Returns a share of the same side that is counted, for a handle that can send.
+    pub(crate) fn reopened(&self) -> Self {
+        Alive::open(self.side.clone())
+    }
+
+    /// Returns `true` unless this share has been closed.
+    pub(crate) fn is_open(&self) -> bool {
+        self.open
+    }
+
+    /// Returns a watch on this side for a send scheduled from it, which the timer
+    /// cancels once the side is no longer open.
+    pub(crate) fn schedule(&self) -> Peer {
+        self.side.scheduled.store(true, Ordering::Relaxed);
+        self.peer()
+    }
+
+    /// Returns a watch on this side, for its counterpart.
+    pub(crate) fn peer(&self) -> Peer {
+        Peer(Arc::downgrade(&self.side))
+    }
+
+    /// Returns the address of the shared allocation, which identifies the side.
+    pub(crate) fn id(&self) -> usize {
+        Arc::as_ptr(&self.side) as usize
+    }
+}
+
+impl Clone for Alive {
+    fn clone(&self) -> Self {
+        if self.open {
+            self.reopened()
+        } else {
+            self.closed()
+        }
+    }
+}
+
+impl Drop for Alive {
+    fn drop(&mut self) {
+        self.close();
+    }
+}
+
+/// A watch on the other side of a channel, which does not keep it alive.
+#[derive(Debug, Clone)]
+pub(crate) struct Peer(Weak<Side>);
+
+impl Peer {
+    /// Returns `true` once every handle of the watched side has closed its sender or
+    /// been dropped.
+    pub(crate) fn hung_up(&self) -> bool {
+        self.0
+            .upgrade()
+            .is_none_or(|side| side.open.load(Ordering::Acquire) == 0)
+    }
+
+    /// Returns the address of the watched side's allocation, see [`Alive::id`].
+    pub(crate) fn id(&self) -> usize {
+        self.0.as_ptr() as usize
+    }
+}
+
+#[cfg(test)]
+mod examples {
+    #[test]
+    #[cfg(loom)]
+    fn test_loom_hang_up_follows_last_message() {
+        use loom::thread;
+
+        loom::model(|| {
+            let (left, mut right) = crate::channel::<u8, u8>();
+
+            let closing = thread::spawn(move || {
+                right.send(1).unwrap();
+                right.close_sender();
+                right
+            });
+
+            // Once the hang up is observed, so is everything sent before it.
+            let hung_up = left.is_disconnected();
+            let received = left.try_recv();
+            drop(closing.join().unwrap());
+            assert!(!hung_up || received == Ok(1), "{:?}", received);
+        });
+    }
+}
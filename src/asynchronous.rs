@@ -0,0 +1,192 @@
+//! A bidirectional channel native to async code, enabled with the `async` feature.
+//!
+//! Unlike the `futures` feature, which adapts the blocking [`Channel`] for use from
+//! async code, [`AsyncChannel`] is backed by `tokio::sync::mpsc` and never touches a
+//! blocking primitive.
+//!
+//! [`AsyncChannel::recv`] is cancellation safe, so it can race other futures in
+//! `tokio::select!`. A message is only taken from the buffer in the same poll that
+//! returns it, so dropping the future of a losing branch never loses a message.
+//!
+//! [`Channel`]: crate::Channel
+
+use tokio::sync::mpsc::{self, error, UnboundedReceiver, UnboundedSender};
+
+use crate::{SendError, TryRecvError};
+
+/// One side of an async bidirectional channel. This channel can send to and receive
+/// from its counterpart.
+///
+/// # Examples
+///
+/// ```
+/// # tokio::runtime::Builder::new_current_thread().build().unwrap().block_on(async {
+/// let (mut l, mut r) = bichannel::async_channel();
+///
+/// l.send(1).await.unwrap();
+/// assert_eq!(Some(1), r.recv().await);
+///
+/// r.send(1).await.unwrap();
+/// assert_eq!(Some(1), l.recv().await);
+/// # });
+/// ```
+#[derive(Debug)]
+pub struct AsyncChannel<S, R> {
+    sender: UnboundedSender<S>,
+    receiver: UnboundedReceiver<R>,
+}
+
+impl<S, R> AsyncChannel<S, R> {
+    /// Sends a value to the other side of this channel, returning it back if it could
+    /// not be sent.
+    ///
+    /// As with [`Channel::send`], a return value of [`Ok`] does *not* mean that the
+    /// data will be received. The buffer is unbounded, so this never waits.
+    ///
+    /// [`Channel::send`]: crate::Channel::send
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # tokio::runtime::Builder::new_current_thread().build().unwrap().block_on(async {
+    /// let (l, r) = bichannel::async_channel::<u8, u8>();
+    ///
+    /// l.send(1).await.unwrap();
+    ///
+    /// drop(r);
+    /// assert_eq!(l.send(2).await.unwrap_err().0, 2);
+    /// # });
+    /// ```
+    pub async fn send(&self, s: S) -> Result<(), SendError<S>> {
+        self.sender
+            .send(s)
+            .map_err(|error::SendError(s)| SendError(s))
+    }
+
+    /// Waits for a value from the other side, returning [`None`] once the other side
+    /// has hung up and every buffered message has been received.
+    ///
+    /// This method is cancellation safe: if the returned future is dropped before it
+    /// completes, no message is lost, and it remains available to the next `recv`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # tokio::runtime::Builder::new_current_thread().build().unwrap().block_on(async {
+    /// let (mut l, r) = bichannel::async_channel::<u8, u8>();
+    ///
+    /// r.send(1).await.unwrap();
+    /// drop(r);
+    ///
+    /// assert_eq!(l.recv().await, Some(1));
+    /// assert_eq!(l.recv().await, None);
+    /// # });
+    /// ```
+    pub async fn recv(&mut self) -> Option<R> {
+        self.receiver.recv().await
+    }
+
+    /// Attempts to return a pending value from the other side without waiting.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use bichannel::TryRecvError;
+    ///
+    /// let (mut l, _r) = bichannel::async_channel::<u8, u8>();
+    ///
+    /// assert_eq!(l.try_recv(), Err(TryRecvError::Empty));
+    /// ```
+    pub fn try_recv(&mut self) -> Result<R, TryRecvError> {
+        self.receiver.try_recv().map_err(|e| match e {
+            error::TryRecvError::Empty => TryRecvError::Empty,
+            error::TryRecvError::Disconnected => TryRecvError::Disconnected,
+        })
+    }
+}
+
+/// Creates an async bidirectional channel returning the left and right sides. Each
+/// side can send and receive from its counterpart, mirroring [`channel`].
+///
+/// [`channel`]: crate::channel
+///
+/// # Examples
+///
+/// ```
+/// # tokio::runtime::Builder::new_current_thread().build().unwrap().block_on(async {
+/// let (left, mut right) = bichannel::async_channel::<&'static str, &'static str>();
+///
+/// left.send("ping").await.unwrap();
+///
+/// assert_eq!(right.recv().await.unwrap(), "ping");
+/// # });
+/// ```
+pub fn async_channel<T, U>() -> (AsyncChannel<T, U>, AsyncChannel<U, T>) {
+    let (ls, lr) = mpsc::unbounded_channel();
+    let (rs, rr) = mpsc::unbounded_channel();
+
+    (
+        AsyncChannel {
+            sender: ls,
+            receiver: rr,
+        },
+        AsyncChannel {
+            sender: rs,
+            receiver: lr,
+        },
+    )
+}
+
+#[cfg(test)]
+mod examples {
+
+    #[tokio::test]
+    async fn test_async_scenario() {
+        let (mut left, mut right) = crate::async_channel::<&'static str, &'static str>();
+
+        let handle = tokio::spawn(async move {
+            while let Some(msg) = right.recv().await {
+                right.send(msg).await.unwrap();
+            }
+        });
+
+        left.send("ping").await.unwrap();
+        assert_eq!(left.recv().await, Some("ping"));
+
+        left.send("pong").await.unwrap();
+        assert_eq!(left.recv().await, Some("pong"));
+
+        drop(left);
+        handle.await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_recv_is_cancellation_safe() {
+        const SENT: usize = 1000;
+
+        let (mut left, right) = crate::async_channel::<(), usize>();
+
+        let producer = tokio::spawn(async move {
+            for i in 0..SENT {
+                right.send(i).await.unwrap();
+                if i % 3 == 0 {
+                    tokio::task::yield_now().await;
+                }
+            }
+        });
+
+        let (mut received, mut cancelled) = (Vec::new(), 0);
+        while received.len() < SENT {
+            tokio::select! {
+                biased;
+                _ = tokio::task::yield_now() => cancelled += 1,
+                Some(i) = left.recv() => received.push(i),
+            }
+        }
+
+        producer.await.unwrap();
+        assert!(cancelled > 0);
+        assert_eq!(received, (0..SENT).collect::<Vec<_>>());
+        assert!(left.try_recv().is_err());
+    }
+}
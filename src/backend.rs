@@ -0,0 +1,1001 @@
+//! The `std::sync::mpsc`, `crossbeam` or `flume` channel backing each direction of a
+//! `Channel`. Crossbeam is used if both features are enabled, as only it supports
+//! `Select`.
+//!
+//! Both halves of a direction share the wakers of the tasks waiting on either end, so
+//! that tasks polling for a message (or for room in a bounded buffer) are woken by the
+//! other half instead of spinning. Waking is skipped unless a task has registered,
+//! keeping the blocking paths cheap.
+
+use std::collections::VecDeque;
+use std::mem;
+use std::sync::atomic::Ordering;
+use std::sync::{Arc, PoisonError, Weak};
+use std::task::Waker;
+#[cfg(feature = "futures")]
+use std::task::{Context, Poll};
+use std::time::{Duration, Instant};
+
+use crate::primitive::{fence, AtomicBool, AtomicU64, AtomicUsize, Mutex, MutexGuard};
+use crate::{
+    OverflowPolicy, RecvError, RecvTimeoutError, SendError, SendTimeoutError, TryRecvError,
+    TrySendError,
+};
+
+#[cfg(not(any(feature = "crossbeam", feature = "flume")))]
+use crate::primitive::mpsc;
+
+#[cfg(not(any(feature = "crossbeam", feature = "flume")))]
+use crate::primitive::mpsc as raw;
+#[cfg(feature = "crossbeam")]
+use crossbeam_channel as raw;
+#[cfg(all(feature = "flume", not(feature = "crossbeam")))]
+use flume as raw;
+
+#[cfg(not(any(feature = "crossbeam", feature = "flume")))]
+type RawReceiver<T> = mpsc::Receiver<T>;
+
+#[cfg(any(feature = "crossbeam", feature = "flume"))]
+type RawSender<T> = raw::Sender<T>;
+#[cfg(any(feature = "crossbeam", feature = "flume"))]
+type RawReceiver<T> = raw::Receiver<T>;
+
+/// The std backend uses distinct types for unbounded and bounded senders, so they are
+/// unified here to keep a single `Channel` type. The std sender does not expose its
+/// bound, so it is kept alongside.
+#[cfg(not(any(feature = "crossbeam", feature = "flume")))]
+#[derive(Debug)]
+enum RawSender<T> {
+    Unbounded(mpsc::Sender<T>),
+    Bounded(mpsc::SyncSender<T>, usize),
+}
+
+#[cfg(not(any(feature = "crossbeam", feature = "flume")))]
+impl<T> RawSender<T> {
+    fn send(&self, t: T) -> Result<(), raw::SendError<T>> {
+        match self {
+            RawSender::Unbounded(sender) => sender.send(t),
+            RawSender::Bounded(sender, _) => sender.send(t),
+        }
+    }
+
+    fn try_send(&self, t: T) -> Result<(), raw::TrySendError<T>> {
+        match self {
+            RawSender::Unbounded(sender) => sender
+                .send(t)
+                .map_err(|raw::SendError(t)| raw::TrySendError::Disconnected(t)),
+            RawSender::Bounded(sender, _) => sender.try_send(t),
+        }
+    }
+
+    fn capacity(&self) -> Option<usize> {
+        match self {
+            RawSender::Unbounded(_) => None,
+            RawSender::Bounded(_, bound) => Some(*bound),
+        }
+    }
+
+    /// The std sender cannot wait on a full buffer with a timeout, so this retries
+    /// every `SEND_POLL_INTERVAL` until the deadline, or blocks if there is none.
+    fn send_timeout(&self, mut t: T, timeout: Duration) -> Result<(), SendTimeoutError<T>> {
+        let deadline = match crate::deadline(timeout) {
+            Some(deadline) => deadline,
+            None => {
+                return self
+                    .send(t)
+                    .map_err(|raw::SendError(t)| SendTimeoutError::Disconnected(t))
+            }
+        };
+        loop {
+            t = match self.try_send(t) {
+                Ok(()) => return Ok(()),
+                Err(raw::TrySendError::Disconnected(t)) => {
+                    return Err(SendTimeoutError::Disconnected(t))
+                }
+                Err(raw::TrySendError::Full(t)) => t,
+            };
+
+            let now = Instant::now();
+            if now >= deadline {
+                return Err(SendTimeoutError::Timeout(t));
+            }
+            std::thread::sleep(SEND_POLL_INTERVAL.min(deadline - now));
+        }
+    }
+}
+
+/// How long the std backend waits before retrying a send timing out on a full buffer.
+#[cfg(not(any(feature = "crossbeam", feature = "flume")))]
+const SEND_POLL_INTERVAL: Duration = Duration::from_millis(1);
+
+#[cfg(not(any(feature = "crossbeam", feature = "flume")))]
+impl<T> Clone for RawSender<T> {
+    fn clone(&self) -> Self {
+        match self {
+            RawSender::Unbounded(sender) => RawSender::Unbounded(sender.clone()),
+            RawSender::Bounded(sender, bound) => RawSender::Bounded(sender.clone(), *bound),
+        }
+    }
+}
+
+impl<T> From<raw::SendError<T>> for SendError<T> {
+    fn from(raw::SendError(t): raw::SendError<T>) -> Self {
+        SendError(t)
+    }
+}
+
+impl From<raw::RecvError> for RecvError {
+    fn from(_: raw::RecvError) -> Self {
+        RecvError
+    }
+}
+
+impl<T> From<raw::TrySendError<T>> for TrySendError<T> {
+    fn from(err: raw::TrySendError<T>) -> Self {
+        match err {
+            raw::TrySendError::Full(t) => TrySendError::Full(t),
+            raw::TrySendError::Disconnected(t) => TrySendError::Disconnected(t),
+        }
+    }
+}
+
+#[cfg(any(feature = "crossbeam", feature = "flume"))]
+impl<T> From<raw::SendTimeoutError<T>> for SendTimeoutError<T> {
+    fn from(err: raw::SendTimeoutError<T>) -> Self {
+        match err {
+            raw::SendTimeoutError::Timeout(t) => SendTimeoutError::Timeout(t),
+            raw::SendTimeoutError::Disconnected(t) => SendTimeoutError::Disconnected(t),
+        }
+    }
+}
+
+impl From<raw::TryRecvError> for TryRecvError {
+    fn from(err: raw::TryRecvError) -> Self {
+        match err {
+            raw::TryRecvError::Empty => TryRecvError::Empty,
+            raw::TryRecvError::Disconnected => TryRecvError::Disconnected,
+        }
+    }
+}
+
+impl From<raw::RecvTimeoutError> for RecvTimeoutError {
+    fn from(err: raw::RecvTimeoutError) -> Self {
+        match err {
+            raw::RecvTimeoutError::Timeout => RecvTimeoutError::Timeout,
+            raw::RecvTimeoutError::Disconnected => RecvTimeoutError::Disconnected,
+        }
+    }
+}
+
+/// The receiving end of a direction that evicts its oldest message when full, shared
+/// with the senders that do the evicting. The std receiver is not `Sync`, so it is
+/// guarded by a lock.
+#[cfg(not(any(feature = "crossbeam", feature = "flume")))]
+type SharedReceiver<T> = Mutex<RawReceiver<T>>;
+#[cfg(any(feature = "crossbeam", feature = "flume"))]
+type SharedReceiver<T> = RawReceiver<T>;
+
+#[cfg(not(any(feature = "crossbeam", feature = "flume")))]
+fn with_shared<T, U>(shared: &SharedReceiver<T>, f: impl FnOnce(&RawReceiver<T>) -> U) -> U {
+    f(&shared.lock().unwrap_or_else(PoisonError::into_inner))
+}
+
+#[cfg(any(feature = "crossbeam", feature = "flume"))]
+fn with_shared<T, U>(shared: &SharedReceiver<T>, f: impl FnOnce(&RawReceiver<T>) -> U) -> U {
+    f(shared)
+}
+
+#[derive(Debug)]
+enum Source<T> {
+    Owned(RawReceiver<T>),
+    Shared(Arc<SharedReceiver<T>>),
+}
+
+impl<T> Source<T> {
+    fn with<U>(&self, f: impl FnOnce(&RawReceiver<T>) -> U) -> U {
+        match self {
+            Source::Owned(raw) => f(raw),
+            Source::Shared(shared) => with_shared(shared, f),
+        }
+    }
+
+    #[cfg(any(feature = "crossbeam", feature = "flume"))]
+    fn raw(&self) -> &RawReceiver<T> {
+        match self {
+            Source::Owned(raw) => raw,
+            Source::Shared(shared) => shared,
+        }
+    }
+}
+
+#[cfg(any(feature = "crossbeam", feature = "flume"))]
+impl<T> Clone for Source<T> {
+    fn clone(&self) -> Self {
+        match self {
+            Source::Owned(raw) => Source::Owned(raw.clone()),
+            Source::Shared(shared) => Source::Shared(shared.clone()),
+        }
+    }
+}
+
+/// Messages set aside by `recv_matching`, which are received ahead of the raw
+/// receiver. The length is tracked separately so the common case of nothing being held
+/// never takes the lock.
+#[derive(Debug)]
+struct Held<T> {
+    len: AtomicUsize,
+    queue: Mutex<VecDeque<T>>,
+}
+
+impl<T> Held<T> {
+    fn new() -> Self {
+        Held {
+            len: AtomicUsize::new(0),
+            queue: Mutex::new(VecDeque::new()),
+        }
+    }
+
+    fn len(&self) -> usize {
+        self.len.load(Ordering::Acquire)
+    }
+
+    fn lock(&self) -> MutexGuard<'_, VecDeque<T>> {
+        self.queue.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+
+    fn push(&self, t: T) {
+        let mut queue = self.lock();
+        queue.push_back(t);
+        self.len.store(queue.len(), Ordering::Release);
+    }
+
+    fn pop(&self) -> Option<T> {
+        if self.len() == 0 {
+            return None;
+        }
+
+        let mut queue = self.lock();
+        let t = queue.pop_front();
+        self.len.store(queue.len(), Ordering::Release);
+        t
+    }
+
+    fn take_matching(&self, pred: impl Fn(&T) -> bool) -> Option<T> {
+        if self.len() == 0 {
+            return None;
+        }
+
+        let mut queue = self.lock();
+        let t = queue
+            .iter()
+            .position(pred)
+            .and_then(|index| queue.remove(index));
+        self.len.store(queue.len(), Ordering::Release);
+        t
+    }
+}
+
+/// The tasks waiting on one end of a direction, which every clone and half of that
+/// end shares, so each registered task is kept and all of them are woken together.
+/// The lock is only taken once a task has registered.
+#[derive(Debug, Default)]
+struct Wakers {
+    registered: AtomicBool,
+    wakers: Mutex<Vec<Waker>>,
+}
+
+impl Wakers {
+    fn lock(&self) -> MutexGuard<'_, Vec<Waker>> {
+        self.wakers.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+
+    #[cfg(feature = "futures")]
+    fn register(&self, waker: &Waker) {
+        let mut wakers = self.lock();
+        if !wakers.iter().any(|w| w.will_wake(waker)) {
+            wakers.push(waker.clone());
+        }
+        self.registered.store(true, Ordering::Relaxed);
+        drop(wakers);
+
+        // Pairs with the fence in `wake`, so that either the waker observes the
+        // registration or the caller observes the state change on its next attempt.
+        fence(Ordering::SeqCst);
+    }
+
+    fn wake(&self) {
+        fence(Ordering::SeqCst);
+
+        if self.registered.load(Ordering::Relaxed) {
+            let wakers = {
+                let mut wakers = self.lock();
+                self.registered.store(false, Ordering::Relaxed);
+                mem::take(&mut *wakers)
+            };
+
+            for waker in wakers {
+                waker.wake();
+            }
+        }
+    }
+}
+
+/// How long a task waiting on a detached direction sleeps before polling it again.
+#[cfg(feature = "futures")]
+const DETACHED_POLL_INTERVAL: Duration = Duration::from_millis(1);
+
+/// The state shared by both halves of one direction, kept in one allocation.
+#[derive(Debug)]
+struct State<T> {
+    /// Messages set aside by `recv_matching`.
+    held: Held<T>,
+    /// Woken when a message is sent, or the last sender is dropped.
+    recv: Wakers,
+    /// Woken when a message is received, or the receiver is dropped.
+    send: Wakers,
+    /// The number of messages accepted into the buffer.
+    sent: AtomicU64,
+    /// The number of messages handed to a receiving caller.
+    received: AtomicU64,
+    /// The deepest the buffer has been, as observed after each send.
+    #[cfg(any(feature = "crossbeam", feature = "flume"))]
+    high_water_mark: AtomicUsize,
+    /// The number of live senders.
+    senders: AtomicUsize,
+    /// The number of live receivers.
+    receivers: AtomicUsize,
+    /// Set when the other half of this direction was created outside this crate, and
+    /// so never wakes a registered task. Tasks are woken by the timer thread instead.
+    #[cfg(feature = "futures")]
+    detached: bool,
+}
+
+impl<T> Default for State<T> {
+    fn default() -> Self {
+        State {
+            held: Held::new(),
+            recv: Wakers::default(),
+            send: Wakers::default(),
+            sent: AtomicU64::default(),
+            received: AtomicU64::default(),
+            #[cfg(any(feature = "crossbeam", feature = "flume"))]
+            high_water_mark: AtomicUsize::default(),
+            senders: AtomicUsize::default(),
+            receivers: AtomicUsize::default(),
+            #[cfg(feature = "futures")]
+            detached: false,
+        }
+    }
+}
+
+impl<T> State<T> {
+    #[cfg(feature = "futures")]
+    fn detached() -> Self {
+        State {
+            detached: true,
+            ..State::default()
+        }
+    }
+
+    #[cfg(not(feature = "futures"))]
+    fn detached() -> Self {
+        State::default()
+    }
+
+    /// Accounts for a task going idle, having the timer thread wake it to poll again
+    /// shortly if nothing else will.
+    #[cfg(feature = "futures")]
+    fn pending<V>(&self, cx: &Context<'_>) -> Poll<V> {
+        if self.detached {
+            let waker = cx.waker().clone();
+            crate::timer::schedule(Instant::now() + DETACHED_POLL_INTERVAL, move || {
+                waker.wake()
+            });
+        }
+        Poll::Pending
+    }
+}
+
+/// Wakes the receiving task when dropped. Declared after the raw sender so that it
+/// runs once the sender is gone and the disconnect is observable.
+///
+/// A signal whose flag is unset belongs to a sender kept aside for upgrading a
+/// `WeakChannel` or for scheduled sends, which is not reported as live.
+#[derive(Debug)]
+struct SenderSignal<T>(Arc<State<T>>, bool);
+
+impl<T> SenderSignal<T> {
+    fn new(state: Arc<State<T>>) -> Self {
+        state.senders.fetch_add(1, Ordering::Relaxed);
+        SenderSignal(state, true)
+    }
+
+    fn uncounted(state: Arc<State<T>>) -> Self {
+        SenderSignal(state, false)
+    }
+}
+
+impl<T> Drop for SenderSignal<T> {
+    fn drop(&mut self) {
+        if self.1 {
+            self.0.senders.fetch_sub(1, Ordering::Relaxed);
+        }
+        self.0.recv.wake();
+    }
+}
+
+/// Wakes the sending task when dropped. Declared after the raw receiver so that it
+/// runs once the receiver is gone and the disconnect is observable.
+///
+/// As with [`SenderSignal`], a signal whose flag is unset is not reported as live.
+#[derive(Debug)]
+struct ReceiverSignal<T>(Arc<State<T>>, bool);
+
+impl<T> ReceiverSignal<T> {
+    fn new(state: Arc<State<T>>) -> Self {
+        state.receivers.fetch_add(1, Ordering::Relaxed);
+        ReceiverSignal(state, true)
+    }
+
+    #[cfg(any(feature = "crossbeam", feature = "flume"))]
+    fn uncounted(state: Arc<State<T>>) -> Self {
+        ReceiverSignal(state, false)
+    }
+}
+
+impl<T> Drop for ReceiverSignal<T> {
+    fn drop(&mut self) {
+        if self.1 {
+            self.0.receivers.fetch_sub(1, Ordering::Relaxed);
+        }
+        self.0.send.wake();
+    }
+}
+
+/// What a sender does when the buffer is full, see [`OverflowPolicy`].
+#[derive(Debug)]
+enum Overflow<T> {
+    Block,
+    DropNewest,
+    /// Holds the receiver so the oldest message can be evicted to make room.
+    DropOldest(Weak<SharedReceiver<T>>),
+    Error,
+}
+
+impl<T> Clone for Overflow<T> {
+    fn clone(&self) -> Self {
+        match self {
+            Overflow::Block => Overflow::Block,
+            Overflow::DropNewest => Overflow::DropNewest,
+            Overflow::DropOldest(shared) => Overflow::DropOldest(shared.clone()),
+            Overflow::Error => Overflow::Error,
+        }
+    }
+}
+
+#[derive(Debug)]
+pub struct Sender<T> {
+    raw: RawSender<T>,
+    signal: SenderSignal<T>,
+    overflow: Overflow<T>,
+    /// A value accepted by `start_send` that did not yet fit in the buffer.
+    #[cfg(feature = "futures")]
+    pending: Option<T>,
+}
+
+impl<T> Sender<T> {
+    pub(crate) fn send(&self, t: T) -> Result<(), SendError<T>> {
+        match &self.overflow {
+            Overflow::Block => self.raw.send(t)?,
+            Overflow::DropNewest => match self.raw.try_send(t) {
+                Ok(()) => (),
+                Err(raw::TrySendError::Full(_)) => return Ok(()),
+                Err(raw::TrySendError::Disconnected(t)) => return Err(SendError(t)),
+            },
+            Overflow::DropOldest(shared) => self.send_evicting(t, shared)?,
+            Overflow::Error => self.raw.try_send(t).map_err(|e| match e {
+                raw::TrySendError::Full(t) | raw::TrySendError::Disconnected(t) => SendError(t),
+            })?,
+        }
+        self.sent();
+        Ok(())
+    }
+
+    pub(crate) fn try_send(&self, t: T) -> Result<(), TrySendError<T>> {
+        match &self.overflow {
+            Overflow::Block | Overflow::Error => self.raw.try_send(t)?,
+            Overflow::DropNewest => match self.raw.try_send(t) {
+                Ok(()) => (),
+                Err(raw::TrySendError::Full(_)) => return Ok(()),
+                Err(raw::TrySendError::Disconnected(t)) => {
+                    return Err(TrySendError::Disconnected(t))
+                }
+            },
+            Overflow::DropOldest(shared) => self.send_evicting(t, shared)?,
+        }
+        self.sent();
+        Ok(())
+    }
+
+    /// Sends like `send`, but waits at most `timeout` for room in a full buffer. Only
+    /// the blocking policy waits, so the others behave as `try_send`.
+    pub(crate) fn send_timeout(&self, t: T, timeout: Duration) -> Result<(), SendTimeoutError<T>> {
+        match &self.overflow {
+            Overflow::Block => self.raw.send_timeout(t, timeout)?,
+            _ => {
+                return self.try_send(t).map_err(|err| match err {
+                    TrySendError::Full(t) => SendTimeoutError::Timeout(t),
+                    TrySendError::Disconnected(t) => SendTimeoutError::Disconnected(t),
+                })
+            }
+        }
+        self.sent();
+        Ok(())
+    }
+
+    /// Counts a message accepted into the buffer, and wakes the receiving task.
+    fn sent(&self) {
+        self.signal.0.sent.fetch_add(1, Ordering::Relaxed);
+        #[cfg(any(feature = "crossbeam", feature = "flume"))]
+        self.signal
+            .0
+            .high_water_mark
+            .fetch_max(self.raw.len(), Ordering::Relaxed);
+        self.signal.0.recv.wake();
+    }
+
+    /// Returns the bound of the buffer, or `None` if it is unbounded.
+    pub(crate) fn capacity(&self) -> Option<usize> {
+        self.raw.capacity()
+    }
+
+    /// Returns the number of live senders of this direction, including this one.
+    pub(crate) fn sender_count(&self) -> usize {
+        self.signal.0.senders.load(Ordering::Relaxed)
+    }
+
+    /// Returns the number of messages accepted into the buffer by any sender of this
+    /// direction.
+    pub(crate) fn sent_count(&self) -> u64 {
+        self.signal.0.sent.load(Ordering::Relaxed)
+    }
+
+    /// Returns `true` if the raw sender can be handed out by `into_raw`, which needs
+    /// no value to be held back by `start_send`.
+    pub(crate) fn is_plain(&self) -> bool {
+        #[cfg(feature = "futures")]
+        if self.pending.is_some() {
+            return false;
+        }
+        true
+    }
+
+    /// Unwraps the raw sender. Must only be called if `is_plain` holds.
+    #[cfg(not(any(feature = "crossbeam", feature = "flume")))]
+    pub(crate) fn into_raw(self) -> crate::MpscSender<T> {
+        match self.raw {
+            RawSender::Unbounded(sender) => crate::MpscSender::Unbounded(sender),
+            RawSender::Bounded(sender, _) => crate::MpscSender::Bounded(sender),
+        }
+    }
+
+    /// Unwraps the raw sender. Must only be called if `is_plain` holds.
+    #[cfg(any(feature = "crossbeam", feature = "flume"))]
+    pub(crate) fn into_raw(self) -> RawSender<T> {
+        self.raw
+    }
+
+    /// Evicts the oldest buffered message for as long as the buffer is full.
+    fn send_evicting(
+        &self,
+        mut t: T,
+        shared: &Weak<SharedReceiver<T>>,
+    ) -> Result<(), SendError<T>> {
+        loop {
+            t = match self.raw.try_send(t) {
+                Ok(()) => return Ok(()),
+                Err(raw::TrySendError::Disconnected(t)) => return Err(SendError(t)),
+                Err(raw::TrySendError::Full(t)) => t,
+            };
+
+            match shared.upgrade() {
+                Some(receiver) => with_shared(&receiver, |raw| drop(raw.try_recv())),
+                None => return Err(SendError(t)),
+            }
+        }
+    }
+
+    /// Queues `t` to be sent, holding onto it if the buffer is full until the next
+    /// call to `poll_flush`.
+    #[cfg(feature = "futures")]
+    pub(crate) fn start_send(&mut self, t: T) -> Result<(), SendError<T>> {
+        match self.try_send(t) {
+            Ok(()) => Ok(()),
+            Err(TrySendError::Full(t)) => {
+                self.pending = Some(t);
+                Ok(())
+            }
+            Err(TrySendError::Disconnected(t)) => Err(SendError(t)),
+        }
+    }
+
+    /// Attempts to send the value held by `start_send`, registering the task to be
+    /// woken when the receiver takes a message if the buffer is still full.
+    #[cfg(feature = "futures")]
+    pub(crate) fn poll_flush(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), SendError<T>>> {
+        let t = match self.pending.take() {
+            Some(t) => t,
+            None => return Poll::Ready(Ok(())),
+        };
+
+        let t = match self.try_send(t) {
+            Ok(()) => return Poll::Ready(Ok(())),
+            Err(TrySendError::Disconnected(t)) => return Poll::Ready(Err(SendError(t))),
+            Err(TrySendError::Full(t)) => t,
+        };
+
+        self.signal.0.send.register(cx.waker());
+
+        match self.try_send(t) {
+            Ok(()) => Poll::Ready(Ok(())),
+            Err(TrySendError::Disconnected(t)) => Poll::Ready(Err(SendError(t))),
+            Err(TrySendError::Full(t)) => {
+                self.pending = Some(t);
+                self.signal.0.pending(cx)
+            }
+        }
+    }
+}
+
+impl<T> Clone for Sender<T> {
+    fn clone(&self) -> Self {
+        Sender {
+            raw: self.raw.clone(),
+            signal: SenderSignal::new(self.signal.0.clone()),
+            overflow: self.overflow.clone(),
+            #[cfg(feature = "futures")]
+            pending: None,
+        }
+    }
+}
+
+impl<T> Sender<T> {
+    /// Clones this sender without counting it in `sender_count`, to be kept aside
+    /// for upgrading a `WeakChannel` or for scheduled sends. Clones of the returned
+    /// sender are counted.
+    pub(crate) fn clone_uncounted(&self) -> Self {
+        Sender {
+            raw: self.raw.clone(),
+            signal: SenderSignal::uncounted(self.signal.0.clone()),
+            overflow: self.overflow.clone(),
+            #[cfg(feature = "futures")]
+            pending: None,
+        }
+    }
+}
+
+#[derive(Debug)]
+pub struct Receiver<T> {
+    source: Source<T>,
+    signal: ReceiverSignal<T>,
+}
+
+impl<T> Receiver<T> {
+    /// Wakes the sending task, now that `t` has left the buffer.
+    fn taken(&self, t: T) -> T {
+        self.signal.0.send.wake();
+        t
+    }
+
+    /// Counts a message handed to the caller.
+    fn delivered(&self, t: T) -> T {
+        self.signal.0.received.fetch_add(1, Ordering::Relaxed);
+        t
+    }
+
+    fn received(&self, t: T) -> T {
+        self.delivered(self.taken(t))
+    }
+
+    /// Returns the number of messages handed to callers by any receiver of this
+    /// direction.
+    pub(crate) fn received_count(&self) -> u64 {
+        self.signal.0.received.load(Ordering::Relaxed)
+    }
+
+    /// Returns `true` if the raw receiver can be handed out by `into_raw`, which needs
+    /// it to be owned rather than shared with an evicting sender, and no messages to
+    /// have been set aside by `recv_matching`.
+    pub(crate) fn is_plain(&self) -> bool {
+        matches!(self.source, Source::Owned(_)) && self.signal.0.held.len() == 0
+    }
+
+    /// Unwraps the raw receiver. Must only be called if `is_plain` holds.
+    pub(crate) fn into_raw(self) -> RawReceiver<T> {
+        match self.source {
+            Source::Owned(raw) => raw,
+            Source::Shared(_) => unreachable!("shared receivers are not plain"),
+        }
+    }
+
+    pub(crate) fn recv(&self) -> Result<T, RecvError> {
+        if let Some(t) = self.signal.0.held.pop() {
+            return Ok(self.delivered(t));
+        }
+
+        self.source
+            .with(|raw| raw.recv())
+            .map(|t| self.received(t))
+            .map_err(Into::into)
+    }
+
+    pub(crate) fn try_recv(&self) -> Result<T, TryRecvError> {
+        if let Some(t) = self.signal.0.held.pop() {
+            return Ok(self.delivered(t));
+        }
+
+        self.source
+            .with(|raw| raw.try_recv())
+            .map(|t| self.received(t))
+            .map_err(Into::into)
+    }
+
+    pub(crate) fn recv_timeout(&self, timeout: Duration) -> Result<T, RecvTimeoutError> {
+        if let Some(t) = self.signal.0.held.pop() {
+            return Ok(self.delivered(t));
+        }
+
+        self.source
+            .with(|raw| raw.recv_timeout(timeout))
+            .map(|t| self.received(t))
+            .map_err(Into::into)
+    }
+
+    /// `std::sync::mpsc::Receiver::recv_deadline` is unstable, so it is emulated with
+    /// `recv_timeout`.
+    #[cfg(not(any(feature = "crossbeam", feature = "flume")))]
+    pub(crate) fn recv_deadline(&self, deadline: Instant) -> Result<T, RecvTimeoutError> {
+        match self.try_recv() {
+            Ok(t) => Ok(t),
+            Err(TryRecvError::Disconnected) => Err(RecvTimeoutError::Disconnected),
+            Err(TryRecvError::Empty) => match deadline.checked_duration_since(Instant::now()) {
+                Some(timeout) => self.recv_timeout(timeout),
+                None => Err(RecvTimeoutError::Timeout),
+            },
+        }
+    }
+
+    #[cfg(any(feature = "crossbeam", feature = "flume"))]
+    pub(crate) fn recv_deadline(&self, deadline: Instant) -> Result<T, RecvTimeoutError> {
+        if let Some(t) = self.signal.0.held.pop() {
+            return Ok(self.delivered(t));
+        }
+
+        self.source
+            .with(|raw| raw.recv_deadline(deadline))
+            .map(|t| self.received(t))
+            .map_err(Into::into)
+    }
+
+    /// Waits like `recv_deadline` for a deadline from `crate::deadline`, or like
+    /// `recv` if there is none.
+    pub(crate) fn recv_until(&self, deadline: Option<Instant>) -> Result<T, RecvTimeoutError> {
+        match deadline {
+            Some(deadline) => self.recv_deadline(deadline),
+            None => self.recv().map_err(Into::into),
+        }
+    }
+
+    /// Blocks until a message matching `pred` is received, setting aside the messages
+    /// before it so that later receives return them in their original order.
+    pub(crate) fn recv_matching(&self, pred: impl Fn(&T) -> bool) -> Result<T, RecvError> {
+        if let Some(t) = self.signal.0.held.take_matching(&pred) {
+            return Ok(self.delivered(t));
+        }
+
+        loop {
+            let t = self.source.with(|raw| raw.recv()).map(|t| self.taken(t))?;
+            if pred(&t) {
+                return Ok(self.delivered(t));
+            }
+            self.signal.0.held.push(t);
+        }
+    }
+
+    /// Attempts to receive without blocking, registering the task to be woken by the
+    /// next send if nothing is buffered. Returns `None` once the sender has hung up.
+    #[cfg(feature = "futures")]
+    pub(crate) fn poll_recv(&self, cx: &mut Context<'_>) -> Poll<Option<T>> {
+        match self.try_recv() {
+            Ok(t) => return Poll::Ready(Some(t)),
+            Err(TryRecvError::Disconnected) => return Poll::Ready(None),
+            Err(TryRecvError::Empty) => (),
+        }
+
+        self.signal.0.recv.register(cx.waker());
+
+        match self.try_recv() {
+            Ok(t) => Poll::Ready(Some(t)),
+            Err(TryRecvError::Disconnected) => Poll::Ready(None),
+            Err(TryRecvError::Empty) => self.signal.0.pending(cx),
+        }
+    }
+
+    /// Registers a receive operation on this receiver with `select`.
+    #[cfg(feature = "crossbeam")]
+    pub(crate) fn select<'a>(&'a self, select: &mut crossbeam_channel::Select<'a>) -> usize {
+        select.recv(self.source.raw())
+    }
+
+    /// Completes a receive operation on this receiver chosen by a `Select`.
+    #[cfg(feature = "crossbeam")]
+    pub(crate) fn complete(
+        &self,
+        operation: crossbeam_channel::SelectedOperation<'_>,
+    ) -> Result<T, RecvError> {
+        let received = operation.recv(self.source.raw()).map(|t| self.taken(t));
+
+        // Messages set aside by `recv_matching` stay ahead of the one just selected.
+        match (self.signal.0.held.pop(), received) {
+            (Some(held), Ok(t)) => {
+                self.signal.0.held.push(t);
+                Ok(self.delivered(held))
+            }
+            (Some(held), Err(_)) => Ok(self.delivered(held)),
+            (None, received) => received.map(|t| self.delivered(t)).map_err(Into::into),
+        }
+    }
+
+    #[cfg(any(feature = "crossbeam", feature = "flume"))]
+    pub(crate) fn len(&self) -> usize {
+        self.signal.0.held.len() + self.source.raw().len()
+    }
+
+    /// Returns the number of live receivers of this direction, including this one.
+    pub(crate) fn receiver_count(&self) -> usize {
+        self.signal.0.receivers.load(Ordering::Relaxed)
+    }
+
+    /// Returns the deepest the buffer has been, as observed after each send.
+    #[cfg(any(feature = "crossbeam", feature = "flume"))]
+    pub(crate) fn high_water_mark(&self) -> usize {
+        self.signal.0.high_water_mark.load(Ordering::Relaxed)
+    }
+
+    #[cfg(any(feature = "crossbeam", feature = "flume"))]
+    pub(crate) fn is_empty(&self) -> bool {
+        self.signal.0.held.len() == 0 && self.source.raw().is_empty()
+    }
+}
+
+#[cfg(any(feature = "crossbeam", feature = "flume"))]
+impl<T> Clone for Receiver<T> {
+    fn clone(&self) -> Self {
+        Receiver {
+            source: self.source.clone(),
+            signal: ReceiverSignal::new(self.signal.0.clone()),
+        }
+    }
+}
+
+#[cfg(any(feature = "crossbeam", feature = "flume"))]
+impl<T> Receiver<T> {
+    /// Clones this receiver without counting it in `receiver_count`, as with
+    /// `Sender::clone_uncounted`.
+    pub(crate) fn clone_uncounted(&self) -> Self {
+        Receiver {
+            source: self.source.clone(),
+            signal: ReceiverSignal::uncounted(self.signal.0.clone()),
+        }
+    }
+}
+
+fn wrap<T>(
+    raw: RawSender<T>,
+    source: Source<T>,
+    overflow: Overflow<T>,
+) -> (Sender<T>, Receiver<T>) {
+    let state = Arc::new(State::default());
+
+    (
+        Sender {
+            raw,
+            signal: SenderSignal::new(state.clone()),
+            overflow,
+            #[cfg(feature = "futures")]
+            pending: None,
+        },
+        Receiver {
+            source,
+            signal: ReceiverSignal::new(state),
+        },
+    )
+}
+
+/// Applies `policy` to a bounded channel. Evicting from a zero capacity buffer would
+/// never make room, so `DropOldest` falls back to `DropNewest` there.
+fn wrap_with<T>(
+    raw: RawSender<T>,
+    receiver: RawReceiver<T>,
+    bound: usize,
+    policy: OverflowPolicy,
+) -> (Sender<T>, Receiver<T>) {
+    let overflow = match policy {
+        OverflowPolicy::Block => Overflow::Block,
+        OverflowPolicy::DropNewest => Overflow::DropNewest,
+        OverflowPolicy::DropOldest if bound == 0 => Overflow::DropNewest,
+        OverflowPolicy::Error => Overflow::Error,
+        OverflowPolicy::DropOldest => {
+            #[cfg(not(any(feature = "crossbeam", feature = "flume")))]
+            let shared = Arc::new(Mutex::new(receiver));
+            #[cfg(any(feature = "crossbeam", feature = "flume"))]
+            let shared = Arc::new(receiver);
+
+            let overflow = Overflow::DropOldest(Arc::downgrade(&shared));
+            return wrap(raw, Source::Shared(shared), overflow);
+        }
+    };
+
+    wrap(raw, Source::Owned(receiver), overflow)
+}
+
+#[cfg(not(any(feature = "crossbeam", feature = "flume")))]
+pub(crate) fn unbounded<T>() -> (Sender<T>, Receiver<T>) {
+    let (sender, receiver) = mpsc::channel();
+    wrap(
+        RawSender::Unbounded(sender),
+        Source::Owned(receiver),
+        Overflow::Block,
+    )
+}
+
+#[cfg(not(any(feature = "crossbeam", feature = "flume")))]
+pub(crate) fn bounded_with<T>(bound: usize, policy: OverflowPolicy) -> (Sender<T>, Receiver<T>) {
+    let (sender, receiver) = mpsc::sync_channel(bound);
+    wrap_with(RawSender::Bounded(sender, bound), receiver, bound, policy)
+}
+
+#[cfg(any(feature = "crossbeam", feature = "flume"))]
+pub(crate) fn unbounded<T>() -> (Sender<T>, Receiver<T>) {
+    let (sender, receiver) = raw::unbounded();
+    wrap(sender, Source::Owned(receiver), Overflow::Block)
+}
+
+#[cfg(any(feature = "crossbeam", feature = "flume"))]
+pub(crate) fn bounded_with<T>(bound: usize, policy: OverflowPolicy) -> (Sender<T>, Receiver<T>) {
+    let (sender, receiver) = raw::bounded(bound);
+    wrap_with(sender, receiver, bound, policy)
+}
+
+/// Wraps a raw sender whose receiver was created outside this crate.
+fn adopt_raw_sender<T>(raw: RawSender<T>) -> Sender<T> {
+    Sender {
+        raw,
+        signal: SenderSignal::new(Arc::new(State::detached())),
+        overflow: Overflow::Block,
+        #[cfg(feature = "futures")]
+        pending: None,
+    }
+}
+
+#[cfg(not(any(feature = "crossbeam", feature = "flume")))]
+pub(crate) fn adopt_sender<T>(sender: mpsc::Sender<T>) -> Sender<T> {
+    adopt_raw_sender(RawSender::Unbounded(sender))
+}
+
+#[cfg(any(feature = "crossbeam", feature = "flume"))]
+pub(crate) fn adopt_sender<T>(sender: RawSender<T>) -> Sender<T> {
+    adopt_raw_sender(sender)
+}
+
+/// Wraps a raw receiver whose sender was created outside this crate.
+pub(crate) fn adopt_receiver<T>(receiver: RawReceiver<T>) -> Receiver<T> {
+    Receiver {
+        source: Source::Owned(receiver),
+        signal: ReceiverSignal::new(Arc::new(State::detached())),
+    }
+}
+
+pub(crate) fn bounded<T>(bound: usize) -> (Sender<T>, Receiver<T>) {
+    bounded_with(bound, OverflowPolicy::Block)
+}
@@ -0,0 +1,358 @@
+//! The [`Backend`] trait, abstracting over the channel implementation that backs each
+//! direction of a [`Channel`].
+//!
+//! [`channel`](crate::channel) and the other constructors use [`DefaultBackend`], which
+//! supports the whole API. A side is generic over its backend, so that
+//! [`channel_with`](crate::channel_with) can create one backed by any implementation
+//! of [`Backend`], offering sending, receiving and disconnect detection.
+
+use std::sync::mpsc;
+use std::time::{Duration, Instant};
+
+use crate::{backend, RecvError, RecvTimeoutError, SendError, TryRecvError, TrySendError};
+
+/// A channel implementation that can back each direction of a [`Channel`].
+///
+/// [`Channel`]: crate::Channel
+pub trait Backend {
+    /// The sending end of one direction.
+    type Sender<T>;
+    /// The receiving end of one direction.
+    type Receiver<T>;
+
+    /// Creates one direction, returning its two ends.
+    fn channel<T>() -> (Self::Sender<T>, Self::Receiver<T>);
+
+    /// Sends a value, blocking if the buffer is full, and handing it back if the
+    /// receiver has hung up.
+    fn send<T>(sender: &Self::Sender<T>, t: T) -> Result<(), SendError<T>>;
+
+    /// Sends a value without blocking, handing it back if it could not be sent.
+    fn try_send<T>(sender: &Self::Sender<T>, t: T) -> Result<(), TrySendError<T>>;
+
+    /// Blocks until a value is received, failing once every sender has hung up and the
+    /// buffer is empty.
+    fn recv<T>(receiver: &Self::Receiver<T>) -> Result<T, RecvError>;
+
+    /// Receives a buffered value without blocking.
+    fn try_recv<T>(receiver: &Self::Receiver<T>) -> Result<T, TryRecvError>;
+
+    /// Blocks until a value is received or `timeout` elapses.
+    fn recv_timeout<T>(
+        receiver: &Self::Receiver<T>,
+        timeout: Duration,
+    ) -> Result<T, RecvTimeoutError>;
+
+    /// Blocks until a value is received or `deadline` is reached. By default, waits
+    /// with [`recv_timeout`](Self::recv_timeout) for the time left.
+    fn recv_deadline<T>(
+        receiver: &Self::Receiver<T>,
+        deadline: Instant,
+    ) -> Result<T, RecvTimeoutError> {
+        Self::recv_timeout(receiver, deadline.saturating_duration_since(Instant::now()))
+    }
+}
+
+/// The backend used by [`channel`](crate::channel) and the other constructors of this
+/// crate: `std::sync::mpsc`, or crossbeam with the `crossbeam` feature, or flume with
+/// the `flume` feature alone, along with the bookkeeping the rest of the API relies on.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct DefaultBackend;
+
+impl Backend for DefaultBackend {
+    type Sender<T> = backend::Sender<T>;
+    type Receiver<T> = backend::Receiver<T>;
+
+    fn channel<T>() -> (Self::Sender<T>, Self::Receiver<T>) {
+        backend::unbounded()
+    }
+
+    fn send<T>(sender: &Self::Sender<T>, t: T) -> Result<(), SendError<T>> {
+        sender.send(t)
+    }
+
+    fn try_send<T>(sender: &Self::Sender<T>, t: T) -> Result<(), TrySendError<T>> {
+        sender.try_send(t)
+    }
+
+    fn recv<T>(receiver: &Self::Receiver<T>) -> Result<T, RecvError> {
+        receiver.recv()
+    }
+
+    fn try_recv<T>(receiver: &Self::Receiver<T>) -> Result<T, TryRecvError> {
+        receiver.try_recv()
+    }
+
+    fn recv_timeout<T>(
+        receiver: &Self::Receiver<T>,
+        timeout: Duration,
+    ) -> Result<T, RecvTimeoutError> {
+        receiver.recv_timeout(timeout)
+    }
+
+    fn recv_deadline<T>(
+        receiver: &Self::Receiver<T>,
+        deadline: Instant,
+    ) -> Result<T, RecvTimeoutError> {
+        receiver.recv_deadline(deadline)
+    }
+}
+
+/// A backend of plain, unbounded `std::sync::mpsc` channels, whatever the features.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct StdBackend;
+
+impl Backend for StdBackend {
+    type Sender<T> = mpsc::Sender<T>;
+    type Receiver<T> = mpsc::Receiver<T>;
+
+    fn channel<T>() -> (Self::Sender<T>, Self::Receiver<T>) {
+        mpsc::channel()
+    }
+
+    fn send<T>(sender: &Self::Sender<T>, t: T) -> Result<(), SendError<T>> {
+        sender.send(t).map_err(|mpsc::SendError(t)| SendError(t))
+    }
+
+    fn try_send<T>(sender: &Self::Sender<T>, t: T) -> Result<(), TrySendError<T>> {
+        sender
+            .send(t)
+            .map_err(|mpsc::SendError(t)| TrySendError::Disconnected(t))
+    }
+
+    fn recv<T>(receiver: &Self::Receiver<T>) -> Result<T, RecvError> {
+        receiver.recv().map_err(|_| RecvError)
+    }
+
+    fn try_recv<T>(receiver: &Self::Receiver<T>) -> Result<T, TryRecvError> {
+        receiver.try_recv().map_err(|err| match err {
+            mpsc::TryRecvError::Empty => TryRecvError::Empty,
+            mpsc::TryRecvError::Disconnected => TryRecvError::Disconnected,
+        })
+    }
+
+    fn recv_timeout<T>(
+        receiver: &Self::Receiver<T>,
+        timeout: Duration,
+    ) -> Result<T, RecvTimeoutError> {
+        receiver.recv_timeout(timeout).map_err(|err| match err {
+            mpsc::RecvTimeoutError::Timeout => RecvTimeoutError::Timeout,
+            mpsc::RecvTimeoutError::Disconnected => RecvTimeoutError::Disconnected,
+        })
+    }
+}
+
+/// A backend of plain, unbounded `crossbeam_channel` channels, enabled with the
+/// `crossbeam` feature.
+#[cfg(feature = "crossbeam")]
+#[derive(Debug, Clone, Copy, Default)]
+pub struct CrossbeamBackend;
+
+#[cfg(feature = "crossbeam")]
+impl Backend for CrossbeamBackend {
+    type Sender<T> = crossbeam_channel::Sender<T>;
+    type Receiver<T> = crossbeam_channel::Receiver<T>;
+
+    fn channel<T>() -> (Self::Sender<T>, Self::Receiver<T>) {
+        crossbeam_channel::unbounded()
+    }
+
+    fn send<T>(sender: &Self::Sender<T>, t: T) -> Result<(), SendError<T>> {
+        sender.send(t).map_err(Into::into)
+    }
+
+    fn try_send<T>(sender: &Self::Sender<T>, t: T) -> Result<(), TrySendError<T>> {
+        sender.try_send(t).map_err(Into::into)
+    }
+
+    fn recv<T>(receiver: &Self::Receiver<T>) -> Result<T, RecvError> {
+        receiver.recv().map_err(Into::into)
+    }
+
+    fn try_recv<T>(receiver: &Self::Receiver<T>) -> Result<T, TryRecvError> {
+        receiver.try_recv().map_err(Into::into)
+    }
+
+    fn recv_timeout<T>(
+        receiver: &Self::Receiver<T>,
+        timeout: Duration,
+    ) -> Result<T, RecvTimeoutError> {
+        receiver.recv_timeout(timeout).map_err(Into::into)
+    }
+
+    fn recv_deadline<T>(
+        receiver: &Self::Receiver<T>,
+        deadline: Instant,
+    ) -> Result<T, RecvTimeoutError> {
+        receiver.recv_deadline(deadline).map_err(Into::into)
+    }
+}
+
+/// A backend of unbounded `flume` channels, enabled with the `flume` feature.
+///
+/// # Examples
+///
+/// ```
+/// use bichannel::FlumeBackend;
+///
+/// let (left, right) = bichannel::channel_with::<FlumeBackend, &str, usize>();
+///
+/// left.send("ping").unwrap();
+/// assert_eq!(right.recv(), Ok("ping"));
+/// ```
+#[cfg(feature = "flume")]
+#[derive(Debug, Clone, Copy, Default)]
+pub struct FlumeBackend;
+
+#[cfg(feature = "flume")]
+impl Backend for FlumeBackend {
+    type Sender<T> = flume::Sender<T>;
+    type Receiver<T> = flume::Receiver<T>;
+
+    fn channel<T>() -> (Self::Sender<T>, Self::Receiver<T>) {
+        flume::unbounded()
+    }
+
+    fn send<T>(sender: &Self::Sender<T>, t: T) -> Result<(), SendError<T>> {
+        sender.send(t).map_err(Into::into)
+    }
+
+    fn try_send<T>(sender: &Self::Sender<T>, t: T) -> Result<(), TrySendError<T>> {
+        sender.try_send(t).map_err(Into::into)
+    }
+
+    fn recv<T>(receiver: &Self::Receiver<T>) -> Result<T, RecvError> {
+        receiver.recv().map_err(Into::into)
+    }
+
+    fn try_recv<T>(receiver: &Self::Receiver<T>) -> Result<T, TryRecvError> {
+        receiver.try_recv().map_err(Into::into)
+    }
+
+    fn recv_timeout<T>(
+        receiver: &Self::Receiver<T>,
+        timeout: Duration,
+    ) -> Result<T, RecvTimeoutError> {
+        receiver.recv_timeout(timeout).map_err(Into::into)
+    }
+
+    fn recv_deadline<T>(
+        receiver: &Self::Receiver<T>,
+        deadline: Instant,
+    ) -> Result<T, RecvTimeoutError> {
+        receiver.recv_deadline(deadline).map_err(Into::into)
+    }
+}
+
+// Without crossbeam, flume backs `DefaultBackend` and its errors are converted along
+// with the other raw errors in `backend`.
+#[cfg(all(feature = "flume", feature = "crossbeam"))]
+impl<T> From<flume::SendError<T>> for SendError<T> {
+    fn from(flume::SendError(t): flume::SendError<T>) -> Self {
+        SendError(t)
+    }
+}
+
+#[cfg(all(feature = "flume", feature = "crossbeam"))]
+impl From<flume::RecvError> for RecvError {
+    fn from(_: flume::RecvError) -> Self {
+        RecvError
+    }
+}
+
+#[cfg(all(feature = "flume", feature = "crossbeam"))]
+impl<T> From<flume::TrySendError<T>> for TrySendError<T> {
+    fn from(err: flume::TrySendError<T>) -> Self {
+        match err {
+            flume::TrySendError::Full(t) => TrySendError::Full(t),
+            flume::TrySendError::Disconnected(t) => TrySendError::Disconnected(t),
+        }
+    }
+}
+
+#[cfg(all(feature = "flume", feature = "crossbeam"))]
+impl From<flume::TryRecvError> for TryRecvError {
+    fn from(err: flume::TryRecvError) -> Self {
+        match err {
+            flume::TryRecvError::Empty => TryRecvError::Empty,
+            flume::TryRecvError::Disconnected => TryRecvError::Disconnected,
+        }
+    }
+}
+
+#[cfg(all(feature = "flume", feature = "crossbeam"))]
+impl From<flume::RecvTimeoutError> for RecvTimeoutError {
+    fn from(err: flume::RecvTimeoutError) -> Self {
+        match err {
+            flume::RecvTimeoutError::Timeout => RecvTimeoutError::Timeout,
+            flume::RecvTimeoutError::Disconnected => RecvTimeoutError::Disconnected,
+        }
+    }
+}
+
+#[cfg(test)]
+mod examples {
+    use std::time::Duration;
+
+    use crate::{Backend, RecvError, RecvTimeoutError, TryRecvError};
+
+    fn exchange<B: Backend>() {
+        let (left, right) = crate::channel_with::<B, &str, usize>();
+
+        assert_eq!(left.try_recv(), Err(TryRecvError::Empty));
+        assert_eq!(
+            left.recv_timeout(Duration::from_millis(1)),
+            Err(RecvTimeoutError::Timeout)
+        );
+        left.send("four").unwrap();
+        let request = right.recv().unwrap();
+        right.try_send(request.len()).unwrap();
+        assert_eq!(left.recv(), Ok(4));
+
+        // Messages sent before hanging up are still received, in order.
+        for len in 1..=3 {
+            right.send(len).unwrap();
+        }
+        drop(right);
+        assert!(left.is_disconnected());
+        assert_eq!(left.recv(), Ok(1));
+        assert_eq!(left.try_recv(), Ok(2));
+        assert_eq!(left.iter().collect::<Vec<_>>(), [3]);
+        assert_eq!(left.recv(), Err(RecvError));
+        assert!(left.send("gone").is_err());
+    }
+
+    #[test]
+    fn test_default_backend() {
+        exchange::<crate::DefaultBackend>();
+    }
+
+    #[test]
+    fn test_std_backend() {
+        exchange::<crate::StdBackend>();
+    }
+
+    #[test]
+    #[cfg(feature = "crossbeam")]
+    fn test_crossbeam_backend() {
+        exchange::<crate::CrossbeamBackend>();
+    }
+
+    #[test]
+    #[cfg(feature = "flume")]
+    fn test_flume_backend() {
+        exchange::<crate::FlumeBackend>();
+
+        let (left, right) = crate::channel_with::<crate::FlumeBackend, u8, char>();
+        assert_eq!(right.try_recv(), Err(TryRecvError::Empty));
+        left.send(1).unwrap();
+        right.send('a').unwrap();
+        assert_eq!(right.try_recv(), Ok(1));
+        assert_eq!(left.try_recv(), Ok('a'));
+
+        drop(left);
+        assert!(right.is_disconnected());
+        assert_eq!(right.try_recv(), Err(TryRecvError::Disconnected));
+        assert!(right.send('b').is_err());
+    }
+}
@@ -0,0 +1,149 @@
+//! Fanning one producer out to many [`Channel`]s.
+
+use std::fmt;
+use std::iter::FromIterator;
+
+use crate::{Channel, SendError, SendHalf};
+
+/// Sends a copy of every message to each of several channels.
+///
+/// Subscribers are added with [`subscribe`] or [`subscribe_half`]. A subscriber whose
+/// counterpart has hung up is dropped on the next [`send`], and delivery continues to
+/// the rest.
+///
+/// [`subscribe`]: Self::subscribe
+/// [`subscribe_half`]: Self::subscribe_half
+/// [`send`]: Self::send
+///
+/// # Examples
+///
+/// ```
+/// use bichannel::BroadcastSender;
+///
+/// let (a, a_peer) = bichannel::channel::<u8, ()>();
+/// let (b, b_peer) = bichannel::channel::<u8, ()>();
+///
+/// let mut broadcast = BroadcastSender::new();
+/// broadcast.subscribe(&a);
+/// broadcast.subscribe(&b);
+///
+/// assert_eq!(broadcast.send(1), Ok(2));
+/// assert_eq!(a_peer.recv(), Ok(1));
+/// assert_eq!(b_peer.recv(), Ok(1));
+/// ```
+pub struct BroadcastSender<T> {
+    subscribers: Vec<SendHalf<T>>,
+}
+
+impl<T> BroadcastSender<T> {
+    /// Creates a broadcast sender with no subscribers.
+    pub fn new() -> Self {
+        BroadcastSender {
+            subscribers: Vec::new(),
+        }
+    }
+
+    /// Adds the counterpart of `channel` as a subscriber.
+    pub fn subscribe<R>(&mut self, channel: &Channel<T, R>) {
+        self.subscribers.push(channel.clone_sender());
+    }
+
+    /// Adds the counterpart of a sending half as a subscriber.
+    pub fn subscribe_half(&mut self, sender: SendHalf<T>) {
+        self.subscribers.push(sender);
+    }
+
+    /// Returns the number of subscribers, including any that have hung up since the
+    /// last send.
+    pub fn len(&self) -> usize {
+        self.subscribers.len()
+    }
+
+    /// Returns `true` if there are no subscribers.
+    pub fn is_empty(&self) -> bool {
+        self.subscribers.is_empty()
+    }
+}
+
+impl<T: Clone> BroadcastSender<T> {
+    /// Sends a copy of `t` to every subscriber, blocking on each as
+    /// [`Channel::send`] does, and returns how many received it.
+    ///
+    /// Subscribers that have hung up are dropped. An error is only returned, handing
+    /// back the value, if no subscriber received it.
+    pub fn send(&mut self, t: T) -> Result<usize, SendError<T>> {
+        self.subscribers
+            .retain(|subscriber| subscriber.send(t.clone()).is_ok());
+
+        match self.subscribers.len() {
+            0 => Err(SendError(t)),
+            delivered => Ok(delivered),
+        }
+    }
+}
+
+impl<T> Default for BroadcastSender<T> {
+    fn default() -> Self {
+        BroadcastSender::new()
+    }
+}
+
+impl<T> Clone for BroadcastSender<T> {
+    fn clone(&self) -> Self {
+        BroadcastSender {
+            subscribers: self.subscribers.clone(),
+        }
+    }
+}
+
+impl<T> FromIterator<SendHalf<T>> for BroadcastSender<T> {
+    fn from_iter<I: IntoIterator<Item = SendHalf<T>>>(iter: I) -> Self {
+        BroadcastSender {
+            subscribers: iter.into_iter().collect(),
+        }
+    }
+}
+
+impl<T> Extend<SendHalf<T>> for BroadcastSender<T> {
+    fn extend<I: IntoIterator<Item = SendHalf<T>>>(&mut self, iter: I) {
+        self.subscribers.extend(iter);
+    }
+}
+
+impl<T> fmt::Debug for BroadcastSender<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("BroadcastSender")
+            .field("subscribers", &self.subscribers.len())
+            .finish()
+    }
+}
+
+#[cfg(test)]
+mod examples {
+    use crate::BroadcastSender;
+
+    #[test]
+    fn test_skips_disconnected_subscribers() {
+        let (a, a_peer) = crate::channel::<u8, ()>();
+        let (b, b_peer) = crate::channel::<u8, ()>();
+        let (c, c_peer) = crate::channel::<u8, ()>();
+
+        let mut broadcast: BroadcastSender<u8> =
+            vec![a.clone_sender(), b.clone_sender(), c.clone_sender()]
+                .into_iter()
+                .collect();
+        drop((a, b, c));
+
+        assert_eq!(broadcast.send(1), Ok(3));
+        drop(b_peer);
+        assert_eq!(broadcast.send(2), Ok(2));
+        assert_eq!(broadcast.len(), 2);
+
+        assert_eq!(a_peer.try_iter().collect::<Vec<_>>(), [1, 2]);
+        assert_eq!(c_peer.try_iter().collect::<Vec<_>>(), [1, 2]);
+
+        drop((a_peer, c_peer));
+        assert_eq!(broadcast.send(3).unwrap_err().0, 3);
+        assert!(broadcast.is_empty());
+    }
+}
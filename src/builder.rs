@@ -0,0 +1,128 @@
+//! Configuring a [`Channel`] before creating it.
+
+use std::sync::Arc;
+
+use crate::{backend, pair, Channel, OverflowPolicy};
+
+/// Configures and creates a bidirectional channel, as an alternative to the
+/// [`channel`](crate::channel), [`sync_channel`](crate::sync_channel) and
+/// [`bounded_with`](crate::bounded_with) shortcuts.
+///
+/// By default the channel is unbounded and unnamed.
+///
+/// # Examples
+///
+/// ```
+/// use bichannel::{ChannelBuilder, OverflowPolicy};
+///
+/// let (left, right) = ChannelBuilder::new()
+///     .bounded(1)
+///     .overflow(OverflowPolicy::Error)
+///     .name("control")
+///     .build::<u8, u8>();
+///
+/// assert_eq!(left.name(), Some("control"));
+/// left.send(1).unwrap();
+/// assert!(left.send(2).is_err());
+/// assert_eq!(right.recv(), Ok(1));
+/// ```
+#[derive(Debug, Clone)]
+pub struct ChannelBuilder {
+    bound: Option<usize>,
+    overflow: OverflowPolicy,
+    name: Option<Arc<str>>,
+}
+
+impl ChannelBuilder {
+    /// Creates a builder for an unbounded, unnamed channel.
+    pub fn new() -> Self {
+        ChannelBuilder {
+            bound: None,
+            overflow: OverflowPolicy::Block,
+            name: None,
+        }
+    }
+
+    /// Buffers at most `bound` messages in each direction, as [`sync_channel`] does.
+    ///
+    /// [`sync_channel`]: crate::sync_channel
+    pub fn bounded(mut self, bound: usize) -> Self {
+        self.bound = Some(bound);
+        self
+    }
+
+    /// Buffers any number of messages in each direction, as [`channel`] does. This is
+    /// the default.
+    ///
+    /// [`channel`]: crate::channel
+    pub fn unbounded(mut self) -> Self {
+        self.bound = None;
+        self
+    }
+
+    /// Sets what a bounded channel does with a message sent while its buffer is full,
+    /// as [`bounded_with`] does. An unbounded channel is never full, so this has no
+    /// effect on it. Defaults to [`OverflowPolicy::Block`].
+    ///
+    /// [`bounded_with`]: crate::bounded_with
+    pub fn overflow(mut self, policy: OverflowPolicy) -> Self {
+        self.overflow = policy;
+        self
+    }
+
+    /// Names both sides of the channel, see [`Channel::name`].
+    pub fn name(mut self, name: &str) -> Self {
+        self.name = Some(name.into());
+        self
+    }
+
+    /// Creates the channel, returning the left and right sides.
+    pub fn build<T, U>(self) -> (Channel<T, U>, Channel<U, T>) {
+        let (mut left, mut right) = match self.bound {
+            None => pair(backend::unbounded(), backend::unbounded()),
+            Some(bound) => pair(
+                backend::bounded_with(bound, self.overflow),
+                backend::bounded_with(bound, self.overflow),
+            ),
+        };
+
+        left.name = self.name.clone();
+        right.name = self.name;
+        (left, right)
+    }
+}
+
+impl Default for ChannelBuilder {
+    fn default() -> Self {
+        ChannelBuilder::new()
+    }
+}
+
+#[cfg(test)]
+mod examples {
+    use crate::{ChannelBuilder, TrySendError};
+
+    #[test]
+    fn test_named_bounded_pair() {
+        let (left, right) = ChannelBuilder::new()
+            .bounded(2)
+            .name("jobs")
+            .build::<u8, ()>();
+
+        assert_eq!(left.name(), Some("jobs"));
+        assert_eq!(right.name(), Some("jobs"));
+
+        left.send(1).unwrap();
+        left.send(2).unwrap();
+        assert_eq!(left.try_send(3), Err(TrySendError::Full(3)));
+
+        assert_eq!(right.recv(), Ok(1));
+        left.try_send(3).unwrap();
+
+        let (left, _right) = ChannelBuilder::new().build::<u8, ()>();
+        assert_eq!(left.name(), None);
+        for i in 0..100 {
+            left.try_send(i).unwrap();
+        }
+    }
+}
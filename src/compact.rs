@@ -0,0 +1,336 @@
+//! A bidirectional channel whose two directions share a single allocation.
+
+use std::collections::VecDeque;
+use std::fmt;
+use std::sync::{Arc, Condvar, Mutex, MutexGuard, PoisonError};
+use std::time::{Duration, Instant};
+
+use crate::{RecvError, RecvTimeoutError, SendError, TryRecvError, TrySendError};
+
+/// The messages of one direction, along with whether each end of it is still held.
+struct Direction<T> {
+    queue: VecDeque<T>,
+    sender_alive: bool,
+    receiver_alive: bool,
+}
+
+/// One direction, woken whenever a message is sent or either end hangs up.
+struct Queue<T> {
+    direction: Mutex<Direction<T>>,
+    ready: Condvar,
+}
+
+impl<T> Queue<T> {
+    fn new() -> Self {
+        Queue {
+            direction: Mutex::new(Direction {
+                queue: VecDeque::new(),
+                sender_alive: true,
+                receiver_alive: true,
+            }),
+            ready: Condvar::new(),
+        }
+    }
+
+    fn lock(&self) -> MutexGuard<'_, Direction<T>> {
+        self.direction
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+    }
+}
+
+/// Both directions, in the one allocation shared by the two sides.
+struct Shared<T, U> {
+    /// The messages sent by the left side.
+    left: Queue<T>,
+    /// The messages sent by the right side.
+    right: Queue<U>,
+}
+
+/// Which side of the shared allocation a [`CompactChannel`] is, which decides the
+/// queue it sends to and the one it receives from.
+enum End<S, R> {
+    Left(Arc<Shared<S, R>>),
+    Right(Arc<Shared<R, S>>),
+}
+
+/// One side of a bidirectional channel created by [`compact_channel`], backing both
+/// directions with a single allocation.
+///
+/// Creating one costs a single allocation, against the several of
+/// [`channel`](crate::channel), which builds each direction from a separate backend
+/// channel and keeps the bookkeeping the rest of the [`Channel`] API relies on. The
+/// core of that API behaves alike: messages are unbounded and never reordered,
+/// messages sent before the other side hung up are still received, and sending to a
+/// side that hung up hands the value back.
+///
+/// Only `send`, `try_send`, `recv`, `try_recv`, `recv_timeout`, `iter` and
+/// `is_disconnected` are offered. The rest of [`Channel`] is left out because each
+/// part needs state that would cost the allocations this type saves:
+///
+/// - `split`, `into_parts`, `select` and cloning hand out the halves of a direction
+///   on their own, which needs a backend channel per direction.
+/// - The overflow policies need a bound, and the queues here are unbounded.
+/// - `cork`, `with_name` and the statistics keep per-side state that `channel`
+///   allocates alongside each side.
+/// - The async methods and the `Stream` and `Sink` impls need wakers registered for
+///   each direction.
+///
+/// Use [`channel`](crate::channel) when any of these are needed.
+///
+/// [`Channel`]: crate::Channel
+pub struct CompactChannel<S, R> {
+    end: End<S, R>,
+}
+
+impl<S, R> CompactChannel<S, R> {
+    fn outgoing(&self) -> &Queue<S> {
+        match &self.end {
+            End::Left(shared) => &shared.left,
+            End::Right(shared) => &shared.right,
+        }
+    }
+
+    fn incoming(&self) -> &Queue<R> {
+        match &self.end {
+            End::Left(shared) => &shared.right,
+            End::Right(shared) => &shared.left,
+        }
+    }
+
+    /// Sends a value to the other side. See [`Channel::send`](crate::Channel::send).
+    pub fn send(&self, s: S) -> Result<(), SendError<S>> {
+        let outgoing = self.outgoing();
+        let mut direction = outgoing.lock();
+        if !direction.receiver_alive {
+            return Err(SendError(s));
+        }
+
+        direction.queue.push_back(s);
+        drop(direction);
+        outgoing.ready.notify_one();
+        Ok(())
+    }
+
+    /// Sends a value to the other side without blocking, which an unbounded channel
+    /// never does anyway. See [`Channel::try_send`](crate::Channel::try_send).
+    pub fn try_send(&self, s: S) -> Result<(), TrySendError<S>> {
+        self.send(s)
+            .map_err(|SendError(s)| TrySendError::Disconnected(s))
+    }
+
+    /// Waits for a value from the other side. See [`Channel::recv`](crate::Channel::recv).
+    pub fn recv(&self) -> Result<R, RecvError> {
+        self.recv_until(None).map_err(|_| RecvError)
+    }
+
+    /// Returns a pending value from the other side without blocking. See
+    /// [`Channel::try_recv`](crate::Channel::try_recv).
+    pub fn try_recv(&self) -> Result<R, TryRecvError> {
+        let mut direction = self.incoming().lock();
+        match direction.queue.pop_front() {
+            Some(r) => Ok(r),
+            None if direction.sender_alive => Err(TryRecvError::Empty),
+            None => Err(TryRecvError::Disconnected),
+        }
+    }
+
+    /// Waits for a value from the other side for at most `timeout`. See
+    /// [`Channel::recv_timeout`](crate::Channel::recv_timeout).
+    pub fn recv_timeout(&self, timeout: Duration) -> Result<R, RecvTimeoutError> {
+        self.recv_until(crate::deadline(timeout))
+    }
+
+    /// Waits for a value from the other side until `deadline`, or without one if
+    /// `None`.
+    fn recv_until(&self, deadline: Option<Instant>) -> Result<R, RecvTimeoutError> {
+        let incoming = self.incoming();
+        let mut direction = incoming.lock();
+        loop {
+            if let Some(r) = direction.queue.pop_front() {
+                return Ok(r);
+            }
+            if !direction.sender_alive {
+                return Err(RecvTimeoutError::Disconnected);
+            }
+
+            direction = match deadline {
+                Some(deadline) => {
+                    let now = Instant::now();
+                    if now >= deadline {
+                        return Err(RecvTimeoutError::Timeout);
+                    }
+                    incoming
+                        .ready
+                        .wait_timeout(direction, deadline - now)
+                        .unwrap_or_else(PoisonError::into_inner)
+                        .0
+                }
+                None => incoming
+                    .ready
+                    .wait(direction)
+                    .unwrap_or_else(PoisonError::into_inner),
+            };
+        }
+    }
+
+    /// Returns an iterator that waits for values from the other side, ending once it
+    /// has hung up and every value it sent has been received. See
+    /// [`Channel::iter`](crate::Channel::iter).
+    pub fn iter(&self) -> impl Iterator<Item = R> + '_ {
+        std::iter::from_fn(move || self.recv().ok())
+    }
+
+    /// Returns `true` if the other side has hung up. See
+    /// [`Channel::is_disconnected`](crate::Channel::is_disconnected).
+    pub fn is_disconnected(&self) -> bool {
+        !self.incoming().lock().sender_alive
+    }
+}
+
+impl<S, R> Drop for CompactChannel<S, R> {
+    fn drop(&mut self) {
+        let outgoing = self.outgoing();
+        outgoing.lock().sender_alive = false;
+        outgoing.ready.notify_all();
+
+        // Messages that can no longer be received are dropped now, as the backend of
+        // `channel` does, rather than along with the other side. They are dropped
+        // outside the lock, in case dropping one touches the channel.
+        let mut direction = self.incoming().lock();
+        direction.receiver_alive = false;
+        let unreceived = std::mem::take(&mut direction.queue);
+        drop(direction);
+        drop(unreceived);
+    }
+}
+
+impl<S, R> fmt::Debug for CompactChannel<S, R> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("CompactChannel")
+            .field("disconnected", &self.is_disconnected())
+            .finish_non_exhaustive()
+    }
+}
+
+/// Creates a bidirectional channel whose two directions share a single allocation,
+/// returning the left and right sides. See [`CompactChannel`] for how it compares to
+/// [`channel`](crate::channel).
+///
+/// # Examples
+///
+/// ```
+/// use std::thread;
+///
+/// let (left, right) = bichannel::compact_channel::<u32, u32>();
+///
+/// let echo = thread::spawn(move || {
+///     for n in right.iter() {
+///         right.send(n * 2).unwrap();
+///     }
+/// });
+///
+/// left.send(21).unwrap();
+/// assert_eq!(left.recv(), Ok(42));
+///
+/// drop(left);
+/// echo.join().unwrap();
+/// ```
+pub fn compact_channel<T, U>() -> (CompactChannel<T, U>, CompactChannel<U, T>) {
+    let shared = Arc::new(Shared {
+        left: Queue::new(),
+        right: Queue::new(),
+    });
+
+    (
+        CompactChannel {
+            end: End::Left(shared.clone()),
+        },
+        CompactChannel {
+            end: End::Right(shared),
+        },
+    )
+}
+
+#[cfg(test)]
+mod examples {
+    use std::thread;
+    use std::time::Duration;
+
+    use crate::{RecvTimeoutError, SendError, TryRecvError, TrySendError};
+
+    /// Records what each step of the same exchange returns, so that a compact channel
+    /// can be checked against `channel`.
+    macro_rules! exchange {
+        ($constructor:expr) => {{
+            let (left, right) = $constructor();
+            let mut seen = Vec::new();
+
+            seen.push(format!("{:?}", right.try_recv()));
+            seen.push(format!("{:?}", left.recv_timeout(Duration::from_millis(1))));
+            for n in 0..3_u32 {
+                left.send(n).unwrap();
+            }
+            right.try_send("back".to_string()).unwrap();
+            seen.push(format!("{:?}", right.iter().take(3).collect::<Vec<_>>()));
+            seen.push(format!("{:?}", left.recv()));
+
+            left.send(3).unwrap();
+            drop(left);
+            seen.push(format!("{:?}", right.is_disconnected()));
+            seen.push(format!("{:?}", right.recv()));
+            seen.push(format!("{:?}", right.try_recv()));
+            seen.push(format!("{:?}", right.recv_timeout(Duration::from_secs(1))));
+            seen.push(format!("{:?}", right.send("lost".to_string())));
+            seen.push(format!("{:?}", right.try_send("lost".to_string())));
+            seen
+        }};
+    }
+
+    #[test]
+    fn test_behaves_like_channel() {
+        assert_eq!(exchange!(crate::compact_channel), exchange!(crate::channel));
+    }
+
+    #[test]
+    fn test_disconnects() {
+        let (left, right) = crate::compact_channel::<u8, u8>();
+        assert_eq!(left.try_recv(), Err(TryRecvError::Empty));
+        assert!(!left.is_disconnected());
+
+        let waiting = thread::spawn(move || right.recv());
+        thread::sleep(Duration::from_millis(10));
+        drop(left);
+        assert!(waiting.join().unwrap().is_err());
+
+        let (left, right) = crate::compact_channel::<u8, u8>();
+        drop(right);
+        assert!(left.is_disconnected());
+        assert_eq!(left.send(1), Err(SendError(1)));
+        assert_eq!(left.try_send(2), Err(TrySendError::Disconnected(2)));
+        assert_eq!(
+            left.recv_timeout(Duration::from_secs(1)),
+            Err(RecvTimeoutError::Disconnected)
+        );
+    }
+
+    #[test]
+    fn test_round_trips_between_threads() {
+        let (left, right) = crate::compact_channel::<u32, u32>();
+        let echo = thread::spawn(move || {
+            for n in right.iter() {
+                if right.send(n + 1).is_err() {
+                    break;
+                }
+            }
+        });
+
+        for n in 0..1000 {
+            left.send(n).unwrap();
+            assert_eq!(left.recv(), Ok(n + 1));
+        }
+
+        drop(left);
+        echo.join().unwrap();
+    }
+}
@@ -0,0 +1,232 @@
+//! Holding back the sends of a [`Channel`] to release them together.
+
+use std::collections::VecDeque;
+use std::sync::atomic::Ordering;
+use std::sync::PoisonError;
+use std::{iter, mem};
+
+use crate::primitive::{AtomicBool, Mutex, MutexGuard};
+use crate::{Channel, SendError};
+
+/// The values sent by a corked side, waiting to be released by `uncork`.
+///
+/// The flag keeps sending uncorked lock free, and is only changed with the buffer
+/// locked, so that a send which sees it set can queue behind the buffered values.
+#[derive(Debug)]
+pub(crate) struct Cork<S> {
+    corked: AtomicBool,
+    buffer: Mutex<VecDeque<S>>,
+}
+
+impl<S> Default for Cork<S> {
+    fn default() -> Self {
+        Cork {
+            corked: AtomicBool::new(false),
+            buffer: Mutex::new(VecDeque::new()),
+        }
+    }
+}
+
+impl<S> Cork<S> {
+    fn lock(&self) -> MutexGuard<'_, VecDeque<S>> {
+        self.buffer.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+
+    /// Buffers `s` if corked, otherwise hands it back to be sent.
+    pub(crate) fn hold(&self, s: S) -> Option<S> {
+        if !self.corked.load(Ordering::Acquire) {
+            return Some(s);
+        }
+
+        let mut buffer = self.lock();
+        if self.corked.load(Ordering::Acquire) {
+            buffer.push_back(s);
+            None
+        } else {
+            Some(s)
+        }
+    }
+
+    /// Stops buffering, passing each buffered value to `send` in order, and stopping
+    /// at the first failure.
+    ///
+    /// Values are passed to `send` with the buffer unlocked, so a send blocked on a
+    /// full channel holds up no other use of the cork. Values buffered meanwhile are
+    /// released after them, and the flag is only cleared once the buffer is empty.
+    pub(crate) fn release(
+        &self,
+        mut send: impl FnMut(S) -> Result<(), SendError<S>>,
+    ) -> Result<(), SendError<Vec<S>>> {
+        loop {
+            let mut buffer = self.lock();
+            if buffer.is_empty() {
+                self.corked.store(false, Ordering::Release);
+                return Ok(());
+            }
+            let mut batch = mem::take(&mut *buffer);
+            drop(buffer);
+
+            while let Some(s) = batch.pop_front() {
+                if let Err(SendError(s)) = send(s) {
+                    let mut buffer = self.lock();
+                    let unsent = iter::once(s).chain(batch).chain(buffer.drain(..)).collect();
+                    self.corked.store(false, Ordering::Release);
+                    return Err(SendError(unsent));
+                }
+            }
+        }
+    }
+}
+
+impl<S, R> Channel<S, R> {
+    /// Starts holding back values sent from this side, until [`uncork`] releases them.
+    ///
+    /// While corked, values sent through this side, including as a `Sink` with the
+    /// `futures` feature, are buffered and succeed immediately. Those of sending
+    /// halves from [`clone_sender`] and [`split`], and of [`send_after`], are not
+    /// held back. Corking twice has no effect, and clones are not corked along with
+    /// this side. Values still buffered when this side is dropped are discarded.
+    ///
+    /// [`uncork`]: Self::uncork
+    /// [`clone_sender`]: Self::clone_sender
+    /// [`split`]: Self::split
+    /// [`send_after`]: Self::send_after
+    pub fn cork(&self) {
+        let _buffer = self.cork.lock();
+        self.cork.corked.store(true, Ordering::Release);
+    }
+
+    /// Sends every value held back since [`cork`] to the other side, in the order they
+    /// were sent, and stops holding values back.
+    ///
+    /// Uncorking a side that holds nothing back does nothing. If a value cannot be
+    /// sent, it is handed back inside the error along with every value after it.
+    /// Values sent through this side while it is uncorking are released after the
+    /// ones already held back.
+    ///
+    /// [`cork`]: Self::cork
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use bichannel::TryRecvError;
+    ///
+    /// let (left, right) = bichannel::channel::<u8, u8>();
+    ///
+    /// left.cork();
+    /// left.send(1).unwrap();
+    /// left.send(2).unwrap();
+    /// assert_eq!(right.try_recv(), Err(TryRecvError::Empty));
+    ///
+    /// left.uncork().unwrap();
+    /// assert_eq!(right.try_iter().collect::<Vec<_>>(), [1, 2]);
+    /// ```
+    pub fn uncork(&self) -> Result<(), SendError<Vec<S>>> {
+        self.cork.release(|s| self.sender.send(s))
+    }
+}
+
+#[cfg(test)]
+mod examples {
+    use crate::{SendError, TryRecvError};
+
+    #[test]
+    fn test_cork_releases_in_order() {
+        let (left, right) = crate::channel::<u8, u8>();
+
+        left.uncork().unwrap();
+
+        left.cork();
+        left.send(1).unwrap();
+        left.try_send(2).unwrap();
+        left.send_all(vec![3]).unwrap();
+        assert_eq!(right.try_recv(), Err(TryRecvError::Empty));
+
+        left.uncork().unwrap();
+        left.send(4).unwrap();
+        assert_eq!(right.try_iter().collect::<Vec<_>>(), [1, 2, 3, 4]);
+    }
+
+    #[test]
+    fn test_uncork_hands_back_unsent() {
+        let (left, right) = crate::channel::<u8, u8>();
+
+        left.cork();
+        left.send_all(vec![1, 2, 3]).unwrap();
+        drop(right);
+
+        assert_eq!(left.uncork(), Err(SendError(vec![1, 2, 3])));
+        assert_eq!(left.send(4), Err(SendError(4)));
+    }
+
+    #[test]
+    fn test_split_sends_corked() {
+        let (left, right) = crate::channel::<u8, u8>();
+
+        left.cork();
+        left.send(1).unwrap();
+        let (sender, _receiver) = left.split();
+        sender.send(2).unwrap();
+
+        assert_eq!(right.try_iter().collect::<Vec<_>>(), [1, 2]);
+    }
+
+    #[test]
+    #[cfg(any(feature = "crossbeam", feature = "flume"))]
+    fn test_sends_while_uncorking_a_full_channel() {
+        use std::sync::Arc;
+        use std::thread;
+        use std::time::Duration;
+
+        let (left, right) = crate::sync_channel::<u8, u8>(1);
+        let left = Arc::new(left);
+
+        left.cork();
+        left.send_all(vec![1, 2, 3]).unwrap();
+        let uncorking = thread::spawn({
+            let left = left.clone();
+            move || left.uncork()
+        });
+
+        // The uncork is blocked on the full channel, which must not block this send.
+        thread::sleep(Duration::from_millis(20));
+        left.send(4).unwrap();
+
+        assert_eq!(right.iter().take(4).collect::<Vec<_>>(), [1, 2, 3, 4]);
+        uncorking.join().unwrap().unwrap();
+    }
+
+    #[test]
+    #[cfg(loom)]
+    fn test_loom_holds_sends_behind_uncorking() {
+        use loom::sync::{Arc, Mutex};
+        use loom::thread;
+        use std::sync::atomic::Ordering;
+
+        use super::Cork;
+
+        loom::model(|| {
+            let cork = Arc::new(Cork::default());
+            cork.corked.store(true, Ordering::Release);
+            assert_eq!(cork.hold(1), None);
+
+            let sent = Arc::new(Mutex::new(Vec::new()));
+            let uncorking = {
+                let (cork, sent) = (cork.clone(), sent.clone());
+                thread::spawn(move || {
+                    cork.release(|s| {
+                        sent.lock().unwrap().push(s);
+                        Ok(())
+                    })
+                })
+            };
+
+            if let Some(s) = cork.hold(2) {
+                sent.lock().unwrap().push(s);
+            }
+            uncorking.join().unwrap().unwrap();
+
+            assert_eq!(*sent.lock().unwrap(), [1, 2]);
+        });
+    }
+}
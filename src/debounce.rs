@@ -0,0 +1,265 @@
+//! Coalescing bursts of sends on a [`Channel`].
+
+use std::fmt;
+use std::sync::{Arc, Mutex, MutexGuard, PoisonError};
+use std::thread;
+use std::time::Duration;
+
+use crate::backend::Receiver;
+use crate::{Channel, RecvError, RecvTimeoutError, SendError, TryRecvError};
+
+/// The value waiting to be delivered, tagged with the send that stored it.
+struct Pending<S> {
+    generation: u64,
+    value: Option<S>,
+}
+
+/// One side of a bidirectional channel that only delivers the last value of a burst of
+/// sends, created by [`Channel::debounce`].
+///
+/// Each send replaces the pending value and restarts the quiet period, so the value is
+/// delivered once no other value has been sent for that long. Delivery happens on the
+/// timer thread shared with [`Channel::send_after`], through [`Channel::try_send`], so
+/// a value that finds a bounded buffer full is discarded rather than waiting for room.
+/// A value still pending when this side is dropped is discarded. Receiving is
+/// unaffected, but as the timer thread shares the channel, it is done here rather than
+/// by borrowing the channel.
+pub struct Debounced<S, R> {
+    /// The side, sent through by the timer thread. Its receiving half is moved out to
+    /// `receiver`, so that a blocking receive never holds the lock.
+    channel: Arc<Mutex<Channel<S, R>>>,
+    receiver: Receiver<R>,
+    quiet: Duration,
+    pending: Arc<Mutex<Pending<S>>>,
+}
+
+fn lock<T>(mutex: &Mutex<T>) -> MutexGuard<'_, T> {
+    mutex.lock().unwrap_or_else(PoisonError::into_inner)
+}
+
+impl<S: Send + 'static, R: Send + 'static> Debounced<S, R> {
+    /// Replaces the pending value with `s`, delivering it to the other side once the
+    /// quiet period passes without another send.
+    ///
+    /// Fails, handing back the value, if the other side has already hung up. A hang up
+    /// during the quiet period silently discards the value.
+    pub fn send(&self, s: S) -> Result<(), SendError<S>> {
+        if lock(&self.channel).is_disconnected() {
+            return Err(SendError(s));
+        }
+
+        let generation = {
+            let mut pending = lock(&self.pending);
+            pending.generation += 1;
+            pending.value = Some(s);
+            pending.generation
+        };
+
+        let deadline = match crate::deadline(self.quiet) {
+            Some(deadline) => deadline,
+            None => return Ok(()),
+        };
+        let channel = Arc::downgrade(&self.channel);
+        let pending = Arc::downgrade(&self.pending);
+
+        crate::timer::schedule(deadline, move || {
+            let (pending, channel) = match (pending.upgrade(), channel.upgrade()) {
+                (Some(pending), Some(channel)) => (pending, channel),
+                _ => return,
+            };
+
+            let value = {
+                let mut pending = lock(&pending);
+                if pending.generation != generation {
+                    return;
+                }
+                pending.value.take()
+            };
+            if let Some(value) = value {
+                let _ = lock(&channel).try_send(value);
+            }
+        });
+
+        Ok(())
+    }
+}
+
+impl<S, R> Debounced<S, R> {
+    /// Runs the callbacks of the side, as it would had it observed the hang up itself.
+    fn disconnected(&self) {
+        lock(&self.channel).on_disconnect.fire();
+    }
+
+    /// Blocks until a value is received from the other side.
+    pub fn recv(&self) -> Result<R, RecvError> {
+        let result = self.receiver.recv();
+        if result.is_err() {
+            self.disconnected();
+        }
+        #[cfg(feature = "tracing")]
+        tracing::trace!(
+            channel = lock(&self.channel).name.as_deref(),
+            ok = result.is_ok(),
+            "recv"
+        );
+        result
+    }
+
+    /// Returns a value already received from the other side, without blocking.
+    pub fn try_recv(&self) -> Result<R, TryRecvError> {
+        let result = self.receiver.try_recv();
+        if let Err(TryRecvError::Disconnected) = result {
+            self.disconnected();
+        }
+        result
+    }
+
+    /// Waits for at most `timeout` for a value from the other side.
+    pub fn recv_timeout(&self, timeout: Duration) -> Result<R, RecvTimeoutError> {
+        let result = self.receiver.recv_timeout(timeout);
+        if let Err(RecvTimeoutError::Disconnected) = result {
+            self.disconnected();
+        }
+        result
+    }
+
+    /// Unwraps this adapter, returning the underlying channel along with the value
+    /// waiting out the quiet period, if any. That value will no longer be delivered.
+    pub fn into_inner(self) -> (Channel<S, R>, Option<S>) {
+        let value = lock(&self.pending).value.take();
+
+        let mut shared = self.channel;
+        let mut channel = loop {
+            match Arc::try_unwrap(shared) {
+                Ok(channel) => break channel.into_inner().unwrap_or_else(PoisonError::into_inner),
+                // A delivery on the timer thread is about to let go of the side.
+                Err(still_shared) => {
+                    shared = still_shared;
+                    thread::yield_now();
+                }
+            }
+        };
+        channel.receiver = self.receiver;
+        (channel, value)
+    }
+}
+
+impl<S, R> fmt::Debug for Debounced<S, R> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Debounced")
+            .field("channel", &*lock(&self.channel))
+            .field("quiet", &self.quiet)
+            .finish_non_exhaustive()
+    }
+}
+
+impl<S, R> Channel<S, R> {
+    /// Returns an adapter that delivers only the last value of each burst of sends,
+    /// once `quiet` has passed without another send.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::time::Duration;
+    ///
+    /// let (left, right) = bichannel::channel::<&str, ()>();
+    /// let left = left.debounce(Duration::from_millis(20));
+    ///
+    /// left.send("h").unwrap();
+    /// left.send("he").unwrap();
+    /// left.send("hello").unwrap();
+    ///
+    /// assert_eq!(right.recv(), Ok("hello"));
+    /// assert!(right.recv_timeout(Duration::from_millis(50)).is_err());
+    /// ```
+    pub fn debounce(mut self, quiet: Duration) -> Debounced<S, R> {
+        let (_, closed) = crate::backend::unbounded();
+        let receiver = std::mem::replace(&mut self.receiver, closed);
+
+        Debounced {
+            channel: Arc::new(Mutex::new(self)),
+            receiver,
+            quiet,
+            pending: Arc::new(Mutex::new(Pending {
+                generation: 0,
+                value: None,
+            })),
+        }
+    }
+}
+
+#[cfg(test)]
+mod examples {
+    use std::thread;
+    use std::time::Duration;
+
+    use crate::{RecvError, TryRecvError};
+
+    #[test]
+    fn test_delivers_last_of_burst() {
+        let (left, right) = crate::channel::<u8, ()>();
+        let left = left.debounce(Duration::from_millis(50));
+
+        for i in 0..5 {
+            left.send(i).unwrap();
+            thread::sleep(Duration::from_millis(10));
+        }
+        assert_eq!(right.try_recv(), Err(TryRecvError::Empty));
+
+        assert_eq!(right.recv(), Ok(4));
+        thread::sleep(Duration::from_millis(100));
+        assert_eq!(right.try_recv(), Err(TryRecvError::Empty));
+    }
+
+    #[test]
+    fn test_drop_discards_pending() {
+        let (left, right) = crate::channel::<u8, ()>();
+        let left = left.debounce(Duration::from_millis(20));
+
+        left.send(1).unwrap();
+        drop(left);
+
+        assert_eq!(right.recv(), Err(RecvError));
+    }
+
+    #[test]
+    fn test_full_buffer_does_not_block() {
+        let (left, right) = crate::sync_channel::<u8, ()>(1);
+        left.send(1).unwrap();
+        let left = left.debounce(Duration::from_millis(10));
+
+        // The delivery finds the buffer full, and must hold up neither the timer thread
+        // nor later sends, so the value is discarded.
+        left.send(2).unwrap();
+        let (other, other_right) = crate::channel::<u8, ()>();
+        other.send_after(3, Duration::from_millis(20)).unwrap();
+        assert_eq!(other_right.recv_timeout(Duration::from_secs(1)), Ok(3));
+
+        assert_eq!(right.recv(), Ok(1));
+        left.send(4).unwrap();
+        assert_eq!(right.recv_timeout(Duration::from_secs(1)), Ok(4));
+        assert_eq!(right.try_recv(), Err(TryRecvError::Empty));
+    }
+
+    #[test]
+    fn test_delivery_respects_cork() {
+        let (left, right) = crate::channel::<u8, u8>();
+        left.cork();
+        let left = left.debounce(Duration::from_millis(10));
+
+        left.send(1).unwrap();
+        thread::sleep(Duration::from_millis(50));
+        assert_eq!(right.try_recv(), Err(TryRecvError::Empty));
+
+        right.send(2).unwrap();
+        assert_eq!(left.recv(), Ok(2));
+
+        let (left, pending) = left.into_inner();
+        assert_eq!(pending, None);
+        left.uncork().unwrap();
+        assert_eq!(right.recv(), Ok(1));
+
+        right.send(3).unwrap();
+        assert_eq!(left.recv(), Ok(3));
+    }
+}
@@ -0,0 +1,115 @@
+//! Suppressing repeated sends on a [`Channel`].
+
+use crate::{Channel, SendError};
+use std::fmt;
+use std::sync::{Mutex, PoisonError};
+
+/// One side of a bidirectional channel that skips sending a value equal to the one
+/// sent just before it, created by [`Channel::dedup`].
+///
+/// Only consecutive duplicates are skipped: a value that differs from the previous one
+/// is sent even if it was sent earlier. Receiving is unaffected, and is done through
+/// [`get_ref`](Self::get_ref).
+pub struct Dedup<S, R> {
+    channel: Channel<S, R>,
+    last: Mutex<Option<S>>,
+}
+
+impl<S: PartialEq + Clone, R> Dedup<S, R> {
+    /// Sends a value to the other side, unless it equals the value last sent.
+    ///
+    /// A skipped value returns `Ok`, just as if it had been sent. A failed send is not
+    /// remembered, so the value is not considered a duplicate of itself if retried.
+    pub fn send(&self, s: S) -> Result<(), SendError<S>> {
+        let mut last = self.last.lock().unwrap_or_else(PoisonError::into_inner);
+        if last.as_ref() == Some(&s) {
+            return Ok(());
+        }
+
+        self.channel.send(s.clone())?;
+        *last = Some(s);
+        Ok(())
+    }
+}
+
+impl<S, R> Dedup<S, R> {
+    /// Returns a reference to the underlying channel, which receives as usual.
+    ///
+    /// Values sent through it are neither checked nor remembered as the value last
+    /// sent.
+    pub fn get_ref(&self) -> &Channel<S, R> {
+        &self.channel
+    }
+
+    /// Returns a mutable reference to the underlying channel.
+    ///
+    /// Values sent through it are neither checked nor remembered as the value last
+    /// sent.
+    pub fn get_mut(&mut self) -> &mut Channel<S, R> {
+        &mut self.channel
+    }
+
+    /// Unwraps this adapter, returning the underlying channel.
+    pub fn into_inner(self) -> Channel<S, R> {
+        self.channel
+    }
+}
+
+impl<S, R> fmt::Debug for Dedup<S, R> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Dedup")
+            .field("channel", &self.channel)
+            .finish_non_exhaustive()
+    }
+}
+
+impl<S: PartialEq + Clone, R> Channel<S, R> {
+    /// Returns an adapter that skips sending a value equal to the one sent just before
+    /// it.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// let (left, right) = bichannel::channel::<u8, ()>();
+    /// let left = left.dedup();
+    ///
+    /// for value in [1, 1, 2, 2, 1] {
+    ///     left.send(value).unwrap();
+    /// }
+    ///
+    /// assert_eq!(right.try_iter().collect::<Vec<_>>(), [1, 2, 1]);
+    /// ```
+    pub fn dedup(self) -> Dedup<S, R> {
+        Dedup {
+            channel: self,
+            last: Mutex::new(None),
+        }
+    }
+}
+
+#[cfg(test)]
+mod examples {
+    use crate::SendError;
+
+    #[test]
+    fn test_skips_consecutive_duplicates() {
+        let (left, right) = crate::channel::<&str, ()>();
+        let left = left.dedup();
+
+        for value in ["a", "a", "b", "a", "a", "a", "c", "c"] {
+            left.send(value).unwrap();
+        }
+
+        assert_eq!(right.try_iter().collect::<Vec<_>>(), ["a", "b", "a", "c"]);
+    }
+
+    #[test]
+    fn test_failed_send_is_not_remembered() {
+        let (left, right) = crate::channel::<u8, ()>();
+        let left = left.dedup();
+        drop(right);
+
+        assert_eq!(left.send(1), Err(SendError(1)));
+        assert_eq!(left.send(1), Err(SendError(1)));
+    }
+}
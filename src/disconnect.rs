@@ -0,0 +1,256 @@
+//! Running callbacks once a [`Channel`] observes that its counterpart has hung up.
+
+use std::fmt;
+use std::sync::PoisonError;
+
+use crate::primitive::Mutex;
+use crate::{Backend, Channel};
+
+type Callback = Box<dyn FnOnce() + Send>;
+
+/// The callbacks registered on one handle by `on_disconnect`, taken and run by the
+/// first operation on it that fails because the other side has hung up.
+#[derive(Default)]
+pub(crate) struct OnDisconnect {
+    callbacks: Mutex<Vec<Callback>>,
+}
+
+impl fmt::Debug for OnDisconnect {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("OnDisconnect").finish_non_exhaustive()
+    }
+}
+
+impl OnDisconnect {
+    fn register(&self, f: Callback) {
+        self.callbacks
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .push(f);
+    }
+
+    /// Runs the registered callbacks, in the order they were registered, outside the
+    /// lock so they may register more.
+    pub(crate) fn fire(&self) {
+        let callbacks = std::mem::take(
+            &mut *self
+                .callbacks
+                .lock()
+                .unwrap_or_else(PoisonError::into_inner),
+        );
+        for f in callbacks {
+            f();
+        }
+    }
+}
+
+impl<S, R, B: Backend> Channel<S, R, B> {
+    /// Registers `f` to run once this side observes that the other side has hung up.
+    ///
+    /// Hanging up is only observed by an operation, so rather than the moment the
+    /// other side goes, `f` runs during the first send or receive on this handle to
+    /// fail because of it, on the thread calling it, including those of [`iter`],
+    /// [`try_iter`], [`into_iter`] and [`close_with`], and, with the `futures` feature,
+    /// polls of `poll_recv` and the side's `Stream` that find it has ended. Halves
+    /// split off this side do not run it. Receiving still returns any messages
+    /// buffered before the other side hung up, so `f` runs once they have all been
+    /// received. A send failing on a full buffer, as with [`OverflowPolicy::Error`],
+    /// only runs `f` if the other side has hung up too. If several callbacks are
+    /// registered, they run in order. Clones of this side have callbacks of their own.
+    ///
+    /// [`iter`]: Self::iter
+    /// [`try_iter`]: Self::try_iter
+    /// [`into_iter`]: IntoIterator::into_iter
+    /// [`close_with`]: Self::close_with
+    /// [`OverflowPolicy::Error`]: crate::OverflowPolicy::Error
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::sync::atomic::{AtomicBool, Ordering};
+    /// use std::sync::Arc;
+    ///
+    /// let (left, right) = bichannel::channel::<u8, u8>();
+    ///
+    /// let cleaned_up = Arc::new(AtomicBool::new(false));
+    /// let flag = cleaned_up.clone();
+    /// left.on_disconnect(move || flag.store(true, Ordering::SeqCst));
+    ///
+    /// drop(right);
+    /// assert!(!cleaned_up.load(Ordering::SeqCst));
+    ///
+    /// assert!(left.recv().is_err());
+    /// assert!(cleaned_up.load(Ordering::SeqCst));
+    /// ```
+    pub fn on_disconnect<F: FnOnce() + Send + 'static>(&self, f: F) {
+        self.on_disconnect.register(Box::new(f));
+    }
+}
+
+#[cfg(test)]
+mod examples {
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::Arc;
+    use std::time::{Duration, Instant};
+
+    use crate::{OverflowPolicy, RecvTimeoutError, SendError, TryRecvError, TrySendError};
+
+    #[test]
+    fn test_on_disconnect_runs_once() {
+        let (left, right) = crate::channel::<u8, u8>();
+        let runs = Arc::new(AtomicUsize::new(0));
+        let counter = runs.clone();
+        left.on_disconnect(move || {
+            counter.fetch_add(1, Ordering::SeqCst);
+        });
+
+        right.send(1).unwrap();
+        drop(right);
+        assert_eq!(left.try_recv(), Ok(1));
+        assert_eq!(runs.load(Ordering::SeqCst), 0);
+
+        assert!(left.recv().is_err());
+        assert_eq!(left.try_recv(), Err(TryRecvError::Disconnected));
+        assert_eq!(left.send(2), Err(SendError(2)));
+        assert_eq!(runs.load(Ordering::SeqCst), 1);
+    }
+
+    #[test]
+    fn test_on_disconnect_runs_on_try_iter_and_close_with() {
+        let (left, right) = crate::channel::<u8, u8>();
+        let runs = Arc::new(AtomicUsize::new(0));
+        let counter = runs.clone();
+        left.on_disconnect(move || {
+            counter.fetch_add(1, Ordering::SeqCst);
+        });
+
+        right.send(1).unwrap();
+        assert_eq!(left.try_iter().collect::<Vec<_>>(), [1]);
+        assert_eq!(runs.load(Ordering::SeqCst), 0);
+
+        drop(right);
+        assert_eq!(left.try_iter().count(), 0);
+        assert_eq!(runs.load(Ordering::SeqCst), 1);
+
+        let (left, right) = crate::channel::<u8, u8>();
+        let counter = runs.clone();
+        left.on_disconnect(move || {
+            counter.fetch_add(1, Ordering::SeqCst);
+        });
+
+        drop(right);
+        assert_eq!(left.close_with(2), Err(SendError(2)));
+        assert_eq!(runs.load(Ordering::SeqCst), 2);
+    }
+
+    #[test]
+    fn test_on_disconnect_ignores_a_full_buffer() {
+        let (left, right) = crate::bounded_with::<u8, u8>(1, OverflowPolicy::Error);
+        let runs = Arc::new(AtomicUsize::new(0));
+        let counter = runs.clone();
+        left.on_disconnect(move || {
+            counter.fetch_add(1, Ordering::SeqCst);
+        });
+
+        left.send(1).unwrap();
+        assert_eq!(left.send(2), Err(SendError(2)));
+        assert!(!left.is_disconnected());
+        assert_eq!(runs.load(Ordering::SeqCst), 0);
+
+        drop(right);
+        assert_eq!(left.send(3), Err(SendError(3)));
+        assert_eq!(runs.load(Ordering::SeqCst), 1);
+    }
+
+    #[test]
+    fn test_on_disconnect_fires_from_every_receive() {
+        type Receive = fn(&crate::Channel<u8, u8>);
+        let receives: [Receive; 5] = [
+            |side| assert_eq!(side.iter().count(), 1),
+            |side| {
+                let timeout = Duration::from_secs(1);
+                while side.recv_timeout(timeout) != Err(RecvTimeoutError::Disconnected) {}
+            },
+            |side| {
+                let deadline = Instant::now() + Duration::from_secs(1);
+                while side.recv_deadline(deadline) != Err(RecvTimeoutError::Disconnected) {}
+            },
+            |side| {
+                let budget = Duration::from_secs(1);
+                while side.try_recv_for(budget) != Err(TryRecvError::Disconnected) {}
+            },
+            |side| assert!(side.recv_matching(|_| false).is_err()),
+        ];
+
+        for receive in receives {
+            let (left, right) = crate::channel::<u8, u8>();
+            let runs = Arc::new(AtomicUsize::new(0));
+            let counter = runs.clone();
+            left.on_disconnect(move || {
+                counter.fetch_add(1, Ordering::SeqCst);
+            });
+
+            right.send(1).unwrap();
+            drop(right);
+            receive(&left);
+            assert_eq!(runs.load(Ordering::SeqCst), 1);
+        }
+
+        let (left, right) = crate::channel::<u8, u8>();
+        let runs = Arc::new(AtomicUsize::new(0));
+        let counter = runs.clone();
+        left.on_disconnect(move || {
+            counter.fetch_add(1, Ordering::SeqCst);
+        });
+        drop(right);
+        assert_eq!(left.into_iter().count(), 0);
+        assert_eq!(runs.load(Ordering::SeqCst), 1);
+    }
+
+    #[test]
+    fn test_on_disconnect_fires_on_send() {
+        let (left, right) = crate::channel::<u8, u8>();
+        let runs = Arc::new(AtomicUsize::new(0));
+        for _ in 0..2 {
+            let counter = runs.clone();
+            left.on_disconnect(move || {
+                counter.fetch_add(1, Ordering::SeqCst);
+            });
+        }
+
+        drop(right);
+        assert_eq!(left.try_send(1), Err(TrySendError::Disconnected(1)));
+        assert_eq!(runs.load(Ordering::SeqCst), 2);
+    }
+
+    #[test]
+    #[cfg(loom)]
+    fn test_loom_registering_while_firing_runs_once() {
+        use loom::sync::atomic::AtomicUsize;
+        use loom::sync::Arc;
+        use loom::thread;
+
+        use super::OnDisconnect;
+
+        loom::model(|| {
+            let callbacks = Arc::new(OnDisconnect::default());
+            let runs = Arc::new(AtomicUsize::new(0));
+
+            let firing = {
+                let callbacks = callbacks.clone();
+                thread::spawn(move || callbacks.fire())
+            };
+            callbacks.register({
+                let runs = runs.clone();
+                Box::new(move || {
+                    runs.fetch_add(1, Ordering::SeqCst);
+                })
+            });
+            firing.join().unwrap();
+
+            // A callback missed by the first firing runs with the next.
+            callbacks.fire();
+            assert_eq!(runs.load(Ordering::SeqCst), 1);
+        });
+    }
+}
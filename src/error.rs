@@ -0,0 +1,656 @@
+//! Errors returned by the operations of this crate, and an [`Error`] that unifies them
+//! all.
+//!
+//! The errors of the underlying channel operations mirror those of `std::sync::mpsc`,
+//! but are owned by this crate so that they format identically regardless of backend.
+
+use core::error;
+use core::fmt;
+
+/// An error returned from [`Channel::send`], handing back the value that could not be
+/// sent because the other side has hung up.
+///
+/// [`Channel::send`]: crate::Channel::send
+#[derive(PartialEq, Eq, Clone, Copy)]
+pub struct SendError<T>(pub T);
+
+impl<T> SendError<T> {
+    /// Returns the value that could not be sent.
+    pub fn into_inner(self) -> T {
+        self.0
+    }
+}
+
+impl<T> fmt::Debug for SendError<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        "SendError(..)".fmt(f)
+    }
+}
+
+impl<T> fmt::Display for SendError<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        "sending on a closed channel".fmt(f)
+    }
+}
+
+impl<T> error::Error for SendError<T> {}
+
+/// An error returned from [`Channel::recv`] when the other side has hung up and no
+/// more messages can be received.
+///
+/// [`Channel::recv`]: crate::Channel::recv
+#[derive(PartialEq, Eq, Clone, Copy, Debug)]
+pub struct RecvError;
+
+impl fmt::Display for RecvError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        "receiving on a closed channel".fmt(f)
+    }
+}
+
+impl error::Error for RecvError {}
+
+/// An error returned from [`Channel::try_send`], handing back the value that could not
+/// be sent.
+///
+/// [`Channel::try_send`]: crate::Channel::try_send
+#[derive(PartialEq, Eq, Clone, Copy)]
+pub enum TrySendError<T> {
+    /// The value could not be sent because the buffer is full.
+    Full(T),
+    /// The value could not be sent because the other side has hung up.
+    Disconnected(T),
+}
+
+impl<T> TrySendError<T> {
+    /// Returns the value that could not be sent.
+    pub fn into_inner(self) -> T {
+        match self {
+            TrySendError::Full(t) | TrySendError::Disconnected(t) => t,
+        }
+    }
+
+    /// Returns `true` if the send failed because the buffer is full.
+    pub fn is_full(&self) -> bool {
+        matches!(self, TrySendError::Full(_))
+    }
+
+    /// Returns `true` if the send failed because the other side has hung up.
+    pub fn is_disconnected(&self) -> bool {
+        matches!(self, TrySendError::Disconnected(_))
+    }
+}
+
+impl<T> From<SendError<T>> for TrySendError<T> {
+    fn from(SendError(t): SendError<T>) -> Self {
+        TrySendError::Disconnected(t)
+    }
+}
+
+impl<T> fmt::Debug for TrySendError<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            TrySendError::Full(..) => "Full(..)".fmt(f),
+            TrySendError::Disconnected(..) => "Disconnected(..)".fmt(f),
+        }
+    }
+}
+
+impl<T> fmt::Display for TrySendError<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            TrySendError::Full(..) => "sending on a full channel".fmt(f),
+            TrySendError::Disconnected(..) => "sending on a closed channel".fmt(f),
+        }
+    }
+}
+
+impl<T> error::Error for TrySendError<T> {}
+
+/// An error returned from [`Channel::send_timeout`], handing back the value that could
+/// not be sent.
+///
+/// [`Channel::send_timeout`]: crate::Channel::send_timeout
+#[derive(PartialEq, Eq, Clone, Copy)]
+pub enum SendTimeoutError<T> {
+    /// The buffer stayed full until the timeout elapsed.
+    Timeout(T),
+    /// The value could not be sent because the other side has hung up.
+    Disconnected(T),
+}
+
+impl<T> SendTimeoutError<T> {
+    /// Returns the value that could not be sent.
+    pub fn into_inner(self) -> T {
+        match self {
+            SendTimeoutError::Timeout(t) | SendTimeoutError::Disconnected(t) => t,
+        }
+    }
+
+    /// Returns `true` if the send timed out.
+    pub fn is_timeout(&self) -> bool {
+        matches!(self, SendTimeoutError::Timeout(_))
+    }
+
+    /// Returns `true` if the send failed because the other side has hung up.
+    pub fn is_disconnected(&self) -> bool {
+        matches!(self, SendTimeoutError::Disconnected(_))
+    }
+}
+
+impl<T> From<SendError<T>> for SendTimeoutError<T> {
+    fn from(SendError(t): SendError<T>) -> Self {
+        SendTimeoutError::Disconnected(t)
+    }
+}
+
+impl<T> fmt::Debug for SendTimeoutError<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SendTimeoutError::Timeout(..) => "Timeout(..)".fmt(f),
+            SendTimeoutError::Disconnected(..) => "Disconnected(..)".fmt(f),
+        }
+    }
+}
+
+impl<T> fmt::Display for SendTimeoutError<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SendTimeoutError::Timeout(..) => "timed out waiting on send operation".fmt(f),
+            SendTimeoutError::Disconnected(..) => "sending on a closed channel".fmt(f),
+        }
+    }
+}
+
+impl<T> error::Error for SendTimeoutError<T> {}
+
+/// An error returned from [`Channel::try_recv`].
+///
+/// [`Channel::try_recv`]: crate::Channel::try_recv
+#[derive(PartialEq, Eq, Clone, Copy, Debug)]
+pub enum TryRecvError {
+    /// No message is buffered, but the other side may still send one.
+    Empty,
+    /// No message is buffered, and the other side has hung up.
+    Disconnected,
+}
+
+impl TryRecvError {
+    /// Returns `true` if the receive failed because no message is buffered.
+    pub fn is_empty(&self) -> bool {
+        matches!(self, TryRecvError::Empty)
+    }
+
+    /// Returns `true` if the receive failed because the other side has hung up.
+    pub fn is_disconnected(&self) -> bool {
+        matches!(self, TryRecvError::Disconnected)
+    }
+}
+
+impl From<RecvError> for TryRecvError {
+    fn from(_: RecvError) -> Self {
+        TryRecvError::Disconnected
+    }
+}
+
+impl fmt::Display for TryRecvError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            TryRecvError::Empty => "receiving on an empty channel".fmt(f),
+            TryRecvError::Disconnected => "receiving on a closed channel".fmt(f),
+        }
+    }
+}
+
+impl error::Error for TryRecvError {}
+
+/// An error returned from [`Channel::recv_timeout`] and [`Channel::recv_deadline`].
+///
+/// [`Channel::recv_timeout`]: crate::Channel::recv_timeout
+/// [`Channel::recv_deadline`]: crate::Channel::recv_deadline
+#[derive(PartialEq, Eq, Clone, Copy, Debug)]
+pub enum RecvTimeoutError {
+    /// No message arrived before the timeout, but the other side may still send one.
+    Timeout,
+    /// No message is buffered, and the other side has hung up.
+    Disconnected,
+}
+
+impl RecvTimeoutError {
+    /// Returns `true` if the receive timed out.
+    pub fn is_timeout(&self) -> bool {
+        matches!(self, RecvTimeoutError::Timeout)
+    }
+
+    /// Returns `true` if the receive failed because the other side has hung up.
+    pub fn is_disconnected(&self) -> bool {
+        matches!(self, RecvTimeoutError::Disconnected)
+    }
+}
+
+impl From<RecvError> for RecvTimeoutError {
+    fn from(_: RecvError) -> Self {
+        RecvTimeoutError::Disconnected
+    }
+}
+
+/// Reports a timeout as an empty channel, for callers that treat a short wait as a
+/// poll.
+impl From<RecvTimeoutError> for TryRecvError {
+    fn from(err: RecvTimeoutError) -> Self {
+        match err {
+            RecvTimeoutError::Timeout => TryRecvError::Empty,
+            RecvTimeoutError::Disconnected => TryRecvError::Disconnected,
+        }
+    }
+}
+
+impl fmt::Display for RecvTimeoutError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            RecvTimeoutError::Timeout => "timed out waiting on channel".fmt(f),
+            RecvTimeoutError::Disconnected => "channel is empty and sending half is closed".fmt(f),
+        }
+    }
+}
+
+impl error::Error for RecvTimeoutError {}
+
+/// An error returned from [`Select::select_timeout`] when none of the registered
+/// channels became ready in time.
+///
+/// [`Select::select_timeout`]: crate::Select::select_timeout
+#[cfg(feature = "crossbeam")]
+#[derive(PartialEq, Eq, Clone, Copy, Debug)]
+pub struct SelectTimeoutError;
+
+#[cfg(feature = "crossbeam")]
+impl fmt::Display for SelectTimeoutError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        "timed out waiting on select".fmt(f)
+    }
+}
+
+#[cfg(feature = "crossbeam")]
+impl error::Error for SelectTimeoutError {}
+
+/// An error reading a frame whose checksum does not match its payload, from a
+/// transport with [`Transport::checksummed`] enabled.
+///
+/// The transport stops reading at the first such frame, so the local side observes
+/// the other side hanging up rather than receiving a corrupt message. The error is
+/// returned by reads of the underlying frames, wrapped in an [`std::io::Error`] of
+/// kind [`InvalidData`](std::io::ErrorKind::InvalidData).
+///
+/// [`Transport::checksummed`]: crate::transport::Transport::checksummed
+#[cfg(feature = "checksum")]
+#[derive(PartialEq, Eq, Clone, Copy, Debug)]
+pub struct FrameIntegrityError {
+    /// The checksum that was sent along with the frame.
+    pub expected: u32,
+    /// The checksum of the frame, and its length, as it was read.
+    pub found: u32,
+}
+
+#[cfg(feature = "checksum")]
+impl fmt::Display for FrameIntegrityError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "frame checksum mismatch: expected {:#010x}, found {:#010x}",
+            self.expected, self.found
+        )
+    }
+}
+
+#[cfg(feature = "checksum")]
+impl error::Error for FrameIntegrityError {}
+
+/// An error returned from [`Channel::call`].
+///
+/// [`Channel::call`]: crate::Channel::call
+#[derive(PartialEq, Eq, Clone, Copy)]
+pub enum CallError<S> {
+    /// The request could not be sent because the other side has hung up. The request
+    /// is handed back inside the [`SendError`].
+    Send(SendError<S>),
+    /// The request was sent, but the other side hung up before responding.
+    Recv(RecvError),
+}
+
+impl<S> From<SendError<S>> for CallError<S> {
+    fn from(err: SendError<S>) -> Self {
+        CallError::Send(err)
+    }
+}
+
+impl<S> From<RecvError> for CallError<S> {
+    fn from(err: RecvError) -> Self {
+        CallError::Recv(err)
+    }
+}
+
+impl<S> fmt::Debug for CallError<S> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CallError::Send(err) => f.debug_tuple("Send").field(err).finish(),
+            CallError::Recv(err) => f.debug_tuple("Recv").field(err).finish(),
+        }
+    }
+}
+
+impl<S> fmt::Display for CallError<S> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CallError::Send(err) => err.fmt(f),
+            CallError::Recv(err) => err.fmt(f),
+        }
+    }
+}
+
+impl<S> error::Error for CallError<S> {}
+
+/// An error returned from [`Channel::call_timeout`].
+///
+/// [`Channel::call_timeout`]: crate::Channel::call_timeout
+#[derive(PartialEq, Eq, Clone, Copy)]
+pub enum CallTimeoutError<S> {
+    /// The request could not be sent because the other side has hung up. The request
+    /// is handed back inside the [`SendError`].
+    Send(SendError<S>),
+    /// The request was sent, but no response arrived before the timeout.
+    Timeout,
+    /// The request was sent, but the other side hung up before responding.
+    Disconnected,
+}
+
+impl<S> From<SendError<S>> for CallTimeoutError<S> {
+    fn from(err: SendError<S>) -> Self {
+        CallTimeoutError::Send(err)
+    }
+}
+
+impl<S> From<RecvTimeoutError> for CallTimeoutError<S> {
+    fn from(err: RecvTimeoutError) -> Self {
+        match err {
+            RecvTimeoutError::Timeout => CallTimeoutError::Timeout,
+            RecvTimeoutError::Disconnected => CallTimeoutError::Disconnected,
+        }
+    }
+}
+
+impl<S> fmt::Debug for CallTimeoutError<S> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CallTimeoutError::Send(err) => f.debug_tuple("Send").field(err).finish(),
+            CallTimeoutError::Timeout => f.write_str("Timeout"),
+            CallTimeoutError::Disconnected => f.write_str("Disconnected"),
+        }
+    }
+}
+
+impl<S> fmt::Display for CallTimeoutError<S> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CallTimeoutError::Send(err) => err.fmt(f),
+            CallTimeoutError::Timeout => RecvTimeoutError::Timeout.fmt(f),
+            CallTimeoutError::Disconnected => RecvTimeoutError::Disconnected.fmt(f),
+        }
+    }
+}
+
+impl<S> error::Error for CallTimeoutError<S> {}
+
+/// An error returned from [`SeqChannel::recv`].
+///
+/// [`SeqChannel::recv`]: crate::SeqChannel::recv
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum SequenceError {
+    /// A message arrived out of order, and was discarded. Later messages are checked
+    /// against the sequence number following `found`.
+    Gap {
+        /// The sequence number of the message that should have arrived next.
+        expected: u64,
+        /// The sequence number of the message that arrived instead.
+        found: u64,
+    },
+    /// The other side has hung up and no more messages can be received.
+    Disconnected,
+}
+
+impl From<RecvError> for SequenceError {
+    fn from(RecvError: RecvError) -> Self {
+        SequenceError::Disconnected
+    }
+}
+
+impl fmt::Display for SequenceError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SequenceError::Gap { expected, found } => write!(
+                f,
+                "received message {} out of order, expected message {}",
+                found, expected
+            ),
+            SequenceError::Disconnected => RecvError.fmt(f),
+        }
+    }
+}
+
+impl error::Error for SequenceError {}
+
+/// An error returned from [`RateLimited::send`], handing back the value that could not
+/// be sent.
+///
+/// [`RateLimited::send`]: crate::RateLimited::send
+#[derive(PartialEq, Eq, Clone, Copy)]
+pub enum RateLimitError<T> {
+    /// The value was not sent because the rate limit has been reached.
+    RateLimited(T),
+    /// The value could not be sent because the other side has hung up.
+    Disconnected(T),
+}
+
+impl<T> RateLimitError<T> {
+    /// Returns the value that could not be sent.
+    pub fn into_inner(self) -> T {
+        match self {
+            RateLimitError::RateLimited(t) | RateLimitError::Disconnected(t) => t,
+        }
+    }
+}
+
+impl<T> From<SendError<T>> for RateLimitError<T> {
+    fn from(SendError(t): SendError<T>) -> Self {
+        RateLimitError::Disconnected(t)
+    }
+}
+
+impl<T> fmt::Debug for RateLimitError<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            RateLimitError::RateLimited(_) => "RateLimited(..)".fmt(f),
+            RateLimitError::Disconnected(_) => "Disconnected(..)".fmt(f),
+        }
+    }
+}
+
+impl<T> fmt::Display for RateLimitError<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            RateLimitError::RateLimited(_) => "sending faster than the rate limit".fmt(f),
+            RateLimitError::Disconnected(_) => SendError(()).fmt(f),
+        }
+    }
+}
+
+impl<T> error::Error for RateLimitError<T> {}
+
+/// A unified error for every channel operation, regardless of backend.
+///
+/// Each of the operation specific errors converts into it, which allows `?` to be used
+/// uniformly. Converting an error that holds an unsent value drops the value.
+///
+/// # Examples
+///
+/// ```
+/// use bichannel::{Channel, Error};
+///
+/// fn relay(from: &Channel<(), u8>, to: &Channel<u8, ()>) -> Result<(), Error> {
+///     let value = from.try_recv()?;
+///     to.send(value)?;
+///     Ok(())
+/// }
+///
+/// let (from, _from_peer) = bichannel::channel();
+/// let (to, _to_peer) = bichannel::channel();
+///
+/// assert_eq!(relay(&from, &to), Err(Error::Empty));
+/// ```
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum Error {
+    /// The other side has hung up.
+    Disconnected,
+    /// There was no message to receive.
+    Empty,
+    /// There was no room in the buffer to send a message.
+    Full,
+    /// The operation did not complete before its timeout.
+    Timeout,
+}
+
+impl<T> From<SendError<T>> for Error {
+    fn from(_: SendError<T>) -> Self {
+        Error::Disconnected
+    }
+}
+
+impl From<RecvError> for Error {
+    fn from(_: RecvError) -> Self {
+        Error::Disconnected
+    }
+}
+
+impl<T> From<TrySendError<T>> for Error {
+    fn from(err: TrySendError<T>) -> Self {
+        match err {
+            TrySendError::Full(_) => Error::Full,
+            TrySendError::Disconnected(_) => Error::Disconnected,
+        }
+    }
+}
+
+impl<T> From<SendTimeoutError<T>> for Error {
+    fn from(err: SendTimeoutError<T>) -> Self {
+        match err {
+            SendTimeoutError::Timeout(_) => Error::Timeout,
+            SendTimeoutError::Disconnected(_) => Error::Disconnected,
+        }
+    }
+}
+
+impl From<TryRecvError> for Error {
+    fn from(err: TryRecvError) -> Self {
+        match err {
+            TryRecvError::Empty => Error::Empty,
+            TryRecvError::Disconnected => Error::Disconnected,
+        }
+    }
+}
+
+impl From<RecvTimeoutError> for Error {
+    fn from(err: RecvTimeoutError) -> Self {
+        match err {
+            RecvTimeoutError::Timeout => Error::Timeout,
+            RecvTimeoutError::Disconnected => Error::Disconnected,
+        }
+    }
+}
+
+#[cfg(feature = "crossbeam")]
+impl From<SelectTimeoutError> for Error {
+    fn from(_: SelectTimeoutError) -> Self {
+        Error::Timeout
+    }
+}
+
+impl<S> From<CallError<S>> for Error {
+    fn from(_: CallError<S>) -> Self {
+        Error::Disconnected
+    }
+}
+
+impl<S> From<CallTimeoutError<S>> for Error {
+    fn from(err: CallTimeoutError<S>) -> Self {
+        match err {
+            CallTimeoutError::Timeout => Error::Timeout,
+            CallTimeoutError::Send(_) | CallTimeoutError::Disconnected => Error::Disconnected,
+        }
+    }
+}
+
+impl<T> From<RateLimitError<T>> for Error {
+    fn from(err: RateLimitError<T>) -> Self {
+        match err {
+            RateLimitError::RateLimited(_) => Error::Full,
+            RateLimitError::Disconnected(_) => Error::Disconnected,
+        }
+    }
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Error::Disconnected => "channel is disconnected".fmt(f),
+            Error::Empty => "channel is empty".fmt(f),
+            Error::Full => "channel is full".fmt(f),
+            Error::Timeout => "timed out waiting on channel".fmt(f),
+        }
+    }
+}
+
+impl error::Error for Error {}
+
+#[cfg(all(test, feature = "std"))]
+mod examples {
+    use crate::{Error, RecvTimeoutError, TryRecvError, TrySendError};
+
+    #[test]
+    fn test_display_is_backend_independent() {
+        let (left, right) = crate::channel::<u8, u8>();
+        drop(right);
+
+        assert_eq!(
+            left.recv().unwrap_err().to_string(),
+            "receiving on a closed channel"
+        );
+        assert_eq!(
+            left.try_recv().unwrap_err().to_string(),
+            "receiving on a closed channel"
+        );
+        assert_eq!(
+            left.send(1).unwrap_err().to_string(),
+            "sending on a closed channel"
+        );
+        assert_eq!(
+            left.try_send(1).unwrap_err().to_string(),
+            "sending on a closed channel"
+        );
+        assert_eq!(
+            RecvTimeoutError::Disconnected.to_string(),
+            "channel is empty and sending half is closed"
+        );
+    }
+
+    #[test]
+    fn test_error_conversions() {
+        assert_eq!(Error::from(TryRecvError::Empty), Error::Empty);
+        assert_eq!(Error::from(TrySendError::Full(1)), Error::Full);
+
+        let (left, right) = crate::channel::<u8, u8>();
+        drop(right);
+
+        let err: Error = left.send(1).unwrap_err().into();
+        assert_eq!(err, Error::Disconnected);
+    }
+}
@@ -0,0 +1,150 @@
+//! Discarding received values that fail a predicate.
+
+use std::fmt;
+use std::time::Duration;
+
+use crate::{Channel, RecvError, RecvTimeoutError, TryRecvError};
+
+/// One side of a bidirectional channel that discards received values not matching a
+/// predicate, created by [`Channel::filter`].
+///
+/// Unlike [`Channel::recv_matching`], values that do not match are dropped rather than
+/// set aside. Sending is unaffected, and is done through [`get_ref`](Self::get_ref).
+pub struct Filter<S, R, P> {
+    channel: Channel<S, R>,
+    pred: P,
+}
+
+impl<S, R, P> Filter<S, R, P>
+where
+    P: Fn(&R) -> bool,
+{
+    /// Blocks until a value matching the predicate is received, discarding any before
+    /// it. See [`Channel::recv`].
+    ///
+    /// Returns [`Err`] only once the other side has hung up without a match being
+    /// found.
+    pub fn recv(&self) -> Result<R, RecvError> {
+        loop {
+            let r = self.channel.recv()?;
+            if (self.pred)(&r) {
+                return Ok(r);
+            }
+        }
+    }
+
+    /// Returns the first pending value matching the predicate without blocking,
+    /// discarding any before it. See [`Channel::try_recv`].
+    ///
+    /// Returns [`TryRecvError::Empty`] if no pending value matches.
+    pub fn try_recv(&self) -> Result<R, TryRecvError> {
+        loop {
+            let r = self.channel.try_recv()?;
+            if (self.pred)(&r) {
+                return Ok(r);
+            }
+        }
+    }
+
+    /// Waits for at most `timeout` for a value matching the predicate, discarding any
+    /// before it. See [`Channel::recv_timeout`].
+    pub fn recv_timeout(&self, timeout: Duration) -> Result<R, RecvTimeoutError> {
+        let deadline = crate::deadline(timeout);
+        loop {
+            let r = self.channel.recv_until(deadline)?;
+            if (self.pred)(&r) {
+                return Ok(r);
+            }
+        }
+    }
+}
+
+impl<S, R, P> Filter<S, R, P> {
+    /// Returns a reference to the underlying channel, which sends as usual.
+    ///
+    /// Values received through it are not checked against the predicate.
+    pub fn get_ref(&self) -> &Channel<S, R> {
+        &self.channel
+    }
+
+    /// Returns a mutable reference to the underlying channel.
+    ///
+    /// Values received through it are not checked against the predicate.
+    pub fn get_mut(&mut self) -> &mut Channel<S, R> {
+        &mut self.channel
+    }
+
+    /// Unwraps this adapter, returning the underlying channel.
+    pub fn into_inner(self) -> Channel<S, R> {
+        self.channel
+    }
+}
+
+impl<S, R, P> fmt::Debug for Filter<S, R, P>
+where
+    S: fmt::Debug,
+    R: fmt::Debug,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Filter")
+            .field("channel", &self.channel)
+            .finish()
+    }
+}
+
+impl<S, R> Channel<S, R> {
+    /// Returns an adapter that only receives values matching `pred`, dropping the
+    /// rest before they are seen.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// let (left, right) = bichannel::channel::<(), u8>();
+    ///
+    /// let left = left.filter(|n| n % 2 == 0);
+    ///
+    /// right.send_all(vec![1, 3, 4]).unwrap();
+    /// assert_eq!(left.recv(), Ok(4));
+    /// ```
+    pub fn filter<P>(self, pred: P) -> Filter<S, R, P>
+    where
+        P: Fn(&R) -> bool,
+    {
+        Filter {
+            channel: self,
+            pred,
+        }
+    }
+}
+
+#[cfg(test)]
+mod examples {
+    use std::thread;
+    use std::time::Duration;
+
+    use crate::{RecvError, TryRecvError};
+
+    #[test]
+    fn test_filter_evens() {
+        let (consumer, producer) = crate::channel::<(), u32>();
+        let evens = consumer.filter(|n| n % 2 == 0);
+
+        producer.send_all(vec![1, 2, 3, 5]).unwrap();
+        assert_eq!(evens.try_recv(), Ok(2));
+        assert_eq!(evens.try_recv(), Err(TryRecvError::Empty));
+
+        producer.send_all(vec![5, 6]).unwrap();
+        assert_eq!(evens.recv_timeout(Duration::MAX), Ok(6));
+
+        let sender = thread::spawn(move || {
+            producer.send(7).unwrap();
+            thread::sleep(Duration::from_millis(20));
+            producer.send(8).unwrap();
+            producer.send(9).unwrap();
+        });
+
+        assert_eq!(evens.recv(), Ok(8));
+        sender.join().unwrap();
+        assert_eq!(evens.recv(), Err(RecvError));
+    }
+}
@@ -0,0 +1,186 @@
+//! A fixed capacity channel backed by `heapless::spsc` queues, enabled with the
+//! `heapless` feature.
+//!
+//! Nothing here allocates or depends on `std`: the queues and liveness flags live in a
+//! [`HeaplessQueues`] that the caller places, for example in a `static`, and each side
+//! borrows from it. There is no way to block without an operating system, so only the
+//! non-blocking `try_send` and `try_recv` are offered, with the same signatures as on
+//! [`Channel`](crate::Channel).
+
+use core::cell::RefCell;
+use core::fmt;
+use core::sync::atomic::{AtomicBool, Ordering};
+
+use heapless::spsc::{Consumer, Producer, Queue};
+
+use crate::{TryRecvError, TrySendError};
+
+/// The storage shared by both sides of a [`HeaplessChannel`], holding one queue per
+/// direction.
+///
+/// Each queue holds at most `N - 1` messages, and `N` must be at least 2.
+pub struct HeaplessQueues<T, U, const N: usize> {
+    left: Queue<T, N>,
+    right: Queue<U, N>,
+    left_alive: AtomicBool,
+    right_alive: AtomicBool,
+}
+
+impl<T, U, const N: usize> HeaplessQueues<T, U, N> {
+    /// Creates empty queues.
+    pub const fn new() -> Self {
+        HeaplessQueues {
+            left: Queue::new(),
+            right: Queue::new(),
+            left_alive: AtomicBool::new(false),
+            right_alive: AtomicBool::new(false),
+        }
+    }
+
+    /// Splits the storage into the left and right sides of a channel. See
+    /// [`channel_heapless`].
+    pub fn split(&mut self) -> (HeaplessChannel<'_, T, U>, HeaplessChannel<'_, U, T>) {
+        let (left_producer, right_consumer) = self.left.split();
+        let (right_producer, left_consumer) = self.right.split();
+        self.left_alive.store(true, Ordering::Release);
+        self.right_alive.store(true, Ordering::Release);
+
+        (
+            HeaplessChannel {
+                producer: RefCell::new(left_producer),
+                consumer: RefCell::new(left_consumer),
+                alive: &self.left_alive,
+                peer: &self.right_alive,
+            },
+            HeaplessChannel {
+                producer: RefCell::new(right_producer),
+                consumer: RefCell::new(right_consumer),
+                alive: &self.right_alive,
+                peer: &self.left_alive,
+            },
+        )
+    }
+}
+
+impl<T, U, const N: usize> Default for HeaplessQueues<T, U, N> {
+    fn default() -> Self {
+        HeaplessQueues::new()
+    }
+}
+
+impl<T, U, const N: usize> fmt::Debug for HeaplessQueues<T, U, N> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("HeaplessQueues")
+            .field("capacity", &(N - 1))
+            .finish()
+    }
+}
+
+/// One side of a fixed capacity bidirectional channel, created by
+/// [`channel_heapless`].
+pub struct HeaplessChannel<'a, S, R> {
+    producer: RefCell<Producer<'a, S>>,
+    consumer: RefCell<Consumer<'a, R>>,
+    /// Cleared when this side is dropped, so the counterpart can tell it hung up.
+    alive: &'a AtomicBool,
+    peer: &'a AtomicBool,
+}
+
+impl<'a, S, R> HeaplessChannel<'a, S, R> {
+    /// Attempts to send a value to the other side without blocking. See
+    /// [`Channel::try_send`](crate::Channel::try_send).
+    pub fn try_send(&self, s: S) -> Result<(), TrySendError<S>> {
+        if !self.peer.load(Ordering::Acquire) {
+            return Err(TrySendError::Disconnected(s));
+        }
+
+        self.producer
+            .borrow_mut()
+            .enqueue(s)
+            .map_err(TrySendError::Full)
+    }
+
+    /// Attempts to return a pending value from the other side without blocking. See
+    /// [`Channel::try_recv`](crate::Channel::try_recv).
+    ///
+    /// Messages sent before the other side hung up are still returned.
+    pub fn try_recv(&self) -> Result<R, TryRecvError> {
+        // Checked before dequeuing, so that a message sent just before the other side
+        // hung up is not missed.
+        let disconnected = !self.peer.load(Ordering::Acquire);
+
+        match self.consumer.borrow_mut().dequeue() {
+            Some(r) => Ok(r),
+            None if disconnected => Err(TryRecvError::Disconnected),
+            None => Err(TryRecvError::Empty),
+        }
+    }
+
+    /// Returns `true` if the other side has hung up.
+    pub fn is_disconnected(&self) -> bool {
+        !self.peer.load(Ordering::Acquire)
+    }
+}
+
+impl<'a, S, R> Drop for HeaplessChannel<'a, S, R> {
+    fn drop(&mut self) {
+        self.alive.store(false, Ordering::Release);
+    }
+}
+
+impl<'a, S, R> fmt::Debug for HeaplessChannel<'a, S, R> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("HeaplessChannel")
+            .field("disconnected", &self.is_disconnected())
+            .finish()
+    }
+}
+
+/// Creates a fixed capacity bidirectional channel in `queues`, returning the left and
+/// right sides. Each direction holds at most `N - 1` messages.
+///
+/// # Examples
+///
+/// ```
+/// use bichannel::{HeaplessQueues, TrySendError};
+///
+/// let mut queues = HeaplessQueues::<u8, u8, 2>::new();
+/// let (left, right) = bichannel::channel_heapless(&mut queues);
+///
+/// left.try_send(1).unwrap();
+/// assert_eq!(left.try_send(2), Err(TrySendError::Full(2)));
+/// assert_eq!(right.try_recv(), Ok(1));
+/// ```
+pub fn channel_heapless<T, U, const N: usize>(
+    queues: &mut HeaplessQueues<T, U, N>,
+) -> (HeaplessChannel<'_, T, U>, HeaplessChannel<'_, U, T>) {
+    queues.split()
+}
+
+#[cfg(test)]
+mod examples {
+    use crate::{HeaplessQueues, TryRecvError, TrySendError};
+
+    #[test]
+    fn test_fixed_capacity_scenario() {
+        let mut queues = HeaplessQueues::<u8, u8, 4>::new();
+        let (left, right) = crate::channel_heapless(&mut queues);
+
+        for i in 0..3 {
+            left.try_send(i).unwrap();
+        }
+        assert_eq!(left.try_send(3), Err(TrySendError::Full(3)));
+
+        right.try_send(10).unwrap();
+        assert_eq!(left.try_recv(), Ok(10));
+        assert_eq!(left.try_recv(), Err(TryRecvError::Empty));
+
+        drop(left);
+        assert!(right.is_disconnected());
+        assert_eq!(right.try_send(11), Err(TrySendError::Disconnected(11)));
+        for i in 0..3 {
+            assert_eq!(right.try_recv(), Ok(i));
+        }
+        assert_eq!(right.try_recv(), Err(TryRecvError::Disconnected));
+    }
+}
@@ -0,0 +1,107 @@
+//! Inspecting a received message in place before deciding to keep it.
+
+use std::fmt;
+use std::ops::Deref;
+
+/// A message received by [`Peekable::recv_ref`], lent out in place through [`Deref`]
+/// and consumed along with the guard, unless moved out with [`take`].
+///
+/// The message stays in the peeked slot of the adapter while the guard lives, so
+/// looking at it moves nothing.
+///
+/// [`Peekable::recv_ref`]: crate::Peekable::recv_ref
+/// [`take`]: Self::take
+pub struct RecvGuard<'a, R> {
+    /// The peeked slot of the adapter, which holds the message until the guard is
+    /// dropped.
+    slot: &'a mut Option<R>,
+}
+
+impl<'a, R> RecvGuard<'a, R> {
+    /// Lends out the message in `slot`, which must hold one.
+    pub(crate) fn new(slot: &'a mut Option<R>) -> Self {
+        debug_assert!(slot.is_some(), "lending out an empty slot");
+        RecvGuard { slot }
+    }
+
+    /// Moves the message out of the guard.
+    pub fn take(self) -> R {
+        self.slot.take().expect("the guard holds a message")
+    }
+}
+
+impl<R> Deref for RecvGuard<'_, R> {
+    type Target = R;
+
+    fn deref(&self) -> &R {
+        self.slot.as_ref().expect("the guard holds a message")
+    }
+}
+
+impl<R> Drop for RecvGuard<'_, R> {
+    fn drop(&mut self) {
+        *self.slot = None;
+    }
+}
+
+impl<R: fmt::Debug> fmt::Debug for RecvGuard<'_, R> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_tuple("RecvGuard").field(&**self).finish()
+    }
+}
+
+#[cfg(test)]
+mod examples {
+    use crate::{RecvError, TryRecvError};
+
+    #[derive(Debug, PartialEq)]
+    struct Frame {
+        id: u32,
+        payload: Vec<u8>,
+    }
+
+    #[test]
+    fn test_inspect_then_take() {
+        let (left, right) = crate::channel::<(), Frame>();
+        for id in 0..3 {
+            right
+                .send(Frame {
+                    id,
+                    payload: vec![id as u8; 4],
+                })
+                .unwrap();
+        }
+        drop(right);
+
+        let mut left = left.peekable();
+        let mut kept = Vec::new();
+        while let Ok(frame) = left.recv_ref() {
+            if frame.id % 2 == 0 {
+                kept.push(frame.take());
+            }
+        }
+
+        assert_eq!(
+            kept.iter().map(|frame| frame.id).collect::<Vec<_>>(),
+            [0, 2]
+        );
+        assert_eq!(kept[1].payload, [2; 4]);
+        assert_eq!(left.try_recv(), Err(TryRecvError::Disconnected));
+        assert_eq!(left.recv_ref().unwrap_err(), RecvError);
+    }
+
+    #[test]
+    fn test_lends_the_peeked_message() {
+        let (left, right) = crate::channel::<(), u8>();
+        let mut left = left.peekable();
+
+        right.send(1).unwrap();
+        right.send(2).unwrap();
+
+        assert_eq!(left.peek(), Some(&1));
+        assert_eq!(*left.recv_ref().unwrap(), 1);
+        assert_eq!(left.try_peek(), Some(&2));
+        assert_eq!(left.recv_ref().unwrap().take(), 2);
+        assert_eq!(left.try_recv(), Err(TryRecvError::Empty));
+    }
+}
@@ -0,0 +1,355 @@
+//! Detecting a counterpart that has stopped responding, by exchanging pings.
+
+use std::convert::TryFrom;
+use std::fmt;
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::{Arc, Mutex, PoisonError};
+use std::thread;
+use std::time::{Duration, Instant};
+
+use crate::{Channel, RecvError, RecvHalf, RecvTimeoutError, SendError, SendHalf, TryRecvError};
+
+/// A message exchanged between two [`Heartbeat`] sides: either a control message, or
+/// a value sent by the user.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Beat<T> {
+    /// Asks the other side to prove it is still responding.
+    Ping,
+    /// Answers a ping.
+    Pong,
+    /// A value sent with [`Heartbeat::send`].
+    Data(T),
+}
+
+type Callback = Box<dyn FnOnce() + Send>;
+
+/// What the background thread shares with its [`Heartbeat`].
+struct Liveness {
+    start: Instant,
+    timeout: Duration,
+    /// Nanoseconds since `start` at which the last pong arrived.
+    last_pong: AtomicU64,
+    dead: AtomicBool,
+    on_dead: Mutex<Option<Callback>>,
+}
+
+impl Liveness {
+    fn now(&self) -> u64 {
+        u64::try_from(self.start.elapsed().as_nanos()).unwrap_or(u64::MAX)
+    }
+
+    fn is_alive(&self) -> bool {
+        // A pong may be recorded between reading the clock and loading it.
+        let since_pong = self
+            .now()
+            .saturating_sub(self.last_pong.load(Ordering::Relaxed));
+        !self.dead.load(Ordering::Relaxed) && Duration::from_nanos(since_pong) <= self.timeout
+    }
+
+    fn pong(&self) {
+        self.last_pong.store(self.now(), Ordering::Relaxed);
+    }
+
+    /// Runs the callback if the other side has stopped responding, the first time it
+    /// is noticed.
+    fn check(&self, hung_up: bool) {
+        if hung_up {
+            self.dead.store(true, Ordering::Relaxed);
+        }
+
+        if !self.is_alive() {
+            let callback = self
+                .on_dead
+                .lock()
+                .unwrap_or_else(PoisonError::into_inner)
+                .take();
+            if let Some(callback) = callback {
+                callback();
+            }
+        }
+    }
+}
+
+/// One side of a bidirectional channel that pings the other side at a fixed interval,
+/// and considers it dead once a ping goes unanswered for longer than a timeout,
+/// created by [`heartbeat_channel`].
+///
+/// Each side spawns a background thread that sends the pings, answers the pings of the
+/// other side, and hands values to [`recv`], so control messages never surface to the
+/// user. As the background thread answers pings, a side keeps its counterpart alive
+/// for as long as it exists, whether or not it is receiving. A side whose counterpart
+/// has hung up is dead immediately.
+///
+/// The wrapped channel is split between this side and its background thread, so
+/// unlike the other adapters it cannot be borrowed or unwrapped.
+///
+/// [`recv`]: Self::recv
+pub struct Heartbeat<S, R> {
+    sender: SendHalf<Beat<S>>,
+    /// Fed by the background thread, which stops once this side is dropped.
+    receiver: Channel<(), R>,
+    liveness: Arc<Liveness>,
+}
+
+impl<S, R> Heartbeat<S, R>
+where
+    S: Send + 'static,
+    R: Send + 'static,
+{
+    /// Wraps a channel carrying heartbeat messages, such as one that relays messages
+    /// between two `Heartbeat`s, sending a ping every `interval`.
+    ///
+    /// The other side is considered dead once no pong has arrived for `timeout`, which
+    /// should leave room for at least one ping to make the round trip.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `interval` is zero, or if the background thread cannot be spawned.
+    pub fn new(channel: Channel<Beat<S>, Beat<R>>, interval: Duration, timeout: Duration) -> Self {
+        assert!(
+            interval > Duration::ZERO,
+            "heartbeat interval must be non-zero"
+        );
+
+        let (sender, incoming) = channel.split();
+        let (values, receiver) = crate::channel();
+        let pinger = sender.clone();
+
+        let liveness = Arc::new(Liveness {
+            start: Instant::now(),
+            timeout,
+            last_pong: AtomicU64::new(0),
+            dead: AtomicBool::new(false),
+            on_dead: Mutex::new(None),
+        });
+        let shared = liveness.clone();
+
+        thread::Builder::new()
+            .name("bichannel-heartbeat".into())
+            .spawn(move || beat(incoming, pinger, values, &shared, interval))
+            .expect("failed to spawn the bichannel heartbeat thread");
+
+        Heartbeat {
+            sender,
+            receiver,
+            liveness,
+        }
+    }
+}
+
+/// Exchanges pings with the other side until either the other side or the local
+/// heartbeat hangs up.
+fn beat<S, R>(
+    incoming: RecvHalf<Beat<R>>,
+    sender: SendHalf<Beat<S>>,
+    values: Channel<R, ()>,
+    liveness: &Liveness,
+    interval: Duration,
+) {
+    let mut next_ping = Instant::now();
+
+    loop {
+        // Checked on every message, so that a steady stream of them does not hold
+        // back the pings.
+        if Instant::now() >= next_ping {
+            if values.is_disconnected() {
+                break;
+            }
+            let _ = sender.send(Beat::Ping);
+            next_ping += interval;
+        }
+
+        match incoming.recv_deadline(next_ping) {
+            Ok(Beat::Data(r)) => {
+                if values.send(r).is_err() {
+                    break;
+                }
+            }
+            Ok(Beat::Ping) => {
+                let _ = sender.send(Beat::Pong);
+            }
+            Ok(Beat::Pong) => liveness.pong(),
+            Err(RecvTimeoutError::Timeout) => {}
+            Err(RecvTimeoutError::Disconnected) => {
+                liveness.check(true);
+                break;
+            }
+        }
+
+        liveness.check(false);
+    }
+}
+
+impl<S, R> Heartbeat<S, R> {
+    /// Sends a value to the other side, alongside the pings.
+    pub fn send(&self, s: S) -> Result<(), SendError<S>> {
+        self.sender.send(Beat::Data(s)).map_err(|err| match err.0 {
+            Beat::Data(s) => SendError(s),
+            _ => unreachable!("only data is sent"),
+        })
+    }
+
+    /// Blocks until the background thread hands over a value, skipping the pings and
+    /// pongs it handles.
+    pub fn recv(&self) -> Result<R, RecvError> {
+        self.receiver.recv()
+    }
+
+    /// Returns a value already handed over by the background thread, without blocking.
+    pub fn try_recv(&self) -> Result<R, TryRecvError> {
+        self.receiver.try_recv()
+    }
+
+    /// Waits for at most `timeout` for the background thread to hand over a value.
+    pub fn recv_timeout(&self, timeout: Duration) -> Result<R, RecvTimeoutError> {
+        self.receiver.recv_timeout(timeout)
+    }
+
+    /// Returns `true` if the other side has answered a ping within the timeout, and
+    /// has not hung up. The other side is given the benefit of the doubt for one
+    /// timeout after this side is created.
+    pub fn is_alive(&self) -> bool {
+        self.liveness.is_alive()
+    }
+
+    /// Registers `f` to run on the background thread once the other side is found to
+    /// be dead, replacing any callback registered before. `f` runs at most once, and
+    /// is not run if this side is dropped first.
+    pub fn on_dead<F>(&self, f: F)
+    where
+        F: FnOnce() + Send + 'static,
+    {
+        *self
+            .liveness
+            .on_dead
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner) = Some(Box::new(f));
+    }
+}
+
+impl<S, R> fmt::Debug for Heartbeat<S, R> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Heartbeat")
+            .field("alive", &self.is_alive())
+            .finish_non_exhaustive()
+    }
+}
+
+/// Creates a bidirectional channel whose sides ping each other every `interval`, and
+/// consider the other side dead once it leaves a ping unanswered for `timeout`,
+/// returning the left and right sides.
+///
+/// # Panics
+///
+/// Panics if `interval` is zero, or if a background thread cannot be spawned.
+///
+/// # Examples
+///
+/// ```
+/// use std::time::Duration;
+///
+/// let (left, right) = bichannel::heartbeat_channel::<&str, &str>(
+///     Duration::from_millis(10),
+///     Duration::from_millis(100),
+/// );
+///
+/// left.send("hello").unwrap();
+/// assert_eq!(right.recv(), Ok("hello"));
+/// assert!(right.is_alive());
+///
+/// drop(left);
+/// assert!(right.recv().is_err());
+/// assert!(!right.is_alive());
+/// ```
+pub fn heartbeat_channel<T, U>(
+    interval: Duration,
+    timeout: Duration,
+) -> (Heartbeat<T, U>, Heartbeat<U, T>)
+where
+    T: Send + 'static,
+    U: Send + 'static,
+{
+    let (left, right) = crate::channel();
+    (
+        Heartbeat::new(left, interval, timeout),
+        Heartbeat::new(right, interval, timeout),
+    )
+}
+
+#[cfg(test)]
+mod examples {
+    use std::sync::mpsc;
+    use std::thread;
+    use std::time::{Duration, Instant};
+
+    use crate::{Beat, Heartbeat};
+
+    #[test]
+    fn test_detects_unresponsive_peer() {
+        let (wire, peer) = crate::channel::<Beat<u8>, Beat<u8>>();
+        let wire = Heartbeat::new(wire, Duration::from_millis(10), Duration::from_millis(50));
+
+        let (dead, on_dead) = mpsc::channel();
+        wire.on_dead(move || dead.send(()).unwrap());
+
+        // The peer answers the first few pings, with data in between, then stalls
+        // without hanging up.
+        for _ in 0..3 {
+            assert_eq!(peer.recv(), Ok(Beat::Ping));
+            peer.send(Beat::Data(7)).unwrap();
+            peer.send(Beat::Pong).unwrap();
+        }
+        assert_eq!(wire.recv(), Ok(7));
+        assert!(wire.is_alive());
+
+        assert!(on_dead.recv_timeout(Duration::from_secs(1)).is_ok());
+        assert!(!wire.is_alive());
+        assert_eq!(wire.recv(), Ok(7));
+        assert_eq!(wire.recv(), Ok(7));
+
+        drop(peer);
+    }
+
+    #[test]
+    fn test_pings_despite_a_stream_of_data() {
+        let (wire, peer) = crate::channel::<Beat<u8>, Beat<u8>>();
+        let wire = Heartbeat::new(wire, Duration::from_millis(5), Duration::from_secs(1));
+
+        // The peer sends data faster than the ping interval, and answers pings.
+        let mut pings = 0;
+        let start = Instant::now();
+        while start.elapsed() < Duration::from_millis(100) {
+            peer.send(Beat::Data(1)).unwrap();
+            while let Ok(beat) = peer.try_recv() {
+                assert_eq!(beat, Beat::Ping);
+                pings += 1;
+                peer.send(Beat::Pong).unwrap();
+            }
+            thread::sleep(Duration::from_millis(1));
+        }
+        assert!(pings >= 5, "{} pings", pings);
+        assert!(wire.is_alive());
+        assert_eq!(wire.try_recv(), Ok(1));
+    }
+
+    #[test]
+    #[should_panic(expected = "heartbeat interval must be non-zero")]
+    fn test_rejects_a_zero_interval() {
+        crate::heartbeat_channel::<u8, u8>(Duration::ZERO, Duration::from_secs(1));
+    }
+
+    #[test]
+    fn test_responsive_peer_stays_alive() {
+        let (left, right) =
+            crate::heartbeat_channel::<u8, u8>(Duration::from_millis(5), Duration::from_millis(30));
+
+        thread::sleep(Duration::from_millis(100));
+        assert!(left.is_alive());
+        assert!(right.is_alive());
+
+        // Neither side surfaces the control messages.
+        assert!(left.try_recv().is_err());
+        right.send(1).unwrap();
+        assert_eq!(left.recv(), Ok(1));
+    }
+}
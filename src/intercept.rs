@@ -0,0 +1,231 @@
+//! Observing every message sent and received on a [`Channel`].
+
+use std::fmt;
+use std::time::Duration;
+
+use crate::{Channel, RecvError, RecvTimeoutError, SendError, TryRecvError, TrySendError};
+
+/// Hooks that observe the messages sent and received on one side of a channel, such as
+/// for metrics, validation or logging, registered with [`Channel::with_interceptor`].
+///
+/// Hooks only see a reference to each message, so they cannot change or drop it. Both
+/// do nothing by default, so an interceptor only needs the hooks it cares about.
+///
+/// A pair of interceptors is itself an interceptor, calling the first then the second,
+/// which is how [`Intercepted::with_interceptor`] chains them.
+pub trait Interceptor<S, R> {
+    /// Called with each message about to be sent, before the send is attempted, so
+    /// messages that fail to send are seen too.
+    fn on_send(&self, _s: &S) {}
+
+    /// Called with each message received, before it is returned.
+    fn on_recv(&self, _r: &R) {}
+}
+
+impl<S, R, I, J> Interceptor<S, R> for (I, J)
+where
+    I: Interceptor<S, R>,
+    J: Interceptor<S, R>,
+{
+    fn on_send(&self, s: &S) {
+        self.0.on_send(s);
+        self.1.on_send(s);
+    }
+
+    fn on_recv(&self, r: &R) {
+        self.0.on_recv(r);
+        self.1.on_recv(r);
+    }
+}
+
+/// One side of a bidirectional channel that passes every message to an
+/// [`Interceptor`], created by [`Channel::with_interceptor`].
+pub struct Intercepted<S, R, I> {
+    channel: Channel<S, R>,
+    interceptor: I,
+}
+
+impl<S, R, I: Interceptor<S, R>> Intercepted<S, R, I> {
+    /// Passes a value to the interceptor, then sends it like [`Channel::send`].
+    pub fn send(&self, s: S) -> Result<(), SendError<S>> {
+        self.interceptor.on_send(&s);
+        self.channel.send(s)
+    }
+
+    /// Passes a value to the interceptor, then sends it without blocking.
+    pub fn try_send(&self, s: S) -> Result<(), TrySendError<S>> {
+        self.interceptor.on_send(&s);
+        self.channel.try_send(s)
+    }
+
+    /// Blocks until a value is received, passing it to the interceptor.
+    pub fn recv(&self) -> Result<R, RecvError> {
+        let r = self.channel.recv()?;
+        self.interceptor.on_recv(&r);
+        Ok(r)
+    }
+
+    /// Returns a pending value without blocking, passing it to the interceptor.
+    pub fn try_recv(&self) -> Result<R, TryRecvError> {
+        let r = self.channel.try_recv()?;
+        self.interceptor.on_recv(&r);
+        Ok(r)
+    }
+
+    /// Waits for at most `timeout` for a value, passing it to the interceptor.
+    pub fn recv_timeout(&self, timeout: Duration) -> Result<R, RecvTimeoutError> {
+        let r = self.channel.recv_timeout(timeout)?;
+        self.interceptor.on_recv(&r);
+        Ok(r)
+    }
+
+    /// Adds another interceptor, called after those already registered.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::cell::Cell;
+    /// use bichannel::Interceptor;
+    ///
+    /// struct Log<'a>(&'a str, &'a Cell<Vec<&'a str>>);
+    ///
+    /// impl Interceptor<u8, u8> for Log<'_> {
+    ///     fn on_send(&self, _: &u8) {
+    ///         let mut log = self.1.take();
+    ///         log.push(self.0);
+    ///         self.1.set(log);
+    ///     }
+    /// }
+    ///
+    /// let log = Cell::new(Vec::new());
+    /// let (left, _right) = bichannel::channel::<u8, u8>();
+    /// let left = left
+    ///     .with_interceptor(Log("first", &log))
+    ///     .with_interceptor(Log("second", &log));
+    ///
+    /// left.send(1).unwrap();
+    /// assert_eq!(log.take(), ["first", "second"]);
+    /// ```
+    pub fn with_interceptor<J: Interceptor<S, R>>(self, j: J) -> Intercepted<S, R, (I, J)> {
+        Intercepted {
+            channel: self.channel,
+            interceptor: (self.interceptor, j),
+        }
+    }
+}
+
+impl<S, R, I> Intercepted<S, R, I> {
+    /// Returns a reference to the underlying channel.
+    ///
+    /// Messages sent and received through it are not seen by the interceptor.
+    pub fn get_ref(&self) -> &Channel<S, R> {
+        &self.channel
+    }
+
+    /// Returns a mutable reference to the underlying channel.
+    ///
+    /// Messages sent and received through it are not seen by the interceptor.
+    pub fn get_mut(&mut self) -> &mut Channel<S, R> {
+        &mut self.channel
+    }
+
+    /// Unwraps this adapter, returning the underlying channel and the interceptor.
+    pub fn into_inner(self) -> (Channel<S, R>, I) {
+        (self.channel, self.interceptor)
+    }
+}
+
+impl<S, R, I> fmt::Debug for Intercepted<S, R, I> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Intercepted")
+            .field("channel", &self.channel)
+            .finish_non_exhaustive()
+    }
+}
+
+impl<S, R> Channel<S, R> {
+    /// Returns an adapter that passes every message sent and received to
+    /// `interceptor`, without changing how messages are exchanged.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::sync::atomic::{AtomicUsize, Ordering};
+    /// use bichannel::Interceptor;
+    ///
+    /// #[derive(Default)]
+    /// struct Bytes(AtomicUsize);
+    ///
+    /// impl Interceptor<String, ()> for Bytes {
+    ///     fn on_send(&self, s: &String) {
+    ///         self.0.fetch_add(s.len(), Ordering::Relaxed);
+    ///     }
+    /// }
+    ///
+    /// let (left, right) = bichannel::channel::<String, ()>();
+    /// let left = left.with_interceptor(Bytes::default());
+    ///
+    /// left.send("hello".to_string()).unwrap();
+    /// assert_eq!(right.recv().unwrap(), "hello");
+    ///
+    /// let (_, bytes) = left.into_inner();
+    /// assert_eq!(bytes.0.into_inner(), 5);
+    /// ```
+    pub fn with_interceptor<I: Interceptor<S, R>>(self, interceptor: I) -> Intercepted<S, R, I> {
+        Intercepted {
+            channel: self,
+            interceptor,
+        }
+    }
+}
+
+#[cfg(test)]
+mod examples {
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::Arc;
+    use std::time::Duration;
+
+    use crate::{Interceptor, TryRecvError};
+
+    #[derive(Clone, Default)]
+    struct Counts {
+        sent: Arc<AtomicUsize>,
+        received: Arc<AtomicUsize>,
+    }
+
+    impl<S, R> Interceptor<S, R> for Counts {
+        fn on_send(&self, _: &S) {
+            self.sent.fetch_add(1, Ordering::Relaxed);
+        }
+
+        fn on_recv(&self, _: &R) {
+            self.received.fetch_add(1, Ordering::Relaxed);
+        }
+    }
+
+    #[test]
+    fn test_interceptors_observe_every_message() {
+        let (left, right) = crate::channel::<u8, char>();
+        let first = Counts::default();
+        let second = Counts::default();
+        let left = left
+            .with_interceptor(first.clone())
+            .with_interceptor(second.clone());
+
+        left.send(1).unwrap();
+        left.try_send(2).unwrap();
+        left.send(3).unwrap();
+        right.send_all(vec!['a', 'b', 'c']).unwrap();
+
+        assert_eq!(left.recv(), Ok('a'));
+        assert_eq!(left.try_recv(), Ok('b'));
+        assert_eq!(left.recv_timeout(Duration::from_secs(1)), Ok('c'));
+        assert_eq!(left.try_recv(), Err(TryRecvError::Empty));
+        assert_eq!(right.try_iter().collect::<Vec<_>>(), [1, 2, 3]);
+
+        for counts in [first, second] {
+            assert_eq!(counts.sent.load(Ordering::Relaxed), 3);
+            assert_eq!(counts.received.load(Ordering::Relaxed), 3);
+        }
+    }
+}
@@ -0,0 +1,277 @@
+//! Byte streams built on channels, for exercising code that expects `std::io` types.
+
+use std::io::{self, Cursor, Read, Write};
+use std::sync::{Mutex, PoisonError};
+
+use crate::{Channel, RecvError, SendError, SyncChannel};
+
+/// The bytes of the last chunk received that have not been read yet, shared by the
+/// stream types.
+#[derive(Debug)]
+struct Unread(Cursor<Vec<u8>>);
+
+impl Unread {
+    fn new() -> Self {
+        Unread(Cursor::new(Vec::new()))
+    }
+
+    /// Copies bytes from the current chunk, receiving the next one with `recv` once it
+    /// has been read. Returns end of file once `recv` fails.
+    fn read(
+        &mut self,
+        buf: &mut [u8],
+        recv: impl Fn() -> Result<Vec<u8>, RecvError>,
+    ) -> io::Result<usize> {
+        if buf.is_empty() {
+            return Ok(0);
+        }
+
+        while self.0.position() >= self.0.get_ref().len() as u64 {
+            match recv() {
+                Ok(chunk) => self.0 = Cursor::new(chunk),
+                Err(_) => return Ok(0),
+            }
+        }
+
+        self.0.read(buf)
+    }
+
+    fn into_inner(self) -> Vec<u8> {
+        let position = self.0.position() as usize;
+        let mut unread = self.0.into_inner();
+        unread.drain(..position);
+        unread
+    }
+}
+
+/// Sends `buf` as one chunk with `send`, reporting a hang up as a broken pipe.
+fn write_chunk(
+    buf: &[u8],
+    send: impl FnOnce(Vec<u8>) -> Result<(), SendError<Vec<u8>>>,
+) -> io::Result<usize> {
+    if buf.is_empty() {
+        return Ok(0);
+    }
+
+    send(buf.to_vec()).map_err(|err| io::Error::new(io::ErrorKind::BrokenPipe, err))?;
+    Ok(buf.len())
+}
+
+/// One end of an in-memory byte stream, created by [`duplex`].
+///
+/// Bytes written to one end become readable on the other, in order. Each write is sent
+/// as one chunk, and a read returns bytes from at most one chunk, holding onto the rest
+/// for the next read. Once the other end is dropped, reads return the bytes still in
+/// flight and then end of file, and writes fail with [`io::ErrorKind::BrokenPipe`].
+///
+/// Like `TcpStream`, it can be read and written through a shared reference, so it
+/// can stand in for a socket when testing a `transport` bridge.
+#[derive(Debug)]
+pub struct DuplexStream {
+    channel: SyncChannel<Vec<u8>, Vec<u8>>,
+    unread: Mutex<Unread>,
+}
+
+/// Creates an in-memory byte stream, returning its two ends.
+///
+/// # Examples
+///
+/// ```
+/// use std::io::{Read, Write};
+///
+/// let (mut left, mut right) = bichannel::io::duplex();
+///
+/// left.write_all(b"hello").unwrap();
+///
+/// let mut buf = [0; 5];
+/// right.read_exact(&mut buf).unwrap();
+/// assert_eq!(&buf, b"hello");
+/// ```
+pub fn duplex() -> (DuplexStream, DuplexStream) {
+    let (left, right) = crate::channel();
+    (
+        DuplexStream::new(left.into()),
+        DuplexStream::new(right.into()),
+    )
+}
+
+impl DuplexStream {
+    fn new(channel: SyncChannel<Vec<u8>, Vec<u8>>) -> Self {
+        DuplexStream {
+            channel,
+            unread: Mutex::new(Unread::new()),
+        }
+    }
+}
+
+impl Read for &DuplexStream {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        self.unread
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .read(buf, || self.channel.recv())
+    }
+}
+
+impl Write for &DuplexStream {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        write_chunk(buf, |chunk| self.channel.send(chunk))
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+impl Read for DuplexStream {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        (&*self).read(buf)
+    }
+}
+
+impl Write for DuplexStream {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        (&*self).write(buf)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        (&*self).flush()
+    }
+}
+
+/// One side of a byte-typed channel used as a byte stream.
+///
+/// Each [`write`](Write::write) sends the bytes as one message. A
+/// [`read`](Read::read) copies bytes from the next message, holding onto any that do
+/// not fit for the next read, so no bytes are lost however small the buffers are.
+/// Reads return end of file once the other side has hung up and every message has been
+/// read.
+///
+/// # Examples
+///
+/// ```
+/// use std::io::{Read, Write};
+/// use bichannel::io::ByteChannel;
+///
+/// let (left, right) = bichannel::channel::<Vec<u8>, Vec<u8>>();
+/// let mut right = ByteChannel::new(right);
+///
+/// left.send(b"hello".to_vec()).unwrap();
+/// right.write_all(b"hi").unwrap();
+///
+/// let mut buf = [0; 3];
+/// assert_eq!(right.read(&mut buf).unwrap(), 3);
+/// assert_eq!(&buf, b"hel");
+/// assert_eq!(left.recv().unwrap(), b"hi");
+/// ```
+#[derive(Debug)]
+pub struct ByteChannel {
+    channel: Channel<Vec<u8>, Vec<u8>>,
+    unread: Unread,
+}
+
+impl ByteChannel {
+    /// Wraps `channel` as a byte stream.
+    pub fn new(channel: Channel<Vec<u8>, Vec<u8>>) -> Self {
+        ByteChannel {
+            channel,
+            unread: Unread::new(),
+        }
+    }
+
+    /// Unwraps the byte stream, returning the channel along with any bytes received
+    /// but not yet read.
+    pub fn into_inner(self) -> (Channel<Vec<u8>, Vec<u8>>, Vec<u8>) {
+        (self.channel, self.unread.into_inner())
+    }
+}
+
+impl From<Channel<Vec<u8>, Vec<u8>>> for ByteChannel {
+    fn from(channel: Channel<Vec<u8>, Vec<u8>>) -> Self {
+        ByteChannel::new(channel)
+    }
+}
+
+impl Read for ByteChannel {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let channel = &self.channel;
+        self.unread.read(buf, || channel.recv())
+    }
+}
+
+impl Write for ByteChannel {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        write_chunk(buf, |chunk| self.channel.send(chunk))
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod examples {
+    use std::io::{ErrorKind, Read, Write};
+
+    #[test]
+    fn test_partial_reads() {
+        let (mut left, mut right) = crate::io::duplex();
+
+        left.write_all(b"hello").unwrap();
+        left.write_all(b" world").unwrap();
+
+        let mut buf = [0; 2];
+        assert_eq!(right.read(&mut buf).unwrap(), 2);
+        assert_eq!(&buf, b"he");
+        assert_eq!(right.read(&mut buf).unwrap(), 2);
+        assert_eq!(&buf, b"ll");
+        assert_eq!(right.read(&mut buf).unwrap(), 1);
+        assert_eq!(&buf[..1], b"o");
+
+        drop(left);
+        let mut rest = String::new();
+        right.read_to_string(&mut rest).unwrap();
+        assert_eq!(rest, " world");
+
+        assert_eq!(right.write(b"!").unwrap_err().kind(), ErrorKind::BrokenPipe);
+    }
+
+    #[test]
+    fn test_byte_channel_keeps_leftovers() {
+        use crate::io::ByteChannel;
+
+        let (left, right) = crate::channel::<Vec<u8>, Vec<u8>>();
+        let mut left = ByteChannel::new(left);
+        let mut right = ByteChannel::new(right);
+
+        left.write_all(b"abc").unwrap();
+        left.write_all(b"defg").unwrap();
+        drop(left);
+
+        let mut received = Vec::new();
+        let mut byte = [0; 1];
+        while right.read(&mut byte).unwrap() == 1 {
+            received.push(byte[0]);
+        }
+        assert_eq!(received, b"abcdefg");
+
+        let (channel, unread) = right.into_inner();
+        assert!(unread.is_empty());
+        assert!(channel.is_disconnected());
+    }
+
+    #[test]
+    #[cfg(feature = "serde")]
+    fn test_feeds_a_transport() {
+        let (left, right) = crate::io::duplex();
+
+        let left = crate::transport::serve::<String, usize, _>(left);
+        let right = crate::transport::serve::<usize, String, _>(right);
+
+        left.send("hello".to_string()).unwrap();
+        let request = right.recv().unwrap();
+        right.send(request.len()).unwrap();
+
+        assert_eq!(left.recv(), Ok(5));
+    }
+}
@@ -1,10 +1,43 @@
-//! Zero dependency `std::sync` based bidirectional channels. Each side can send
-//! and receive with its counterpart.
+//! `std::sync` based bidirectional channels. Each side can send and receive with its
+//! counterpart. With the default flags the crate has no dependencies; the optional
+//! flags below each pull in the crates they integrate with.
 //!
 //! Note, the default `Channel` inherits `!Sync` from `std::sync::mpsc::Receiver`. If you
 //! would prefer, a `crossbeam` implementation is available by enabling the `crossbeam` flag. In
 //! addition to its desirable performance characteristics, it also drops this `!Sync` constraint.
 //!
+//! The `flume` flag backs `Channel` with `flume` instead, for programs already relying on
+//! `flume` elsewhere. It also drops the `!Sync` constraint, and adds a `FlumeBackend` for
+//! `channel_with`. `Select` needs `crossbeam`, which is used if both flags are enabled.
+//!
+//! Enabling the `futures` flag implements `futures::Stream` and `futures::Sink` for each side,
+//! so messages can be awaited and sent from async code without blocking the executor. For a
+//! channel native to async code, the `async` flag adds an `AsyncChannel` backed by
+//! `tokio::sync::mpsc`.
+//!
+//! For targets without an allocator, the `heapless` flag adds a `HeaplessChannel` backed by
+//! fixed capacity `heapless::spsc` queues, offering the non-blocking half of the API.
+//! Everything else depends on the `std` flag, which is on by default: with
+//! `default-features = false, features = ["heapless"]`, the crate is `no_std`, which is
+//! checked with `cargo test --no-default-features --features heapless --lib`.
+//!
+//! The `serde` flag adds the `transport` module, which bridges a `Channel` over a byte
+//! stream such as a `TcpStream`, so that processes can talk with the same API.
+//! The `compression` flag implies `serde`, and lets the transport compress what it writes.
+//! The `checksum` flag implies `serde` too, and lets it checksum every frame it writes.
+//! The `json` flag implies `serde`, and adds a codec writing messages as JSON lines.
+//! The `bincode` flag implies `serde` too, and makes the transport write messages with
+//! `bincode` rather than JSON by default.
+//!
+//! The `tracing` flag emits a `tracing` event at the `TRACE` level on every `send` and
+//! `recv`, recording the name of the side, if it has one, and whether the call succeeded.
+//! It also adds `Channel::recv_watchdog`, which warns through `tracing` about a `recv`
+//! blocked for longer than expected.
+//!
+//! This crate is model checked with `loom` under a `loom` cfg rather than a flag, so
+//! that it is never enabled by feature unification, with
+//! `RUSTFLAGS="--cfg loom" cargo test --release --features futures --lib loom`.
+//!
 //! ## Getting Started
 //!
 //! ```toml
@@ -35,29 +68,183 @@
 //! NOTE: This README uses [cargo-readme](https://github.com/livioribeiro/cargo-readme). To
 //! update the README, use `cargo readme > README.md`
 
-#[cfg(not(feature = "crossbeam"))]
-use std::sync::mpsc::{channel as create_channel, Receiver, Sender};
-
-#[cfg(not(feature = "crossbeam"))]
-pub use std::sync::mpsc::RecvError;
-#[cfg(not(feature = "crossbeam"))]
-pub use std::sync::mpsc::SendError;
-#[cfg(not(feature = "crossbeam"))]
-pub use std::sync::mpsc::TryRecvError;
-#[cfg(not(feature = "crossbeam"))]
-pub use std::sync::mpsc::TrySendError;
+#![cfg_attr(not(any(feature = "std", test)), no_std)]
 
+#[cfg(feature = "std")]
+mod alive;
+#[cfg(feature = "async")]
+mod asynchronous;
+#[cfg(feature = "std")]
+mod backend;
+#[cfg(feature = "std")]
+mod backend_trait;
+#[cfg(feature = "std")]
+mod broadcast;
+#[cfg(feature = "std")]
+mod builder;
+#[cfg(feature = "std")]
+mod compact;
+#[cfg(feature = "std")]
+mod cork;
+#[cfg(feature = "std")]
+mod debounce;
+#[cfg(feature = "std")]
+mod dedup;
+#[cfg(feature = "std")]
+mod disconnect;
+mod error;
+#[cfg(feature = "std")]
+mod filter;
+#[cfg(feature = "heapless")]
+mod fixed;
+#[cfg(feature = "std")]
+mod guard;
+#[cfg(feature = "std")]
+mod heartbeat;
+#[cfg(feature = "std")]
+mod intercept;
+#[cfg(feature = "std")]
+pub mod io;
+#[cfg(feature = "std")]
+mod map;
+#[cfg(feature = "std")]
+mod merge;
+#[cfg(feature = "std")]
+mod mux;
+#[cfg(feature = "std")]
+mod peek;
+#[cfg(feature = "std")]
+mod pipe;
+#[cfg(feature = "std")]
+mod primitive;
+#[cfg(feature = "std")]
+mod priority;
+#[cfg(feature = "std")]
+mod rate;
+#[cfg(feature = "std")]
+mod reliable;
+#[cfg(feature = "std")]
+mod replay;
 #[cfg(feature = "crossbeam")]
-use crossbeam_channel::{unbounded as create_channel, Receiver, Sender};
+mod select;
+#[cfg(feature = "std")]
+mod seq;
+#[cfg(feature = "futures")]
+mod sink;
+#[cfg(feature = "std")]
+mod stats;
+#[cfg(feature = "futures")]
+mod stream;
+#[cfg(feature = "std")]
+mod sync;
+#[cfg(feature = "std")]
+mod tee;
+#[cfg(feature = "std")]
+mod timer;
+#[cfg(feature = "serde")]
+pub mod transport;
+#[cfg(feature = "std")]
+mod ttl;
+#[cfg(feature = "std")]
+mod watchdog;
+#[cfg(any(feature = "crossbeam", feature = "flume"))]
+mod weak;
+#[cfg(feature = "std")]
+mod window;
+#[cfg(feature = "std")]
+mod worker;
+#[cfg(feature = "std")]
+mod zip;
 
+#[cfg(feature = "std")]
+use alive::{Alive, Peer};
+#[cfg(feature = "async")]
+pub use asynchronous::{async_channel, AsyncChannel};
+#[cfg(feature = "std")]
+use backend::{Receiver, Sender};
 #[cfg(feature = "crossbeam")]
-pub use crossbeam_channel::RecvError;
-#[cfg(feature = "crossbeam")]
-pub use crossbeam_channel::SendError;
+pub use backend_trait::CrossbeamBackend;
+#[cfg(feature = "flume")]
+pub use backend_trait::FlumeBackend;
+#[cfg(feature = "std")]
+pub use backend_trait::{Backend, DefaultBackend, StdBackend};
+#[cfg(feature = "std")]
+pub use broadcast::BroadcastSender;
+#[cfg(feature = "std")]
+pub use builder::ChannelBuilder;
+#[cfg(feature = "std")]
+pub use compact::{compact_channel, CompactChannel};
+#[cfg(feature = "std")]
+use cork::Cork;
+#[cfg(feature = "std")]
+pub use debounce::Debounced;
+#[cfg(feature = "std")]
+pub use dedup::Dedup;
+#[cfg(feature = "std")]
+use disconnect::OnDisconnect;
+#[cfg(feature = "checksum")]
+pub use error::FrameIntegrityError;
 #[cfg(feature = "crossbeam")]
-pub use crossbeam_channel::TryRecvError;
+pub use error::SelectTimeoutError;
+pub use error::{CallError, CallTimeoutError, Error, RateLimitError, SequenceError};
+pub use error::{RecvError, RecvTimeoutError, SendError, SendTimeoutError};
+pub use error::{TryRecvError, TrySendError};
+#[cfg(feature = "std")]
+pub use filter::Filter;
+#[cfg(feature = "heapless")]
+pub use fixed::{channel_heapless, HeaplessChannel, HeaplessQueues};
+#[cfg(feature = "std")]
+pub use guard::RecvGuard;
+#[cfg(feature = "std")]
+pub use heartbeat::{heartbeat_channel, Beat, Heartbeat};
+#[cfg(feature = "std")]
+pub use intercept::{Intercepted, Interceptor};
+#[cfg(feature = "std")]
+pub use map::{MapRecv, MapSend};
+#[cfg(feature = "std")]
+pub use merge::{merge, Merge};
+#[cfg(feature = "std")]
+pub use mux::{Demux, Mux, MuxSender, UnroutedPolicy};
+#[cfg(feature = "std")]
+pub use peek::Peekable;
+#[cfg(feature = "std")]
+pub use pipe::{pipe, pipe_until_closed};
+#[cfg(all(feature = "std", not(any(feature = "crossbeam", feature = "flume"))))]
+use primitive::mpsc;
+#[cfg(feature = "std")]
+pub use priority::{priority_channel, Lane, PriorityChannel};
+#[cfg(feature = "std")]
+pub use rate::{RateLimitPolicy, RateLimited};
+#[cfg(feature = "std")]
+pub use reliable::{Packet, Reliable};
+#[cfg(feature = "std")]
+pub use replay::replay;
 #[cfg(feature = "crossbeam")]
-pub use crossbeam_channel::TrySendError;
+pub use select::{Select, SelectedOperation};
+#[cfg(feature = "std")]
+pub use seq::{seq_channel, SeqChannel};
+#[cfg(feature = "std")]
+pub use stats::ChannelStats;
+#[cfg(feature = "std")]
+use std::fmt;
+#[cfg(feature = "std")]
+use std::sync::Arc;
+#[cfg(feature = "std")]
+use std::time::{Duration, Instant};
+#[cfg(feature = "std")]
+pub use sync::SyncChannel;
+#[cfg(feature = "std")]
+pub use tee::Tee;
+#[cfg(feature = "std")]
+pub use ttl::{ttl_channel, TtlChannel};
+#[cfg(any(feature = "crossbeam", feature = "flume"))]
+pub use weak::WeakChannel;
+#[cfg(feature = "std")]
+pub use window::{TimeWindows, Windows};
+#[cfg(feature = "std")]
+pub use worker::{spawn_worker, Worker};
+#[cfg(feature = "std")]
+pub use zip::{zip, Zip};
 
 /// One side of a bidirectional channel. This channel can send to and receive from its
 /// counterpart.
@@ -74,23 +261,105 @@ pub use crossbeam_channel::TrySendError;
 /// assert_eq!(Ok(1), l.recv());
 ///
 /// ```
-#[derive(Debug)]
-pub struct Channel<S, R> {
-    sender: Sender<S>,
-    receiver: Receiver<R>,
+///
+/// # Swapping directions
+///
+/// A side cannot be flipped to take its counterpart's perspective, even when `S` and
+/// `R` are the same type. A side holds the sending end of one direction and the
+/// receiving end of the other, and the two ends that flipping would need are held by
+/// the counterpart. Code that needs the other perspective should be given the
+/// counterpart itself, or one of its halves from [`split`](Channel::split):
+///
+/// ```
+/// use bichannel::Channel;
+///
+/// fn echo(peer: &Channel<u8, u8>) {
+///     let n = peer.recv().unwrap();
+///     peer.send(n + 1).unwrap();
+/// }
+///
+/// let (client, server) = bichannel::channel::<u8, u8>();
+///
+/// client.send(1).unwrap();
+/// echo(&server);
+/// assert_eq!(client.recv(), Ok(2));
+/// ```
+#[cfg(feature = "std")]
+pub struct Channel<S, R, B: Backend = DefaultBackend> {
+    sender: B::Sender<S>,
+    receiver: B::Receiver<R>,
+    /// Held for as long as this side lives, so the counterpart can tell when it hangs up.
+    alive: Alive,
+    peer: Peer,
+    name: Option<Arc<str>>,
+    cork: Cork<S>,
+    on_disconnect: OnDisconnect,
+    #[cfg(any(feature = "crossbeam", feature = "flume"))]
+    link: weak::SharedLink<S, R, B>,
 }
 
-#[cfg(feature = "crossbeam")]
+/// Prints the name of the side, if it has one, so that sides can be told apart in
+/// diagnostics.
+#[cfg(feature = "std")]
+impl<S, R, B: Backend> fmt::Debug for Channel<S, R, B> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut debug = f.debug_struct("Channel");
+        if let Some(name) = &self.name {
+            debug.field("name", name);
+        }
+        debug.finish_non_exhaustive()
+    }
+}
+
+// Messages are never pinned, so a side can be moved freely regardless of its payloads.
+#[cfg(feature = "std")]
+impl<S, R, B: Backend> Unpin for Channel<S, R, B> {}
+
+/// Compares sides by identity: two handles are equal if they are the same side of the
+/// same channel, as clones of one side are. Payloads are never compared.
+#[cfg(feature = "std")]
+impl<S, R, B: Backend> PartialEq for Channel<S, R, B> {
+    fn eq(&self, other: &Self) -> bool {
+        self.alive.id() == other.alive.id()
+    }
+}
+
+#[cfg(feature = "std")]
+impl<S, R, B: Backend> Eq for Channel<S, R, B> {}
+
+#[cfg(any(feature = "crossbeam", feature = "flume"))]
 impl<S, R> Clone for Channel<S, R> {
     fn clone(&self) -> Self {
+        let mut channel = Channel::assemble(
+            self.sender.clone(),
+            self.receiver.clone(),
+            self.alive.clone(),
+            self.peer.clone(),
+        );
+        channel.name = self.name.clone();
+        channel.link = self.link.clone();
+        channel
+    }
+}
+
+#[cfg(feature = "std")]
+impl<S, R, B: Backend> Channel<S, R, B> {
+    /// Assembles a side from its halves, with no name, nothing corked and no
+    /// callbacks.
+    fn assemble(sender: B::Sender<S>, receiver: B::Receiver<R>, alive: Alive, peer: Peer) -> Self {
         Channel {
-            sender: self.sender.clone(),
-            receiver: self.receiver.clone(),
+            sender,
+            receiver,
+            alive,
+            peer,
+            name: None,
+            cork: Cork::default(),
+            on_disconnect: OnDisconnect::default(),
+            #[cfg(any(feature = "crossbeam", feature = "flume"))]
+            link: Arc::default(),
         }
     }
-}
 
-impl<S, R> Channel<S, R> {
     /// See mpsc::Sender::send
     ///
     /// Attempts to send a value to the other side of this channel, returning it back if it could
@@ -104,7 +373,9 @@ impl<S, R> Channel<S, R> {
     /// will be received. It is possible for the corresponding receiver to
     /// hang up immediately after this function returns [`Ok`].
     ///
-    /// This method will never block the current thread.
+    /// This method will never block the current thread for channels created with
+    /// [`channel`]. Channels created with [`sync_channel`] will block while the
+    /// buffer is full.
     ///
     /// # Examples
     ///
@@ -120,7 +391,73 @@ impl<S, R> Channel<S, R> {
     /// assert_eq!(r.send(1).unwrap_err().0, 1);
     /// ```
     pub fn send(&self, s: S) -> Result<(), SendError<S>> {
-        self.sender.send(s)
+        let result = match self.cork.hold(s) {
+            Some(s) => B::send(&self.sender, s),
+            None => Ok(()),
+        };
+        // A send also fails on a full buffer with `OverflowPolicy::Error`.
+        if result.is_err() && self.is_disconnected() {
+            self.on_disconnect.fire();
+        }
+        #[cfg(feature = "tracing")]
+        tracing::trace!(channel = self.name.as_deref(), ok = result.is_ok(), "send");
+        result
+    }
+
+    /// See mpsc::SyncSender::try_send
+    ///
+    /// Attempts to send a value to the other side of this channel without blocking.
+    ///
+    /// This method differs from [`send`] by returning immediately if the
+    /// channel's buffer is full or the other side has disconnected. In both
+    /// cases the value is handed back inside the error so it can be retried or
+    /// dropped. Channels created with [`channel`] are never full.
+    ///
+    /// [`send`]: Self::send
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use bichannel::TrySendError;
+    ///
+    /// let (l, r) = bichannel::sync_channel::<u8, u8>(1);
+    ///
+    /// l.try_send(1).unwrap();
+    /// assert_eq!(l.try_send(2), Err(TrySendError::Full(2)));
+    ///
+    /// drop(r);
+    /// assert_eq!(l.try_send(3), Err(TrySendError::Disconnected(3)));
+    /// ```
+    pub fn try_send(&self, s: S) -> Result<(), TrySendError<S>> {
+        let result = match self.cork.hold(s) {
+            Some(s) => B::try_send(&self.sender, s),
+            None => Ok(()),
+        };
+        if let Err(TrySendError::Disconnected(_)) = result {
+            self.on_disconnect.fire();
+        }
+        result
+    }
+
+    /// Returns `true` if the other side of this channel has hung up.
+    ///
+    /// This is a cheap check that never blocks or consumes a message. Note that a
+    /// return value of `false` is only a hint: the other side may hang up immediately
+    /// after this function returns. Messages the other side sent before hanging up
+    /// can still be received.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// let (left, right) = bichannel::channel::<u8, u8>();
+    ///
+    /// assert!(!left.is_disconnected());
+    ///
+    /// drop(right);
+    /// assert!(left.is_disconnected());
+    /// ```
+    pub fn is_disconnected(&self) -> bool {
+        self.peer.hung_up()
     }
 
     /// See mpsc::Receiver::recv
@@ -136,7 +473,7 @@ impl<S, R> Channel<S, R> {
     /// this call is blocking, this call will wake up and return [`Err`] to
     /// indicate that no more messages can ever be received on this channel.
     /// However, since channels are buffered, messages sent before the disconnect
-    /// will still be properly received.
+    /// will still be properly received, in order, whichever backend is in use.
     ///
     /// # Examples
     ///
@@ -176,7 +513,13 @@ impl<S, R> Channel<S, R> {
     /// assert_eq!(Err(RecvError), recv.recv());
     /// ```
     pub fn recv(&self) -> Result<R, RecvError> {
-        self.receiver.recv()
+        let result = B::recv(&self.receiver);
+        if result.is_err() {
+            self.on_disconnect.fire();
+        }
+        #[cfg(feature = "tracing")]
+        tracing::trace!(channel = self.name.as_deref(), ok = result.is_ok(), "recv");
+        result
     }
 
     /// See mpsc::Receiver::try_recv.
@@ -204,76 +547,2485 @@ impl<S, R> Channel<S, R> {
     /// assert!(right.try_recv().is_err());
     /// ```
     pub fn try_recv(&self) -> Result<R, TryRecvError> {
-        self.receiver.try_recv()
+        let result = B::try_recv(&self.receiver);
+        if let Err(TryRecvError::Disconnected) = result {
+            self.on_disconnect.fire();
+        }
+        result
     }
-}
 
-/// Creates a bichannelrectional channel returning the left and right
-/// sides. Each side can send and receive from its counterpart
-///
-/// # Examples
-///
-/// ```
-/// let (left, right) = bichannel::channel::<&'static str, &'static str>();
-///
-/// left.send("ping").unwrap();
-///
-/// assert_eq!(right.recv().unwrap(), "ping");
-/// ```
-pub fn channel<T, U>() -> (Channel<T, U>, Channel<U, T>) {
-    let (ls, lr) = create_channel();
-    let (rs, rr) = create_channel();
+    /// Sends each value of `items` to the other side in order, stopping at the first
+    /// one that could not be sent.
+    ///
+    /// On failure, the value that could not be sent is handed back inside the error,
+    /// and the remaining values are dropped unsent. With [`sync_channel`], this blocks
+    /// on each value that does not fit in the buffer.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// let (left, right) = bichannel::channel::<u8, u8>();
+    ///
+    /// left.send_all(vec![1, 2, 3]).unwrap();
+    /// assert_eq!(right.try_iter().collect::<Vec<_>>(), vec![1, 2, 3]);
+    ///
+    /// drop(right);
+    /// assert_eq!(left.send_all(vec![4, 5]).unwrap_err().0, 4);
+    /// ```
+    pub fn send_all<I: IntoIterator<Item = S>>(&self, items: I) -> Result<(), SendError<S>> {
+        items.into_iter().try_for_each(|s| self.send(s))
+    }
 
-    (
-        Channel {
-            sender: ls,
-            receiver: rr,
-        },
-        Channel {
-            sender: rs,
-            receiver: lr,
-        },
-    )
-}
+    /// Closes the sending half of this side, leaving the receiving half intact.
+    ///
+    /// Once closed, the other side's `recv` returns [`RecvError`] after draining
+    /// anything already sent and its [`is_disconnected`] returns `true`, while this
+    /// side can keep receiving messages still in flight from the other direction.
+    /// Subsequent sends from this side fail and hand the value back.
+    ///
+    /// With the `crossbeam` or `flume` feature, clones of this side share the sender: the
+    /// other side only observes the disconnect once every clone has closed or dropped.
+    /// Handles upgraded from a `WeakChannel` afterwards have their sender closed.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use bichannel::RecvError;
+    ///
+    /// let (mut left, right) = bichannel::channel::<u8, u8>();
+    ///
+    /// right.send(1).unwrap();
+    /// left.close_sender();
+    ///
+    /// assert!(right.is_disconnected());
+    /// assert_eq!(right.recv(), Err(RecvError));
+    /// assert_eq!(left.recv(), Ok(1));
+    /// assert_eq!(left.send(2).unwrap_err().0, 2);
+    /// ```
+    ///
+    /// [`is_disconnected`]: Self::is_disconnected
+    pub fn close_sender(&mut self) {
+        #[cfg(any(feature = "crossbeam", feature = "flume"))]
+        self.retire_link_sender();
+        let (closed, _) = B::channel();
+        self.sender = closed;
+        self.alive.close();
+    }
 
-#[cfg(test)]
-mod examples {
+    /// Closes the sending half of this side, then collects the messages still arriving
+    /// from the other side until it hangs up or `drain_timeout` elapses, and drops this
+    /// side.
+    ///
+    /// This is a graceful teardown: the other side observes the close as soon as
+    /// [`close_sender`] would let it, and whatever it sends before hanging up is
+    /// returned rather than lost. Messages sent after the timeout are discarded.
+    ///
+    /// [`close_sender`]: Self::close_sender
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::time::Duration;
+    ///
+    /// let (left, right) = bichannel::channel::<u8, u8>();
+    ///
+    /// right.send(1).unwrap();
+    /// right.send(2).unwrap();
+    /// drop(right);
+    ///
+    /// assert_eq!(left.shutdown(Duration::from_secs(1)), [1, 2]);
+    /// ```
+    pub fn shutdown(mut self, drain_timeout: Duration) -> Vec<R> {
+        self.close_sender();
 
-    #[test]
-    fn test_threaded_scenario() {
-        let (thread, main) = crate::channel();
+        let deadline = deadline(drain_timeout);
+        let mut drained = Vec::new();
+        while let Ok(r) = self.recv_until(deadline) {
+            drained.push(r);
+        }
+        drained
+    }
 
-        let handle = std::thread::spawn(move || loop {
-            match main.try_recv() {
-                Ok("stop") => break "stopped",
-                Err(crate::TryRecvError::Empty) => (),
-                _ => main.send("cant stop").unwrap(),
-            }
-        });
+    /// Returns the name given to this side by [`with_name`] or [`ChannelBuilder::name`],
+    /// if any.
+    ///
+    /// [`with_name`]: Self::with_name
+    pub fn name(&self) -> Option<&str> {
+        self.name.as_deref()
+    }
 
-        thread.send("slow down").unwrap();
-        assert_eq!(thread.recv().unwrap(), "cant stop");
+    /// Names this side, replacing any previous name. The name is shown by the `Debug`
+    /// output, and does not affect the other side.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// let (left, right) = bichannel::channel::<u8, u8>();
+    /// let left = left.with_name("control");
+    ///
+    /// assert_eq!(format!("{:?}", left), r#"Channel { name: "control", .. }"#);
+    /// assert_eq!(right.name(), None);
+    /// ```
+    pub fn with_name(mut self, name: &str) -> Self {
+        self.name = Some(name.into());
+        self
+    }
 
-        thread.send("stop").unwrap();
-        assert_eq!(handle.join().unwrap(), "stopped");
+    /// Returns an identifier shared by both sides of this channel, and by no side of
+    /// any other live channel.
+    ///
+    /// The identifier comes from the allocations that track whether each side is
+    /// alive, so it may be reused once both sides of this channel have been dropped.
+    /// The identifier of a side recombined with [`join`] from the halves of two
+    /// different channels is unspecified.
+    ///
+    /// [`join`]: Self::join
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// let (left, right) = bichannel::channel::<u8, u8>();
+    /// let (other, _) = bichannel::channel::<u8, u8>();
+    ///
+    /// assert_eq!(left.pair_id(), right.pair_id());
+    /// assert_ne!(left.pair_id(), other.pair_id());
+    /// ```
+    pub fn pair_id(&self) -> usize {
+        self.alive.id().min(self.peer.id())
+    }
+
+    /// Sends a request to the other side, then blocks until it responds.
+    ///
+    /// This is a convenience for the common request/response pattern of a [`send`]
+    /// followed by a [`recv`]. If the request could not be sent, it is handed back in
+    /// [`CallError::Send`].
+    ///
+    /// [`send`]: Self::send
+    /// [`recv`]: Self::recv
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::thread;
+    ///
+    /// let (client, server) = bichannel::channel::<u8, u8>();
+    ///
+    /// thread::spawn(move || {
+    ///     for req in server.iter() {
+    ///         server.send(req * 2).unwrap();
+    ///     }
+    /// });
+    ///
+    /// assert_eq!(client.call(21), Ok(42));
+    /// ```
+    pub fn call(&self, req: S) -> Result<R, CallError<S>> {
+        self.send(req)?;
+        Ok(self.recv()?)
+    }
+
+    /// Sends a request to the other side, then waits up to `timeout` for it to
+    /// respond.
+    ///
+    /// The timeout only applies to waiting for the response; the request is sent
+    /// immediately. If the timeout elapses, a response that arrives late is left
+    /// buffered, and would be returned by the next receive on this side. Use
+    /// [`call_timeout_draining`] to discard late responses instead.
+    ///
+    /// [`call_timeout_draining`]: Self::call_timeout_draining
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::time::Duration;
+    /// use bichannel::CallTimeoutError;
+    ///
+    /// let (client, _server) = bichannel::channel::<u8, u8>();
+    ///
+    /// assert_eq!(
+    ///     client.call_timeout(1, Duration::from_millis(10)),
+    ///     Err(CallTimeoutError::Timeout)
+    /// );
+    /// ```
+    pub fn call_timeout(&self, req: S, timeout: Duration) -> Result<R, CallTimeoutError<S>> {
+        self.send(req)?;
+        Ok(self.recv_timeout(timeout)?)
+    }
+
+    /// Like [`call_timeout`], but first discards every message already buffered from
+    /// the other side, such as a late response to an earlier call that timed out.
+    ///
+    /// Only use this when the other side sends nothing but responses, as any other
+    /// buffered message is discarded along with them.
+    ///
+    /// [`call_timeout`]: Self::call_timeout
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::time::Duration;
+    ///
+    /// let (client, server) = bichannel::channel::<u8, u8>();
+    ///
+    /// // a late response to an earlier call
+    /// server.send(0).unwrap();
+    ///
+    /// std::thread::spawn(move || {
+    ///     for req in server.iter() {
+    ///         server.send(req * 2).unwrap();
+    ///     }
+    /// });
+    ///
+    /// assert_eq!(client.call_timeout_draining(21, Duration::from_secs(1)), Ok(42));
+    /// ```
+    pub fn call_timeout_draining(
+        &self,
+        req: S,
+        timeout: Duration,
+    ) -> Result<R, CallTimeoutError<S>> {
+        self.try_iter().for_each(drop);
+        self.call_timeout(req, timeout)
+    }
+
+    /// Blocks for a message from the other side, returning `default` instead once the
+    /// other side has hung up.
+    ///
+    /// See [`recv_or_else`] to compute the fallback only when it is needed.
+    ///
+    /// [`recv_or_else`]: Self::recv_or_else
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// let (left, right) = bichannel::channel::<(), &str>();
+    ///
+    /// right.send("work").unwrap();
+    /// drop(right);
+    ///
+    /// assert_eq!(left.recv_or("stop"), "work");
+    /// assert_eq!(left.recv_or("stop"), "stop");
+    /// ```
+    pub fn recv_or(&self, default: R) -> R {
+        self.recv().unwrap_or(default)
+    }
+
+    /// Blocks for a message from the other side, returning the result of `f` instead
+    /// once the other side has hung up.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// let (left, right) = bichannel::channel::<(), Vec<u8>>();
+    ///
+    /// drop(right);
+    /// assert_eq!(left.recv_or_else(|| vec![0; 4]), [0; 4]);
+    /// ```
+    pub fn recv_or_else<F>(&self, f: F) -> R
+    where
+        F: FnOnce() -> R,
+    {
+        self.recv().unwrap_or_else(|_| f())
+    }
+
+    /// See mpsc::Receiver::recv_timeout
+    ///
+    /// Attempts to wait for a value from the other side, returning an error if the
+    /// other side has hung up, or if it waits more than `timeout`.
+    ///
+    /// This function will always block the current thread if there is no data
+    /// available and it's possible for more data to be sent. Once a message is
+    /// sent from the other side then this will wake up and return that message.
+    ///
+    /// If the corresponding channel has disconnected, or it disconnects while
+    /// this call is blocking, this call will wake up and return [`Err`] to
+    /// indicate that no more messages can ever be received on this channel.
+    /// However, since channels are buffered, messages sent before the disconnect
+    /// will still be properly received.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::time::{Duration, Instant};
+    /// use bichannel::RecvTimeoutError;
+    ///
+    /// let (left, right) = bichannel::channel::<u8, u8>();
+    ///
+    /// assert_eq!(
+    ///     Err(RecvTimeoutError::Timeout),
+    ///     left.recv_timeout(Duration::from_millis(10))
+    /// );
+    ///
+    /// right.send(1).unwrap();
+    /// assert_eq!(Ok(1), left.recv_timeout(Duration::from_millis(10)));
+    /// ```
+    pub fn recv_timeout(&self, timeout: Duration) -> Result<R, RecvTimeoutError> {
+        let result = B::recv_timeout(&self.receiver, timeout);
+        if let Err(RecvTimeoutError::Disconnected) = result {
+            self.on_disconnect.fire();
+        }
+        result
+    }
+
+    /// See crossbeam_channel::Receiver::recv_deadline
+    ///
+    /// Attempts to wait for a value from the other side, returning an error if the
+    /// other side has hung up, or if `deadline` is reached.
+    ///
+    /// This behaves like [`recv_timeout`], but is convenient when a single
+    /// deadline bounds several receives. A value that is already buffered is
+    /// returned even if the deadline has passed; otherwise a deadline in the
+    /// past returns [`RecvTimeoutError::Timeout`] immediately.
+    ///
+    /// [`recv_timeout`]: Self::recv_timeout
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::time::{Duration, Instant};
+    /// use bichannel::RecvTimeoutError;
+    ///
+    /// let (left, right) = bichannel::channel::<u8, u8>();
+    ///
+    /// let deadline = Instant::now() + Duration::from_millis(10);
+    ///
+    /// right.send(1).unwrap();
+    /// assert_eq!(Ok(1), left.recv_deadline(deadline));
+    /// assert_eq!(Err(RecvTimeoutError::Timeout), left.recv_deadline(deadline));
+    /// ```
+    pub fn recv_deadline(&self, deadline: Instant) -> Result<R, RecvTimeoutError> {
+        let result = B::recv_deadline(&self.receiver, deadline);
+        if let Err(RecvTimeoutError::Disconnected) = result {
+            self.on_disconnect.fire();
+        }
+        result
     }
 
-    //    #[test]
-    //    fn test_arc_scenario() {
-    //        let (l, r) = crate::channel::<i8, i8>();
-    //
-    //        let wrapped = std::sync::Arc::new(l);
-    //
-    //        {
-    //            let wrapped = wrapped.clone();
-    //            std::thread::spawn(move || {
-    //                wrapped.recv().unwrap();
-    //            });
-    //        }
-    //
-    //        wrapped.recv().unwrap();
-    //    }
-    //
+    /// Waits like [`recv_deadline`](Self::recv_deadline) for a deadline from
+    /// [`deadline`], or like [`recv`](Self::recv) if there is none.
+    pub(crate) fn recv_until(&self, deadline: Option<Instant>) -> Result<R, RecvTimeoutError> {
+        match deadline {
+            Some(deadline) => self.recv_deadline(deadline),
+            None => self.recv().map_err(Into::into),
+        }
+    }
+
+    /// Attempts to wait for a value from the other side for at most `budget`,
+    /// reporting a timeout as [`TryRecvError::Empty`].
+    ///
+    /// This waits like [`recv_timeout`], but fails with the same error as
+    /// [`try_recv`], so a short wait can stand in for a poll without changing how
+    /// errors are handled.
+    ///
+    /// [`recv_timeout`]: Self::recv_timeout
+    /// [`try_recv`]: Self::try_recv
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::time::Duration;
+    /// use bichannel::TryRecvError;
+    ///
+    /// let (left, right) = bichannel::channel::<u8, u8>();
+    ///
+    /// assert_eq!(left.try_recv_for(Duration::from_millis(10)), Err(TryRecvError::Empty));
+    ///
+    /// right.send(1).unwrap();
+    /// assert_eq!(left.try_recv_for(Duration::from_millis(10)), Ok(1));
+    /// ```
+    pub fn try_recv_for(&self, budget: Duration) -> Result<R, TryRecvError> {
+        self.recv_timeout(budget).map_err(Into::into)
+    }
+
+    /// Blocks for a message from the other side, then receives up to `max - 1` more
+    /// that are already buffered without blocking, returning them in order.
+    ///
+    /// Batching amortizes the cost of receiving under high throughput. If the other side
+    /// hangs up with nothing buffered, this returns [`RecvError`]; messages buffered
+    /// before it hung up are still returned. A `max` of zero returns an empty batch
+    /// without blocking.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// let (left, right) = bichannel::channel::<u8, u8>();
+    ///
+    /// for i in 0..5 {
+    ///     right.send(i).unwrap();
+    /// }
+    ///
+    /// assert_eq!(left.recv_many(3), Ok(vec![0, 1, 2]));
+    /// assert_eq!(left.recv_many(3), Ok(vec![3, 4]));
+    /// ```
+    pub fn recv_many(&self, max: usize) -> Result<Vec<R>, RecvError> {
+        if max == 0 {
+            return Ok(Vec::new());
+        }
+
+        let mut batch = vec![self.recv()?];
+        batch.extend(self.try_iter().take(max - 1));
+        Ok(batch)
+    }
+
+    /// Receives every message already buffered from the other side without blocking,
+    /// returning them in order.
+    ///
+    /// Unlike [`recv_many`], this never waits, and returns an empty batch if nothing is
+    /// buffered. Messages sent before the other side hung up are still returned.
+    ///
+    /// [`recv_many`]: Self::recv_many
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// let (left, right) = bichannel::channel::<u8, u8>();
+    ///
+    /// assert!(left.try_recv_all().is_empty());
+    ///
+    /// right.send_all(vec![1, 2, 3]).unwrap();
+    /// drop(right);
+    ///
+    /// assert_eq!(left.try_recv_all(), [1, 2, 3]);
+    /// assert!(left.try_recv_all().is_empty());
+    /// ```
+    pub fn try_recv_all(&self) -> Vec<R> {
+        self.try_iter().collect()
+    }
+
+    /// See mpsc::Receiver::iter
+    ///
+    /// Returns an iterator that will block waiting for messages from the other side,
+    /// but never [`panic!`]. It will return [`None`] when the other side has hung up.
+    ///
+    /// Because the iterator only ends once the other side hangs up, iterating on a
+    /// thread that also holds the other side never ends: the loop blocks waiting on a
+    /// side that cannot be dropped until it finishes. The same goes for two sides
+    /// iterating each other, as each keeps its own sending half alive. Drop the other
+    /// side first, or consume this one with [`drain`] or [`into_iter`], which drop
+    /// this side's sending half before waiting.
+    ///
+    /// [`drain`]: Self::drain
+    /// [`into_iter`]: IntoIterator::into_iter
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// let (left, right) = bichannel::channel::<u8, u8>();
+    ///
+    /// right.send(1).unwrap();
+    /// right.send(2).unwrap();
+    /// right.send(3).unwrap();
+    /// drop(right);
+    ///
+    /// let mut received = Vec::new();
+    /// for msg in left.iter() {
+    ///     received.push(msg);
+    /// }
+    ///
+    /// assert_eq!(received, vec![1, 2, 3]);
+    /// ```
+    pub fn iter(&self) -> Iter<'_, R, B> {
+        Iter {
+            receiver: &self.receiver,
+            on_disconnect: Some(&self.on_disconnect),
+        }
+    }
+
+    /// See mpsc::Receiver::try_iter
+    ///
+    /// Returns an iterator that will attempt to yield all pending messages from the
+    /// other side. It will return [`None`] if there are no more pending values or if
+    /// the other side has hung up. The iterator will never [`panic!`] or block the
+    /// user by waiting for values.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// let (left, right) = bichannel::channel::<u8, u8>();
+    ///
+    /// // nothing is buffered, so this returns immediately
+    /// assert_eq!(left.try_iter().next(), None);
+    ///
+    /// right.send(1).unwrap();
+    /// right.send(2).unwrap();
+    ///
+    /// assert_eq!(left.try_iter().collect::<Vec<_>>(), vec![1, 2]);
+    /// ```
+    pub fn try_iter(&self) -> TryIter<'_, R, B> {
+        TryIter {
+            receiver: &self.receiver,
+            on_disconnect: Some(&self.on_disconnect),
+        }
+    }
+
+    /// Consumes this side, folding every remaining message from the other side into
+    /// an accumulator, and returns it once the other side has hung up.
+    ///
+    /// As with [`into_iter`], the sending half is dropped first, so two sides folding
+    /// each other cannot deadlock.
+    ///
+    /// [`into_iter`]: IntoIterator::into_iter
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::thread;
+    ///
+    /// let (left, right) = bichannel::channel::<(), u32>();
+    ///
+    /// thread::spawn(move || {
+    ///     for i in 1..=4 {
+    ///         right.send(i).unwrap();
+    ///     }
+    /// });
+    ///
+    /// assert_eq!(left.fold(0, |sum, i| sum + i), 10);
+    /// ```
+    pub fn fold<T, F>(self, init: T, f: F) -> T
+    where
+        F: FnMut(T, R) -> T,
+    {
+        self.into_iter().fold(init, f)
+    }
+
+    /// Consumes this side, calling `f` on every remaining message from the other side,
+    /// and returns once the other side has hung up.
+    ///
+    /// As with [`into_iter`], the sending half is dropped first, so two sides
+    /// consuming each other cannot deadlock.
+    ///
+    /// [`into_iter`]: IntoIterator::into_iter
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// let (left, right) = bichannel::channel::<(), &str>();
+    ///
+    /// right.send("a").unwrap();
+    /// right.send("b").unwrap();
+    /// drop(right);
+    ///
+    /// let mut seen = Vec::new();
+    /// left.for_each(|m| seen.push(m));
+    /// assert_eq!(seen, ["a", "b"]);
+    /// ```
+    pub fn for_each<F>(self, f: F)
+    where
+        F: FnMut(R),
+    {
+        self.into_iter().for_each(f)
+    }
+
+    /// Consumes this side, collecting every remaining message from the other side,
+    /// and returns them once the other side has hung up.
+    ///
+    /// The sending half is dropped before waiting, so the other side observes the
+    /// hang up, and draining is guaranteed to end as soon as the other side is gone,
+    /// even if it is draining this side in turn. Unlike [`iter`], this cannot wait on
+    /// a sender it keeps alive itself.
+    ///
+    /// [`iter`]: Self::iter
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::thread;
+    ///
+    /// let (left, right) = bichannel::channel::<u8, u8>();
+    ///
+    /// left.send(1).unwrap();
+    /// right.send(2).unwrap();
+    ///
+    /// // Each side drains the other; neither waits on its own sender.
+    /// let drained = thread::spawn(move || right.drain());
+    /// assert_eq!(left.drain(), [2]);
+    /// assert_eq!(drained.join().unwrap(), [1]);
+    /// ```
+    pub fn drain(self) -> Vec<R> {
+        self.into_iter().collect()
+    }
+}
+
+#[cfg(feature = "std")]
+impl<S, R> Channel<S, R> {
+    /// Attempts to send a value to the other side, waiting at most `timeout` for room
+    /// in a full buffer.
+    ///
+    /// This differs from [`send`] only for channels created with [`sync_channel`],
+    /// where the value is handed back inside the error if the buffer stays full for
+    /// the whole timeout, so that it can be retried. With the std backend, a full
+    /// buffer is checked again every millisecond rather than waited on.
+    ///
+    /// [`send`]: Self::send
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::time::Duration;
+    /// use bichannel::SendTimeoutError;
+    ///
+    /// let (left, right) = bichannel::sync_channel::<u8, u8>(1);
+    ///
+    /// left.send(1).unwrap();
+    /// assert_eq!(
+    ///     left.send_timeout(2, Duration::from_millis(10)),
+    ///     Err(SendTimeoutError::Timeout(2))
+    /// );
+    ///
+    /// assert_eq!(right.recv(), Ok(1));
+    /// left.send_timeout(2, Duration::from_millis(10)).unwrap();
+    /// ```
+    pub fn send_timeout(&self, s: S, timeout: Duration) -> Result<(), SendTimeoutError<S>> {
+        let result = match self.cork.hold(s) {
+            Some(s) => self.sender.send_timeout(s, timeout),
+            None => Ok(()),
+        };
+        if let Err(SendTimeoutError::Disconnected(_)) = result {
+            self.on_disconnect.fire();
+        }
+        result
+    }
+
+    /// Returns a new sending half that sends to the same counterpart as this side.
+    ///
+    /// This allows several producers to feed one direction of the channel while this
+    /// side keeps the sole receiver, without needing the `crossbeam` or `flume` feature. The
+    /// counterpart does not observe a disconnect until this side and every sending
+    /// half cloned from it have been dropped. Sending halves are never corked, so
+    /// values sent through one are not held back by [`cork`](Self::cork).
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// let (left, right) = bichannel::channel::<u8, u8>();
+    ///
+    /// let sender = left.clone_sender();
+    /// sender.send(1).unwrap();
+    /// left.send(2).unwrap();
+    ///
+    /// assert_eq!(right.recv(), Ok(1));
+    /// assert_eq!(right.recv(), Ok(2));
+    /// ```
+    pub fn clone_sender(&self) -> SendHalf<S> {
+        SendHalf {
+            sender: self.sender.clone(),
+            alive: self.alive.clone(),
+        }
+    }
+
+    /// Splits this side into its sending and receiving halves, so that each can be
+    /// moved to a different thread. The halves can be recombined with [`join`]. Values
+    /// held back by [`cork`] are sent first.
+    ///
+    /// [`join`]: Self::join
+    /// [`cork`]: Self::cork
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// let (left, right) = bichannel::channel::<u8, u8>();
+    ///
+    /// let (sender, receiver) = left.split();
+    ///
+    /// sender.send(1).unwrap();
+    /// assert_eq!(right.recv(), Ok(1));
+    ///
+    /// right.send(2).unwrap();
+    /// assert_eq!(receiver.recv(), Ok(2));
+    /// ```
+    pub fn split(self) -> (SendHalf<S>, RecvHalf<R>) {
+        let _ = self.uncork();
+        (
+            SendHalf {
+                sender: self.sender,
+                alive: self.alive,
+            },
+            RecvHalf {
+                receiver: self.receiver,
+                peer: self.peer,
+            },
+        )
+    }
+
+    /// Recombines a sending and a receiving half into a single side, the inverse of
+    /// [`split`].
+    ///
+    /// [`split`]: Self::split
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use bichannel::Channel;
+    ///
+    /// let (left, right) = bichannel::channel::<u8, u8>();
+    ///
+    /// let (sender, receiver) = left.split();
+    /// let left = Channel::join(sender, receiver);
+    ///
+    /// left.send(1).unwrap();
+    /// assert_eq!(right.recv(), Ok(1));
+    /// ```
+    pub fn join(sender: SendHalf<S>, receiver: RecvHalf<R>) -> Channel<S, R> {
+        Channel::assemble(
+            sender.sender,
+            receiver.receiver,
+            sender.alive,
+            receiver.peer,
+        )
+    }
+
+    /// Assembles a side from a raw sender and receiver created outside this crate, such
+    /// as the ends of two `std::sync::mpsc` channels owned by existing code.
+    ///
+    /// A side assembled this way cannot tell when the other end hangs up until its
+    /// sends or receives fail, so [`is_disconnected`] always returns `false`. Async
+    /// tasks polling it are not woken by the other end, and poll again every
+    /// millisecond instead.
+    ///
+    /// [`is_disconnected`]: Self::is_disconnected
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::sync::mpsc;
+    /// use bichannel::Channel;
+    ///
+    /// let (to_right, from_left) = mpsc::channel();
+    /// let (to_left, from_right) = mpsc::channel();
+    ///
+    /// let left = Channel::from_parts(to_right, from_right);
+    /// let right = Channel::from_parts(to_left, from_left);
+    ///
+    /// left.send("ping").unwrap();
+    /// assert_eq!(right.recv(), Ok("ping"));
+    ///
+    /// right.send("pong").unwrap();
+    /// assert_eq!(left.recv(), Ok("pong"));
+    /// ```
+    #[cfg(not(any(feature = "crossbeam", feature = "flume")))]
+    pub fn from_parts(sender: mpsc::Sender<S>, receiver: mpsc::Receiver<R>) -> Channel<S, R> {
+        Channel::adopt(
+            backend::adopt_sender(sender),
+            backend::adopt_receiver(receiver),
+        )
+    }
+
+    /// Assembles a side from a raw sender and receiver created outside this crate, such
+    /// as the ends of two `crossbeam_channel` channels owned by existing code.
+    ///
+    /// A side assembled this way cannot tell when the other end hangs up until its
+    /// sends or receives fail, so [`is_disconnected`] always returns `false`. Async
+    /// tasks polling it are not woken by the other end, and poll again every
+    /// millisecond instead.
+    ///
+    /// [`is_disconnected`]: Self::is_disconnected
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use bichannel::Channel;
+    ///
+    /// let (to_right, from_left) = crossbeam_channel::unbounded();
+    /// let (to_left, from_right) = crossbeam_channel::bounded(1);
+    ///
+    /// let left = Channel::from_parts(to_right, from_right);
+    /// let right = Channel::from_parts(to_left, from_left);
+    ///
+    /// left.send("ping").unwrap();
+    /// assert_eq!(right.recv(), Ok("ping"));
+    ///
+    /// right.send("pong").unwrap();
+    /// assert_eq!(left.recv(), Ok("pong"));
+    /// ```
+    #[cfg(feature = "crossbeam")]
+    pub fn from_parts(
+        sender: crossbeam_channel::Sender<S>,
+        receiver: crossbeam_channel::Receiver<R>,
+    ) -> Channel<S, R> {
+        Channel::adopt(
+            backend::adopt_sender(sender),
+            backend::adopt_receiver(receiver),
+        )
+    }
+
+    /// Takes this side apart into its raw sender and receiver, the inverse of
+    /// [`from_parts`], for code that needs the `std::sync::mpsc` types themselves.
+    ///
+    /// `std::sync::mpsc` has distinct sender types for unbounded and bounded channels,
+    /// so the sender is returned as an [`MpscSender`], holding a `SyncSender` for
+    /// channels created with [`sync_channel`] or [`bounded_with`]. The raw sender
+    /// blocks when a bounded buffer is full, whatever the overflow policy.
+    ///
+    /// Values held back by [`cork`] are sent first. The raw halves carry none of the
+    /// bookkeeping that tracks whether this side is alive, so the other side's
+    /// [`is_disconnected`] returns `true` as soon as the parts are taken, although
+    /// its sends and receives keep working until both raw halves have been dropped.
+    ///
+    /// Fails, handing the side back, if either half cannot be represented by the raw
+    /// type without losing messages, so that none are dropped unseen:
+    ///
+    /// * the other side was created with [`OverflowPolicy::DropOldest`], which evicts
+    ///   through this side's receiver;
+    /// * messages set aside by [`recv_matching`] are still waiting to be received.
+    ///
+    /// [`from_parts`]: Self::from_parts
+    /// [`cork`]: Self::cork
+    /// [`is_disconnected`]: Self::is_disconnected
+    /// [`recv_matching`]: Self::recv_matching
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use bichannel::MpscSender;
+    ///
+    /// let (left, right) = bichannel::channel::<u8, u8>();
+    ///
+    /// let (sender, receiver) = left.into_parts().unwrap();
+    ///
+    /// sender.send(1).unwrap();
+    /// assert_eq!(right.recv(), Ok(1));
+    ///
+    /// right.send(2).unwrap();
+    /// assert_eq!(receiver.recv(), Ok(2));
+    ///
+    /// let (bounded, _) = bichannel::sync_channel::<u8, u8>(1);
+    /// let (sender, _) = bounded.into_parts().unwrap();
+    /// assert!(matches!(sender, MpscSender::Bounded(_)));
+    /// ```
+    // The side is handed back whole on failure, as with `Arc::try_unwrap`.
+    #[cfg(not(any(feature = "crossbeam", feature = "flume")))]
+    #[allow(clippy::result_large_err)]
+    pub fn into_parts(self) -> Result<(MpscSender<S>, mpsc::Receiver<R>), Self> {
+        let _ = self.uncork();
+        if !self.sender.is_plain() || !self.receiver.is_plain() {
+            return Err(self);
+        }
+
+        Ok((self.sender.into_raw(), self.receiver.into_raw()))
+    }
+
+    /// Takes this side apart into its raw sender and receiver, the inverse of
+    /// [`from_parts`], for code that needs the `crossbeam_channel` types themselves.
+    ///
+    /// The raw sender blocks when a bounded buffer is full, whatever the overflow
+    /// policy.
+    ///
+    /// Values held back by [`cork`] are sent first. The raw halves carry none of the
+    /// bookkeeping that tracks whether this side is alive, so unless clones of this
+    /// side remain, the other side's [`is_disconnected`] returns `true` as soon as the
+    /// parts are taken, although its sends and receives keep working until both raw
+    /// halves, and any clones, have been dropped.
+    ///
+    /// Fails, handing the side back, if either half cannot be represented by the raw
+    /// type without losing messages, so that none are dropped unseen:
+    ///
+    /// * the other side was created with [`OverflowPolicy::DropOldest`], which evicts
+    ///   through this side's receiver;
+    /// * messages set aside by [`recv_matching`] are still waiting to be received.
+    ///
+    /// [`from_parts`]: Self::from_parts
+    /// [`cork`]: Self::cork
+    /// [`is_disconnected`]: Self::is_disconnected
+    /// [`recv_matching`]: Self::recv_matching
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// let (left, right) = bichannel::channel::<u8, u8>();
+    ///
+    /// let (sender, receiver) = left.into_parts().unwrap();
+    ///
+    /// sender.send(1).unwrap();
+    /// assert_eq!(right.recv(), Ok(1));
+    ///
+    /// right.send(2).unwrap();
+    /// assert_eq!(receiver.recv(), Ok(2));
+    /// ```
+    #[cfg(feature = "crossbeam")]
+    #[allow(clippy::result_large_err)]
+    pub fn into_parts(
+        self,
+    ) -> Result<(crossbeam_channel::Sender<S>, crossbeam_channel::Receiver<R>), Self> {
+        let _ = self.uncork();
+        if !self.sender.is_plain() || !self.receiver.is_plain() {
+            return Err(self);
+        }
+
+        Ok((self.sender.into_raw(), self.receiver.into_raw()))
+    }
+
+    /// Assembles a side from a raw sender and receiver created outside this crate, such
+    /// as the ends of two `flume` channels owned by existing code.
+    ///
+    /// A side assembled this way cannot tell when the other end hangs up until its
+    /// sends or receives fail, so [`is_disconnected`] always returns `false`. Async
+    /// tasks polling it are not woken by the other end, and poll again every
+    /// millisecond instead.
+    ///
+    /// [`is_disconnected`]: Self::is_disconnected
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use bichannel::Channel;
+    ///
+    /// let (to_right, from_left) = flume::unbounded();
+    /// let (to_left, from_right) = flume::bounded(1);
+    ///
+    /// let left = Channel::from_parts(to_right, from_right);
+    /// let right = Channel::from_parts(to_left, from_left);
+    ///
+    /// left.send("ping").unwrap();
+    /// assert_eq!(right.recv(), Ok("ping"));
+    ///
+    /// right.send("pong").unwrap();
+    /// assert_eq!(left.recv(), Ok("pong"));
+    /// ```
+    #[cfg(all(feature = "flume", not(feature = "crossbeam")))]
+    pub fn from_parts(sender: flume::Sender<S>, receiver: flume::Receiver<R>) -> Channel<S, R> {
+        Channel::adopt(
+            backend::adopt_sender(sender),
+            backend::adopt_receiver(receiver),
+        )
+    }
+
+    /// Takes this side apart into its raw sender and receiver, the inverse of
+    /// [`from_parts`], for code that needs the `flume` types themselves.
+    ///
+    /// The raw sender blocks when a bounded buffer is full, whatever the overflow
+    /// policy.
+    ///
+    /// Values held back by [`cork`] are sent first. The raw halves carry none of the
+    /// bookkeeping that tracks whether this side is alive, so unless clones of this
+    /// side remain, the other side's [`is_disconnected`] returns `true` as soon as the
+    /// parts are taken, although its sends and receives keep working until both raw
+    /// halves, and any clones, have been dropped.
+    ///
+    /// Fails, handing the side back, if either half cannot be represented by the raw
+    /// type without losing messages, so that none are dropped unseen:
+    ///
+    /// * the other side was created with [`OverflowPolicy::DropOldest`], which evicts
+    ///   through this side's receiver;
+    /// * messages set aside by [`recv_matching`] are still waiting to be received.
+    ///
+    /// [`from_parts`]: Self::from_parts
+    /// [`cork`]: Self::cork
+    /// [`is_disconnected`]: Self::is_disconnected
+    /// [`recv_matching`]: Self::recv_matching
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// let (left, right) = bichannel::channel::<u8, u8>();
+    ///
+    /// let (sender, receiver) = left.into_parts().unwrap();
+    ///
+    /// sender.send(1).unwrap();
+    /// assert_eq!(right.recv(), Ok(1));
+    ///
+    /// right.send(2).unwrap();
+    /// assert_eq!(receiver.recv(), Ok(2));
+    /// ```
+    #[cfg(all(feature = "flume", not(feature = "crossbeam")))]
+    #[allow(clippy::result_large_err)]
+    pub fn into_parts(self) -> Result<(flume::Sender<S>, flume::Receiver<R>), Self> {
+        let _ = self.uncork();
+        if !self.sender.is_plain() || !self.receiver.is_plain() {
+            return Err(self);
+        }
+
+        Ok((self.sender.into_raw(), self.receiver.into_raw()))
+    }
+
+    fn adopt(sender: Sender<S>, receiver: Receiver<R>) -> Channel<S, R> {
+        let alive = Alive::new();
+        // With no counterpart to watch, the side watches itself, so that it does not
+        // appear disconnected while it can send.
+        let peer = alive.peer();
+
+        Channel::assemble(sender, receiver, alive, peer)
+    }
+
+    /// Sends `reason` as a final message to the other side, then drops this side.
+    ///
+    /// The other side receives the reason after everything sent before it, and then
+    /// observes the hang up, so a shutdown protocol can tell it why this side left.
+    /// Messages held back by [`cork`] are sent first. If the other side has already
+    /// hung up, the reason is handed back inside the error.
+    ///
+    /// With the `crossbeam` or `flume` feature, the other side only observes the hang up
+    /// once every clone of this side has been dropped too.
+    ///
+    /// [`cork`]: Self::cork
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use bichannel::RecvError;
+    ///
+    /// let (left, right) = bichannel::channel::<&str, &str>();
+    ///
+    /// left.close_with("bye").unwrap();
+    ///
+    /// assert_eq!(right.recv(), Ok("bye"));
+    /// assert_eq!(right.recv(), Err(RecvError));
+    /// ```
+    pub fn close_with(self, reason: S) -> Result<(), SendError<S>> {
+        let _ = self.uncork();
+        let result = self.sender.send(reason);
+        if result.is_err() {
+            self.on_disconnect.fire();
+        }
+        result
+    }
+
+    /// Blocks until a message matching `pred` is received, returning the first one.
+    ///
+    /// Messages that do not match are set aside rather than dropped, and every later
+    /// receive on this side returns them first, in the order they arrived. Returns
+    /// [`Err`] once the other side has hung up without a match being found.
+    ///
+    /// With the `crossbeam` feature, messages that have been set aside do not wake a
+    /// `Select`, so they are only returned once another message arrives or a
+    /// receive is attempted directly.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// let (left, right) = bichannel::channel::<(), (u8, &str)>();
+    ///
+    /// right.send((1, "one")).unwrap();
+    /// right.send((2, "two")).unwrap();
+    ///
+    /// assert_eq!(left.recv_matching(|&(id, _)| id == 2), Ok((2, "two")));
+    /// assert_eq!(left.recv(), Ok((1, "one")));
+    /// ```
+    pub fn recv_matching<P>(&self, pred: P) -> Result<R, RecvError>
+    where
+        P: Fn(&R) -> bool,
+    {
+        let result = self.receiver.recv_matching(pred);
+        if result.is_err() {
+            self.on_disconnect.fire();
+        }
+        result
+    }
+
+    /// See crossbeam_channel::Receiver::len
+    ///
+    /// Returns the number of messages from the other side that are waiting to be
+    /// received on this side.
+    ///
+    /// Only available with the `crossbeam` or `flume` feature, as `std::sync::mpsc`
+    /// does not expose its queue depth.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// let (left, right) = bichannel::channel::<u8, u8>();
+    ///
+    /// right.send(1).unwrap();
+    /// right.send(2).unwrap();
+    ///
+    /// assert_eq!(left.len(), 2);
+    /// assert_eq!(right.len(), 0);
+    /// ```
+    #[cfg(any(feature = "crossbeam", feature = "flume"))]
+    pub fn len(&self) -> usize {
+        self.receiver.len()
+    }
+
+    /// See crossbeam_channel::Receiver::is_empty
+    ///
+    /// Returns `true` if there are no messages from the other side waiting to be
+    /// received on this side.
+    ///
+    /// Only available with the `crossbeam` or `flume` feature.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// let (left, right) = bichannel::channel::<u8, u8>();
+    ///
+    /// assert!(left.is_empty());
+    ///
+    /// right.send(1).unwrap();
+    /// assert!(!left.is_empty());
+    /// ```
+    #[cfg(any(feature = "crossbeam", feature = "flume"))]
+    pub fn is_empty(&self) -> bool {
+        self.receiver.is_empty()
+    }
+
+    /// Returns the bound of the buffer this side sends into, or `None` if it is
+    /// unbounded.
+    ///
+    /// Channels created with [`sync_channel`] or [`bounded_with`] report their bound,
+    /// which is zero for a rendezvous channel.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// let (left, _right) = bichannel::sync_channel::<u8, u8>(4);
+    /// assert_eq!(left.capacity(), Some(4));
+    ///
+    /// let (left, _right) = bichannel::channel::<u8, u8>();
+    /// assert_eq!(left.capacity(), None);
+    /// ```
+    pub fn capacity(&self) -> Option<usize> {
+        self.sender.capacity()
+    }
+
+    /// Returns the number of live senders to the other side, counting this side, its
+    /// clones and every half split or cloned from them.
+    ///
+    /// Like [`is_disconnected`], the count is only a snapshot.
+    ///
+    /// [`is_disconnected`]: Self::is_disconnected
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// let (left, _right) = bichannel::channel::<u8, u8>();
+    ///
+    /// let producer = left.clone_sender();
+    /// assert_eq!(left.sender_count(), 2);
+    ///
+    /// drop(producer);
+    /// assert_eq!(left.sender_count(), 1);
+    /// ```
+    pub fn sender_count(&self) -> usize {
+        self.sender.sender_count()
+    }
+
+    /// Returns the number of live receivers of messages from the other side, counting
+    /// this side, its clones and every half split or cloned from them.
+    ///
+    /// Only clones share a receiver, so this is always one without the `crossbeam` or
+    /// `flume` feature.
+    pub fn receiver_count(&self) -> usize {
+        self.receiver.receiver_count()
+    }
+}
+
+/// The raw sender of a side taken apart by [`Channel::into_parts`], without the
+/// `crossbeam` or `flume` feature. `std::sync::mpsc` uses different types for the
+/// senders of unbounded and bounded channels.
+#[cfg(all(feature = "std", not(any(feature = "crossbeam", feature = "flume"))))]
+#[derive(Debug)]
+pub enum MpscSender<S> {
+    /// The sender of a channel created with [`channel`].
+    Unbounded(mpsc::Sender<S>),
+    /// The sender of a channel created with [`sync_channel`] or [`bounded_with`].
+    Bounded(mpsc::SyncSender<S>),
+}
+
+#[cfg(all(feature = "std", not(any(feature = "crossbeam", feature = "flume"))))]
+impl<S> MpscSender<S> {
+    /// Sends a value with the underlying sender, blocking while a bounded buffer is
+    /// full.
+    pub fn send(&self, s: S) -> Result<(), mpsc::SendError<S>> {
+        match self {
+            MpscSender::Unbounded(sender) => sender.send(s),
+            MpscSender::Bounded(sender) => sender.send(s),
+        }
+    }
+}
+
+#[cfg(all(feature = "std", not(any(feature = "crossbeam", feature = "flume"))))]
+impl<S> Clone for MpscSender<S> {
+    fn clone(&self) -> Self {
+        match self {
+            MpscSender::Unbounded(sender) => MpscSender::Unbounded(sender.clone()),
+            MpscSender::Bounded(sender) => MpscSender::Bounded(sender.clone()),
+        }
+    }
+}
+
+/// The sending half of one side of a bidirectional channel, created by
+/// [`clone_sender`] or [`split`].
+///
+/// It sends to the same counterpart as the side it was created from, and can itself
+/// be cloned to add more producers.
+///
+/// [`clone_sender`]: Channel::clone_sender
+/// [`split`]: Channel::split
+#[cfg(feature = "std")]
+#[derive(Debug)]
+pub struct SendHalf<S> {
+    sender: Sender<S>,
+    alive: Alive,
+}
+
+#[cfg(feature = "std")]
+impl<S> Clone for SendHalf<S> {
+    fn clone(&self) -> Self {
+        SendHalf {
+            sender: self.sender.clone(),
+            alive: self.alive.clone(),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl<S> SendHalf<S> {
+    /// Attempts to send a value to the counterpart, returning it back if it could
+    /// not be sent. See [`Channel::send`].
+    pub fn send(&self, s: S) -> Result<(), SendError<S>> {
+        self.sender.send(s)
+    }
+
+    /// Attempts to send a value to the counterpart without blocking. See
+    /// [`Channel::try_send`].
+    pub fn try_send(&self, s: S) -> Result<(), TrySendError<S>> {
+        self.sender.try_send(s)
+    }
+
+    /// Attempts to send a value to the counterpart, waiting at most `timeout` for room
+    /// in a full buffer. See [`Channel::send_timeout`].
+    pub fn send_timeout(&self, s: S, timeout: Duration) -> Result<(), SendTimeoutError<S>> {
+        self.sender.send_timeout(s, timeout)
+    }
+}
+
+/// The receiving half of one side of a bidirectional channel, created by [`split`].
+///
+/// [`split`]: Channel::split
+#[cfg(feature = "std")]
+#[derive(Debug)]
+pub struct RecvHalf<R> {
+    receiver: Receiver<R>,
+    peer: Peer,
+}
+
+#[cfg(any(feature = "crossbeam", feature = "flume"))]
+impl<R> Clone for RecvHalf<R> {
+    fn clone(&self) -> Self {
+        RecvHalf {
+            receiver: self.receiver.clone(),
+            peer: self.peer.clone(),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl<R> RecvHalf<R> {
+    /// Attempts to wait for a value from the counterpart. See [`Channel::recv`].
+    pub fn recv(&self) -> Result<R, RecvError> {
+        self.receiver.recv()
+    }
+
+    /// Attempts to return a pending value from the counterpart without blocking. See
+    /// [`Channel::try_recv`].
+    pub fn try_recv(&self) -> Result<R, TryRecvError> {
+        self.receiver.try_recv()
+    }
+
+    /// Attempts to wait for a value from the counterpart for at most `timeout`. See
+    /// [`Channel::recv_timeout`].
+    pub fn recv_timeout(&self, timeout: Duration) -> Result<R, RecvTimeoutError> {
+        self.receiver.recv_timeout(timeout)
+    }
+
+    /// Attempts to wait for a value from the counterpart until `deadline`. See
+    /// [`Channel::recv_deadline`].
+    pub fn recv_deadline(&self, deadline: Instant) -> Result<R, RecvTimeoutError> {
+        self.receiver.recv_deadline(deadline)
+    }
+
+    /// Attempts to wait for a value from the counterpart for at most `budget`,
+    /// reporting a timeout as empty. See [`Channel::try_recv_for`].
+    pub fn try_recv_for(&self, budget: Duration) -> Result<R, TryRecvError> {
+        self.receiver.recv_timeout(budget).map_err(Into::into)
+    }
+
+    /// Returns `true` if the counterpart has hung up. See
+    /// [`Channel::is_disconnected`].
+    pub fn is_disconnected(&self) -> bool {
+        self.peer.hung_up()
+    }
+
+    /// Returns a blocking iterator over messages from the counterpart. See
+    /// [`Channel::iter`].
+    pub fn iter(&self) -> Iter<'_, R> {
+        Iter {
+            receiver: &self.receiver,
+            on_disconnect: None,
+        }
+    }
+
+    /// Returns a non-blocking iterator over pending messages from the counterpart.
+    /// See [`Channel::try_iter`].
+    pub fn try_iter(&self) -> TryIter<'_, R> {
+        TryIter {
+            receiver: &self.receiver,
+            on_disconnect: None,
+        }
+    }
+}
+
+/// An iterator over messages received on a [`Channel`], created by [`iter`].
+///
+/// This iterator will block whenever [`next`] is called, waiting for a new message,
+/// and [`None`] will be returned when the other side has hung up.
+///
+/// [`iter`]: Channel::iter
+/// [`next`]: Iterator::next
+#[cfg(feature = "std")]
+#[derive(Debug)]
+pub struct Iter<'a, R, B: Backend = DefaultBackend> {
+    receiver: &'a B::Receiver<R>,
+    /// The callbacks of the side iterated, which halves do not have.
+    on_disconnect: Option<&'a OnDisconnect>,
+}
+
+#[cfg(feature = "std")]
+impl<'a, R, B: Backend> Iterator for Iter<'a, R, B> {
+    type Item = R;
+
+    fn next(&mut self) -> Option<R> {
+        let received = B::recv(self.receiver).ok();
+        if let (None, Some(on_disconnect)) = (&received, self.on_disconnect) {
+            on_disconnect.fire();
+        }
+        received
+    }
+}
+
+/// An iterator that attempts to yield all pending messages on a [`Channel`],
+/// created by [`try_iter`].
+///
+/// [`None`] will be returned when there are no pending messages left in the
+/// buffer, or when the other side has hung up. This iterator will never block.
+///
+/// [`try_iter`]: Channel::try_iter
+#[cfg(feature = "std")]
+#[derive(Debug)]
+pub struct TryIter<'a, R, B: Backend = DefaultBackend> {
+    receiver: &'a B::Receiver<R>,
+    /// The callbacks of the side iterated, which halves do not have.
+    on_disconnect: Option<&'a OnDisconnect>,
+}
+
+#[cfg(feature = "std")]
+impl<'a, R, B: Backend> Iterator for TryIter<'a, R, B> {
+    type Item = R;
+
+    fn next(&mut self) -> Option<R> {
+        match B::try_recv(self.receiver) {
+            Ok(r) => Some(r),
+            Err(TryRecvError::Empty) => None,
+            Err(TryRecvError::Disconnected) => {
+                if let Some(on_disconnect) = self.on_disconnect {
+                    on_disconnect.fire();
+                }
+                None
+            }
+        }
+    }
+}
+
+/// An owning iterator over messages received on a [`Channel`], created by
+/// [`into_iter`].
+///
+/// The sending half of the channel is dropped on conversion, so the other side
+/// observes the disconnect while this iterator drains what remains. This iterator
+/// will block whenever [`next`] is called, and [`None`] will be returned when the
+/// other side has hung up.
+///
+/// [`into_iter`]: Channel::into_iter
+/// [`next`]: Iterator::next
+#[cfg(feature = "std")]
+#[derive(Debug)]
+pub struct IntoIter<R, B: Backend = DefaultBackend> {
+    receiver: B::Receiver<R>,
+    on_disconnect: OnDisconnect,
+}
+
+#[cfg(feature = "std")]
+impl<R, B: Backend> Iterator for IntoIter<R, B> {
+    type Item = R;
+
+    fn next(&mut self) -> Option<R> {
+        let received = B::recv(&self.receiver).ok();
+        if received.is_none() {
+            self.on_disconnect.fire();
+        }
+        received
+    }
+}
+
+#[cfg(feature = "std")]
+impl<S, R, B: Backend> IntoIterator for Channel<S, R, B> {
+    type Item = R;
+    type IntoIter = IntoIter<R, B>;
+
+    /// Consumes this side of the channel, dropping its sender, and returns an
+    /// iterator over the remaining messages from the other side.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// let (left, right) = bichannel::channel::<u8, u8>();
+    ///
+    /// right.send(1).unwrap();
+    /// right.send(2).unwrap();
+    /// drop(right);
+    ///
+    /// assert_eq!(left.into_iter().collect::<Vec<_>>(), vec![1, 2]);
+    /// ```
+    fn into_iter(self) -> IntoIter<R, B> {
+        IntoIter {
+            receiver: self.receiver,
+            on_disconnect: self.on_disconnect,
+        }
+    }
+}
+
+/// Creates a bichannelrectional channel returning the left and right
+/// sides. Each side can send and receive from its counterpart
+///
+/// See [`ChannelBuilder`] to configure the channel before creating it.
+///
+/// # Examples
+///
+/// ```
+/// let (left, right) = bichannel::channel::<&'static str, &'static str>();
+///
+/// left.send("ping").unwrap();
+///
+/// assert_eq!(right.recv().unwrap(), "ping");
+/// ```
+#[cfg(feature = "std")]
+pub fn channel<T, U>() -> (Channel<T, U>, Channel<U, T>) {
+    pair(backend::unbounded(), backend::unbounded())
+}
+
+/// Creates a bidirectional channel backed by `B`, returning the left and right sides.
+///
+/// Sides backed by anything but the [`DefaultBackend`] offer sending, receiving and
+/// [`is_disconnected`](Channel::is_disconnected). See [`Backend`].
+///
+/// # Examples
+///
+/// ```
+/// use bichannel::StdBackend;
+///
+/// let (left, right) = bichannel::channel_with::<StdBackend, &str, &str>();
+///
+/// left.send("ping").unwrap();
+/// assert_eq!(right.recv(), Ok("ping"));
+/// ```
+#[cfg(feature = "std")]
+pub fn channel_with<B: Backend, T, U>() -> (Channel<T, U, B>, Channel<U, T, B>) {
+    pair(B::channel(), B::channel())
+}
+
+/// Creates a channel connected to itself, so that every message sent on it is received
+/// back on the same side.
+///
+/// Useful as a stand-in for a real counterpart in tests. Clones and halves of the
+/// channel share one buffer, and the channel never appears disconnected while any of
+/// them can still send.
+///
+/// # Examples
+///
+/// ```
+/// let echo = bichannel::loopback::<u8>();
+///
+/// echo.send(5).unwrap();
+/// assert_eq!(echo.recv(), Ok(5));
+/// ```
+#[cfg(feature = "std")]
+pub fn loopback<T>() -> Channel<T, T> {
+    let (sender, receiver) = backend::unbounded();
+    Channel::adopt(sender, receiver)
+}
+
+/// Creates a bounded bidirectional channel returning the left and right
+/// sides. Each direction buffers at most `bound` messages, after which `send`
+/// blocks until the other side receives.
+///
+/// A `bound` of zero creates a rendezvous channel, where each `send` blocks
+/// until the paired `recv` takes the value.
+///
+/// # Examples
+///
+/// ```
+/// use bichannel::TryRecvError;
+///
+/// let (left, right) = bichannel::sync_channel::<u8, u8>(1);
+///
+/// left.send(1).unwrap();
+/// assert_eq!(right.recv(), Ok(1));
+/// assert_eq!(right.try_recv(), Err(TryRecvError::Empty));
+/// ```
+#[cfg(feature = "std")]
+pub fn sync_channel<T, U>(bound: usize) -> (Channel<T, U>, Channel<U, T>) {
+    pair(backend::bounded(bound), backend::bounded(bound))
+}
+
+/// Creates a bidirectional channel with no buffering, returning the left and right
+/// sides, for passing a value back and forth between two threads in lockstep.
+///
+/// Each `send` blocks until the other side's `recv` takes the value, so nothing is
+/// ever queued, and [`try_send`] only succeeds while the other side is waiting. This
+/// is [`sync_channel`] with a bound of zero. The two directions are separate, so each
+/// side still observes the other hanging up.
+///
+/// [`try_send`]: Channel::try_send
+///
+/// # Examples
+///
+/// ```
+/// use std::thread;
+///
+/// let (ping, pong) = bichannel::rendezvous::<u32>();
+///
+/// let echo = thread::spawn(move || {
+///     for n in pong.iter() {
+///         pong.send(n + 1).unwrap();
+///     }
+/// });
+///
+/// ping.send(1).unwrap();
+/// assert_eq!(ping.recv(), Ok(2));
+///
+/// drop(ping);
+/// echo.join().unwrap();
+/// ```
+#[cfg(feature = "std")]
+pub fn rendezvous<T>() -> (Channel<T, T>, Channel<T, T>) {
+    sync_channel(0)
+}
+
+/// Creates a bidirectional channel where each direction only keeps the latest unread
+/// message, returning the left and right sides.
+///
+/// Sending replaces any message the other side has not received yet, so a slow
+/// receiver only ever observes the most recent value. `recv` returns the latest value
+/// and clears it, and `try_recv` returns [`TryRecvError::Empty`] until a new value is
+/// sent.
+///
+/// # Examples
+///
+/// ```
+/// use bichannel::TryRecvError;
+///
+/// let (worker, ui) = bichannel::watch_channel::<u8, ()>();
+///
+/// worker.send(1).unwrap();
+/// worker.send(2).unwrap();
+/// worker.send(3).unwrap();
+///
+/// assert_eq!(ui.recv(), Ok(3));
+/// assert_eq!(ui.try_recv(), Err(TryRecvError::Empty));
+/// ```
+#[cfg(feature = "std")]
+pub fn watch_channel<T, U>() -> (Channel<T, U>, Channel<U, T>) {
+    bounded_with(1, OverflowPolicy::DropOldest)
+}
+
+/// What a bounded channel does with a message sent while its buffer is full.
+#[cfg(feature = "std")]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum OverflowPolicy {
+    /// `send` blocks until the other side receives, and `try_send` fails with
+    /// [`TrySendError::Full`]. This is the behavior of [`sync_channel`].
+    Block,
+    /// The incoming message is silently discarded, and the send reports success.
+    DropNewest,
+    /// The oldest buffered message is discarded to make room for the incoming one.
+    /// With a `bound` of zero there is nothing to evict, so this behaves like
+    /// [`DropNewest`](OverflowPolicy::DropNewest).
+    DropOldest,
+    /// Sending never blocks: `try_send` fails with [`TrySendError::Full`], and `send`
+    /// fails with a [`SendError`] returning the message.
+    Error,
+}
+
+/// Creates a bounded bidirectional channel returning the left and right sides, where
+/// each direction buffers at most `bound` messages and handles a full buffer
+/// according to `policy`.
+///
+/// # Examples
+///
+/// ```
+/// use bichannel::OverflowPolicy;
+///
+/// let (left, right) = bichannel::bounded_with::<u8, u8>(2, OverflowPolicy::DropOldest);
+///
+/// left.send(1).unwrap();
+/// left.send(2).unwrap();
+/// left.send(3).unwrap();
+///
+/// assert_eq!(right.try_iter().collect::<Vec<_>>(), [2, 3]);
+/// ```
+#[cfg(feature = "std")]
+pub fn bounded_with<T, U>(bound: usize, policy: OverflowPolicy) -> (Channel<T, U>, Channel<U, T>) {
+    pair(
+        backend::bounded_with(bound, policy),
+        backend::bounded_with(bound, policy),
+    )
+}
+
+/// Returns the instant `timeout` from now, or `None` if it is too far away to be
+/// represented, in which case callers wait without a deadline, as std does.
+#[cfg(feature = "std")]
+pub(crate) fn deadline(timeout: Duration) -> Option<Instant> {
+    Instant::now().checked_add(timeout)
+}
+
+/// Wires two underlying channels into a pair of counterpart sides.
+#[cfg(feature = "std")]
+fn pair<B: Backend, T, U>(
+    (ls, lr): (B::Sender<T>, B::Receiver<T>),
+    (rs, rr): (B::Sender<U>, B::Receiver<U>),
+) -> (Channel<T, U, B>, Channel<U, T, B>) {
+    let left_alive = Alive::new();
+    let right_alive = Alive::new();
+    let left_peer = left_alive.peer();
+
+    (
+        Channel::assemble(ls, rr, left_alive, right_alive.peer()),
+        Channel::assemble(rs, lr, right_alive, left_peer),
+    )
+}
+
+#[cfg(all(test, feature = "std"))]
+mod examples {
+
+    #[test]
+    fn test_threaded_scenario() {
+        let (thread, main) = crate::channel();
+
+        let handle = std::thread::spawn(move || loop {
+            match main.try_recv() {
+                Ok("stop") => break "stopped",
+                Err(crate::TryRecvError::Empty) => (),
+                _ => main.send("cant stop").unwrap(),
+            }
+        });
+
+        thread.send("slow down").unwrap();
+        assert_eq!(thread.recv().unwrap(), "cant stop");
+
+        thread.send("stop").unwrap();
+        assert_eq!(handle.join().unwrap(), "stopped");
+    }
+
+    #[test]
+    fn test_recv_timeout_scenario() {
+        let (left, _right) = crate::channel::<(), ()>();
+
+        let timeout = std::time::Duration::from_millis(50);
+        let start = std::time::Instant::now();
+
+        assert_eq!(
+            left.recv_timeout(timeout),
+            Err(crate::RecvTimeoutError::Timeout)
+        );
+        assert!(start.elapsed() >= timeout);
+    }
+
+    #[test]
+    fn test_recv_deadline_elapsed() {
+        let (left, right) = crate::channel::<u8, u8>();
+
+        let deadline = std::time::Instant::now();
+        std::thread::sleep(std::time::Duration::from_millis(1));
+
+        right.send(1).unwrap();
+        assert_eq!(left.recv_deadline(deadline), Ok(1));
+        assert_eq!(
+            left.recv_deadline(deadline),
+            Err(crate::RecvTimeoutError::Timeout)
+        );
+    }
+
+    #[test]
+    fn test_rendezvous_scenario() {
+        use std::sync::atomic::{AtomicBool, Ordering};
+        use std::sync::Arc;
+
+        let (left, right) = crate::sync_channel::<u8, u8>(0);
+        let sent = Arc::new(AtomicBool::new(false));
+
+        let handle = {
+            let sent = sent.clone();
+            std::thread::spawn(move || {
+                right.send(1).unwrap();
+                sent.store(true, Ordering::SeqCst);
+            })
+        };
+
+        std::thread::sleep(std::time::Duration::from_millis(50));
+        assert!(!sent.load(Ordering::SeqCst));
+
+        assert_eq!(left.recv(), Ok(1));
+        handle.join().unwrap();
+        assert!(sent.load(Ordering::SeqCst));
+    }
+
+    #[test]
+    fn test_try_iter_drains_buffer() {
+        let (left, right) = crate::channel::<u8, u8>();
+
+        right.send(1).unwrap();
+        right.send(2).unwrap();
+
+        assert_eq!(left.try_iter().count(), 2);
+        assert_eq!(left.try_iter().count(), 0);
+    }
+
+    #[test]
+    fn test_into_iter_collects_after_drop() {
+        let (left, right) = crate::channel::<usize, usize>();
+
+        for i in 0..10 {
+            right.send(i).unwrap();
+        }
+        drop(right);
+
+        let received: Vec<usize> = left.into_iter().collect();
+        assert_eq!(received.len(), 10);
+    }
+
+    #[test]
+    #[cfg(any(feature = "crossbeam", feature = "flume"))]
+    fn test_queue_depth() {
+        let (left, right) = crate::channel::<u8, u8>();
+
+        right.send(1).unwrap();
+        right.send(2).unwrap();
+        right.send(3).unwrap();
+
+        assert_eq!(left.len(), 3);
+        assert!(right.is_empty());
+    }
+
+    #[test]
+    fn test_close_sender_keeps_receiver() {
+        let (mut left, right) = crate::channel::<&'static str, &'static str>();
+
+        right.send("in flight").unwrap();
+        left.close_sender();
+
+        assert_eq!(right.recv(), Err(crate::RecvError));
+        assert_eq!(left.recv(), Ok("in flight"));
+    }
+
+    #[test]
+    fn test_is_disconnected_after_drop() {
+        let (left, right) = crate::channel::<u8, u8>();
+
+        let handle = std::thread::spawn(move || {
+            right.send(1).unwrap();
+        });
+
+        assert_eq!(left.recv(), Ok(1));
+        handle.join().unwrap();
+        assert!(left.is_disconnected());
+    }
+
+    #[test]
+    fn test_is_disconnected_after_close_sender() {
+        let (mut left, right) = crate::channel::<u8, u8>();
+        let sender = left.clone_sender();
+
+        left.close_sender();
+        assert!(!right.is_disconnected());
+        assert!(!left.is_disconnected());
+
+        drop(sender);
+        assert!(right.is_disconnected());
+        assert!(right.split().1.is_disconnected());
+    }
+
+    #[test]
+    fn test_cloned_senders_scenario() {
+        let (producers, consumer) = crate::channel::<u8, ()>();
+
+        let handles: Vec<_> = (0..2)
+            .map(|i| {
+                let sender = producers.clone_sender();
+                std::thread::spawn(move || sender.send(i).unwrap())
+            })
+            .collect();
+
+        for handle in handles {
+            handle.join().unwrap();
+        }
+        drop(producers);
+
+        let mut received: Vec<u8> = consumer.into_iter().collect();
+        received.sort();
+        assert_eq!(received, vec![0, 1]);
+    }
+
+    #[test]
+    fn test_split_and_join_scenario() {
+        let (left, right) = crate::channel::<u8, u8>();
+
+        let (sender, receiver) = left.split();
+
+        let sending = std::thread::spawn(move || {
+            sender.send(1).unwrap();
+            sender
+        });
+        let receiving = std::thread::spawn(move || {
+            assert_eq!(receiver.recv(), Ok(2));
+            receiver
+        });
+
+        assert_eq!(right.recv(), Ok(1));
+        right.send(2).unwrap();
+
+        let left = crate::Channel::join(sending.join().unwrap(), receiving.join().unwrap());
+
+        left.send(3).unwrap();
+        assert_eq!(right.recv(), Ok(3));
+        right.send(4).unwrap();
+        assert_eq!(left.recv(), Ok(4));
+    }
+
+    #[test]
+    fn test_call_scenario() {
+        let (client, server) = crate::channel::<&'static str, &'static str>();
+
+        let handle = std::thread::spawn(move || {
+            let req = server.recv().unwrap();
+            server.send(req).unwrap();
+        });
+
+        assert_eq!(client.call("echo"), Ok("echo"));
+        handle.join().unwrap();
+
+        assert_eq!(
+            client.call("unanswered"),
+            Err(crate::CallError::Send(crate::SendError("unanswered")))
+        );
+    }
+
+    #[test]
+    fn test_call_without_response() {
+        let (client, server) = crate::channel::<u8, u8>();
+
+        let handle = std::thread::spawn(move || {
+            server.recv().unwrap();
+        });
+
+        assert_eq!(
+            client.call(1),
+            Err(crate::CallError::Recv(crate::RecvError))
+        );
+        handle.join().unwrap();
+    }
+
+    #[test]
+    fn test_call_timeout_late_response() {
+        use std::time::Duration;
+
+        let (client, server) = crate::channel::<u8, u8>();
+
+        let handle = std::thread::spawn(move || {
+            for req in server.iter() {
+                if req == 1 {
+                    std::thread::sleep(Duration::from_millis(50));
+                }
+                server.send(req).unwrap();
+            }
+        });
+
+        assert_eq!(
+            client.call_timeout(1, Duration::from_millis(10)),
+            Err(crate::CallTimeoutError::Timeout)
+        );
+
+        std::thread::sleep(Duration::from_millis(100));
+        assert_eq!(
+            client.call_timeout_draining(2, Duration::from_secs(1)),
+            Ok(2)
+        );
+
+        drop(client);
+        handle.join().unwrap();
+    }
+
+    #[test]
+    fn test_recv_many_batches() {
+        let (left, right) = crate::channel::<u8, u8>();
+
+        for i in 0..5 {
+            right.send(i).unwrap();
+        }
+        drop(right);
+
+        assert_eq!(left.recv_many(3).unwrap().len(), 3);
+        assert_eq!(left.recv_many(3), Ok(vec![3, 4]));
+        assert_eq!(left.recv_many(3), Err(crate::RecvError));
+    }
+
+    #[test]
+    fn test_send_all_preserves_order() {
+        let (left, right) = crate::sync_channel::<usize, usize>(2);
+
+        let handle = std::thread::spawn(move || left.send_all((0..10).collect::<Vec<_>>()));
+
+        let received: Vec<usize> = right.iter().take(10).collect();
+        assert_eq!(received, (0..10).collect::<Vec<_>>());
+        assert_eq!(handle.join().unwrap(), Ok(()));
+    }
+
+    #[test]
+    fn test_watch_coalesces_updates() {
+        let (worker, ui) = crate::watch_channel::<u8, ()>();
+
+        worker.send(1).unwrap();
+        worker.send(2).unwrap();
+        worker.send(3).unwrap();
+
+        assert_eq!(ui.recv(), Ok(3));
+        assert_eq!(ui.try_recv(), Err(crate::TryRecvError::Empty));
+
+        let handle = std::thread::spawn(move || ui.recv());
+        std::thread::sleep(std::time::Duration::from_millis(10));
+        worker.send(4).unwrap();
+        assert_eq!(handle.join().unwrap(), Ok(4));
+
+        assert_eq!(worker.send(5).unwrap_err().0, 5);
+    }
+
+    #[test]
+    fn test_overflow_policies() {
+        use crate::{OverflowPolicy, TrySendError};
+
+        let (left, right) = crate::bounded_with::<u8, ()>(2, OverflowPolicy::DropOldest);
+        for i in 1..=3 {
+            left.send(i).unwrap();
+        }
+        assert_eq!(right.try_iter().collect::<Vec<_>>(), [2, 3]);
+
+        let (left, right) = crate::bounded_with::<u8, ()>(2, OverflowPolicy::DropNewest);
+        for i in 1..=3 {
+            left.try_send(i).unwrap();
+        }
+        assert_eq!(right.try_iter().collect::<Vec<_>>(), [1, 2]);
+
+        let (left, right) = crate::bounded_with::<u8, ()>(2, OverflowPolicy::Error);
+        left.send(1).unwrap();
+        left.send(2).unwrap();
+        assert_eq!(left.try_send(3), Err(TrySendError::Full(3)));
+        assert_eq!(left.send(3).unwrap_err().0, 3);
+        assert_eq!(right.try_iter().collect::<Vec<_>>(), [1, 2]);
+
+        let (left, right) = crate::bounded_with::<u8, ()>(0, OverflowPolicy::DropOldest);
+        left.send(1).unwrap();
+        assert_eq!(right.try_recv(), Err(crate::TryRecvError::Empty));
+    }
+
+    #[test]
+    fn test_recv_matching_keeps_order() {
+        let (left, right) = crate::channel::<(), u8>();
+
+        for i in 0..10 {
+            right.send(i).unwrap();
+        }
+
+        assert_eq!(left.recv_matching(|i| i % 3 == 2), Ok(2));
+        assert_eq!(left.recv_matching(|i| i % 3 == 2), Ok(5));
+        assert_eq!(left.recv(), Ok(0));
+        assert_eq!(left.recv_matching(|i| i % 2 == 0), Ok(4));
+        drop(right);
+
+        assert_eq!(left.recv_matching(|&i| i > 100), Err(crate::RecvError));
+        assert_eq!(left.try_iter().collect::<Vec<_>>(), [1, 3, 6, 7, 8, 9]);
+    }
+
+    #[test]
+    fn test_debug_shows_name() {
+        let (left, right) = crate::ChannelBuilder::new()
+            .name("control")
+            .build::<u8, u8>();
+        let right = right.with_name("control-reply");
+
+        assert!(format!("{:?}", left).contains("\"control\""));
+        assert!(format!("{:?}", right).contains("\"control-reply\""));
+        assert_eq!(
+            format!("{:?}", crate::channel::<u8, u8>().0),
+            "Channel { .. }"
+        );
+    }
+
+    #[test]
+    fn test_try_recv_for() {
+        use crate::TryRecvError;
+        use std::time::Duration;
+
+        let (left, right) = crate::channel::<u8, ()>();
+
+        let sender = std::thread::spawn(move || {
+            std::thread::sleep(Duration::from_millis(20));
+            left.send(1).unwrap();
+            left
+        });
+
+        assert_eq!(right.try_recv_for(Duration::from_millis(100)), Ok(1));
+        assert_eq!(
+            right.try_recv_for(Duration::from_millis(10)),
+            Err(TryRecvError::Empty)
+        );
+
+        drop(sender.join().unwrap());
+        assert_eq!(
+            right.try_recv_for(Duration::from_millis(10)),
+            Err(TryRecvError::Disconnected)
+        );
+    }
+
+    #[test]
+    fn test_shutdown_drains() {
+        use std::time::{Duration, Instant};
+
+        let (left, right) = crate::channel::<u8, u8>();
+
+        let worker = std::thread::spawn(move || {
+            for i in 0..5 {
+                right.send(i).unwrap();
+            }
+            // The worker sees the shutdown, and flushes one last message.
+            assert_eq!(right.recv(), Err(crate::RecvError));
+            right.send(5).unwrap();
+        });
+
+        let start = Instant::now();
+        assert_eq!(left.shutdown(Duration::from_secs(5)), [0, 1, 2, 3, 4, 5]);
+        assert!(start.elapsed() < Duration::from_secs(1));
+        worker.join().unwrap();
+
+        let (left, _right) = crate::channel::<u8, u8>();
+        assert!(left.shutdown(Duration::from_millis(10)).is_empty());
+
+        // A timeout too large for an `Instant` waits for the hang up instead.
+        let (left, right) = crate::channel::<u8, u8>();
+        right.send(1).unwrap();
+        drop(right);
+        assert_eq!(left.shutdown(Duration::MAX), [1]);
+    }
+
+    #[test]
+    fn test_identity() {
+        let (left, right) = crate::channel::<u8, u8>();
+        let (other_left, other_right) = crate::channel::<u8, u8>();
+
+        assert_eq!(left.pair_id(), right.pair_id());
+        assert_eq!(other_left.pair_id(), other_right.pair_id());
+        assert_ne!(left.pair_id(), other_left.pair_id());
+        assert_ne!(left.pair_id(), other_right.pair_id());
+
+        assert_eq!(left, left);
+        assert_ne!(left, other_left);
+
+        #[cfg(any(feature = "crossbeam", feature = "flume"))]
+        assert_eq!(left.clone(), left);
+    }
+
+    // Takes std's channels, which are loom's when model checking.
+    #[test]
+    #[cfg(not(loom))]
+    fn test_from_parts() {
+        #[cfg(not(any(feature = "crossbeam", feature = "flume")))]
+        use std::sync::mpsc::channel as raw_channel;
+
+        #[cfg(all(feature = "flume", not(feature = "crossbeam")))]
+        use flume::unbounded as raw_channel;
+
+        #[cfg(feature = "crossbeam")]
+        use crossbeam_channel::unbounded as raw_channel;
+
+        let (to_right, from_left) = raw_channel::<u8>();
+        let (to_left, from_right) = raw_channel::<&str>();
+
+        let left = crate::Channel::from_parts(to_right, from_right);
+        let right = crate::Channel::from_parts(to_left, from_left);
+
+        left.send(1).unwrap();
+        right.send("one").unwrap();
+        assert_eq!(right.recv(), Ok(1));
+        assert_eq!(left.recv(), Ok("one"));
+        assert_eq!(left.stats().sent, 1);
+
+        drop(right);
+        assert!(!left.is_disconnected());
+        assert_eq!(left.recv(), Err(crate::RecvError));
+        assert!(left.send(2).is_err());
+    }
+
+    #[test]
+    fn test_into_parts() {
+        use crate::OverflowPolicy;
+
+        let (left, right) = crate::channel::<u8, &str>();
+        let (sender, receiver) = left.into_parts().unwrap();
+
+        // The liveness of the side is not carried by the parts, but they still work.
+        assert!(right.is_disconnected());
+        sender.send(1).unwrap();
+        right.send("one").unwrap();
+        assert_eq!(right.recv(), Ok(1));
+        assert_eq!(receiver.recv(), Ok("one"));
+
+        drop((sender, receiver));
+        assert_eq!(right.recv(), Err(crate::RecvError));
+        assert!(right.send("two").is_err());
+
+        // Bounded senders are handed out too.
+        let (left, right) = crate::sync_channel::<u8, u8>(1);
+        let (sender, _receiver) = left.into_parts().unwrap();
+        sender.send(1).unwrap();
+        assert_eq!(right.recv(), Ok(1));
+
+        // The right side evicts through the left side's receiver.
+        let (left, _right) = crate::bounded_with::<u8, u8>(1, OverflowPolicy::DropOldest);
+        assert!(left.into_parts().is_err());
+
+        let (left, right) = crate::channel::<u8, u8>();
+        right.send(1).unwrap();
+        right.send(2).unwrap();
+        assert_eq!(left.recv_matching(|&n| n == 2), Ok(2));
+        let left = left.into_parts().unwrap_err();
+        assert_eq!(left.recv(), Ok(1));
+        assert!(left.into_parts().is_ok());
+    }
+
+    #[test]
+    fn test_fold_sums() {
+        let (left, right) = crate::channel::<u32, u32>();
+
+        let peer = std::thread::spawn(move || {
+            for i in 1..=100 {
+                right.send(i).unwrap();
+            }
+            // The left side's sender is gone as soon as it starts folding.
+            assert_eq!(right.recv(), Err(crate::RecvError));
+        });
+
+        assert_eq!(left.fold(0, |sum, i| sum + i), 5050);
+        peer.join().unwrap();
+    }
+
+    #[test]
+    fn test_for_each_collects() {
+        let (left, right) = crate::channel::<(), u8>();
+
+        let peer = std::thread::spawn(move || {
+            for i in 0..3 {
+                std::thread::sleep(std::time::Duration::from_millis(5));
+                right.send(i).unwrap();
+            }
+        });
+
+        let mut seen = Vec::new();
+        left.for_each(|i| seen.push(i));
+        assert_eq!(seen, [0, 1, 2]);
+
+        peer.join().unwrap();
+    }
+
+    #[test]
+    fn test_recv_or_falls_back_on_disconnect() {
+        let (left, right) = crate::channel::<(), u8>();
+
+        right.send(1).unwrap();
+        assert_eq!(left.recv_or(0), 1);
+
+        drop(right);
+        assert_eq!(left.recv_or(0), 0);
+        assert_eq!(left.recv_or_else(|| 9), 9);
+    }
+
+    #[test]
+    fn test_loopback_delivers_to_itself() {
+        let echo = crate::loopback::<u8>();
+
+        echo.send(5).unwrap();
+        echo.send(6).unwrap();
+        assert_eq!(echo.recv(), Ok(5));
+        assert_eq!(echo.try_recv(), Ok(6));
+        assert_eq!(echo.try_recv(), Err(crate::TryRecvError::Empty));
+        assert!(!echo.is_disconnected());
+
+        let (sender, receiver) = echo.split();
+        sender.send(7).unwrap();
+        drop(sender);
+        assert_eq!(receiver.recv(), Ok(7));
+        assert_eq!(receiver.recv(), Err(crate::RecvError));
+    }
+
+    #[test]
+    fn test_rendezvous_does_not_buffer() {
+        use std::sync::atomic::{AtomicUsize, Ordering};
+        use std::sync::Arc;
+        use std::time::Duration;
+
+        let (left, right) = crate::rendezvous::<u8>();
+        assert_eq!(left.try_send(0), Err(crate::TrySendError::Full(0)));
+
+        let sent = Arc::new(AtomicUsize::new(0));
+        let sender = {
+            let sent = sent.clone();
+            std::thread::spawn(move || {
+                for i in 1..=2 {
+                    left.send(i).unwrap();
+                    sent.store(i as usize, Ordering::SeqCst);
+                }
+            })
+        };
+
+        std::thread::sleep(Duration::from_millis(30));
+        assert_eq!(sent.load(Ordering::SeqCst), 0);
+
+        assert_eq!(right.recv(), Ok(1));
+        std::thread::sleep(Duration::from_millis(30));
+        assert_eq!(sent.load(Ordering::SeqCst), 1);
+
+        assert_eq!(right.recv(), Ok(2));
+        sender.join().unwrap();
+        assert_eq!(sent.load(Ordering::SeqCst), 2);
+    }
+
+    #[test]
+    fn test_close_with_sends_last_words() {
+        let (left, right) = crate::channel::<&str, ()>();
+
+        left.cork();
+        left.send("draining").unwrap();
+        left.close_with("bye").unwrap();
+
+        assert_eq!(right.recv(), Ok("draining"));
+        assert_eq!(right.recv(), Ok("bye"));
+        assert_eq!(right.recv(), Err(crate::RecvError));
+
+        let (left, right) = crate::channel::<&str, ()>();
+        drop(right);
+        assert_eq!(left.close_with("bye"), Err(crate::SendError("bye")));
+    }
+
+    #[test]
+    fn test_try_recv_all_drains_the_buffer() {
+        let (left, right) = crate::channel::<(), u8>();
+        assert!(left.try_recv_all().is_empty());
+
+        right.send_all(vec![1, 2, 3]).unwrap();
+        assert_eq!(left.try_recv_all().len(), 3);
+        assert!(left.try_recv_all().is_empty());
+
+        right.send(4).unwrap();
+        drop(right);
+        assert_eq!(left.try_recv_all(), [4]);
+        assert!(left.try_recv_all().is_empty());
+    }
+
+    #[test]
+    fn test_send_timeout_hands_back_the_value() {
+        use std::time::{Duration, Instant};
+
+        use crate::SendTimeoutError;
+
+        let (left, right) = crate::sync_channel::<u8, ()>(1);
+        left.send(1).unwrap();
+
+        let start = Instant::now();
+        let timeout = Duration::from_millis(30);
+        assert_eq!(
+            left.send_timeout(2, timeout),
+            Err(SendTimeoutError::Timeout(2))
+        );
+        assert!(start.elapsed() >= timeout);
+
+        let receiver = std::thread::spawn(move || {
+            std::thread::sleep(Duration::from_millis(10));
+            assert_eq!(right.recv(), Ok(1));
+            right
+        });
+        left.send_timeout(2, Duration::from_secs(5)).unwrap();
+
+        let right = receiver.join().unwrap();
+        assert_eq!(right.recv(), Ok(2));
+        left.send_timeout(3, Duration::MAX).unwrap();
+        assert_eq!(right.recv(), Ok(3));
+        drop(right);
+        assert_eq!(
+            left.send_timeout(4, timeout),
+            Err(SendTimeoutError::Disconnected(4))
+        );
+    }
+
+    #[test]
+    fn test_capacity_reports_the_bound() {
+        let (left, right) = crate::sync_channel::<u8, u8>(4);
+        assert_eq!(left.capacity(), Some(4));
+        assert_eq!(right.capacity(), Some(4));
+
+        let (left, _) = crate::bounded_with::<u8, u8>(2, crate::OverflowPolicy::DropOldest);
+        assert_eq!(left.capacity(), Some(2));
+        assert_eq!(crate::rendezvous::<u8>().0.capacity(), Some(0));
+
+        let (mut left, _) = crate::channel::<u8, u8>();
+        assert_eq!(left.capacity(), None);
+        left.close_sender();
+        assert_eq!(left.capacity(), None);
+    }
+
+    #[test]
+    #[cfg(any(feature = "crossbeam", feature = "flume"))]
+    fn test_counts_track_clones() {
+        let (left, right) = crate::channel::<u8, u8>();
+        assert_eq!((left.sender_count(), left.receiver_count()), (1, 1));
+
+        let first = left.clone();
+        let second = left.clone();
+        assert_eq!(left.sender_count(), 3);
+        assert_eq!(left.receiver_count(), 3);
+        assert_eq!(right.sender_count(), 1);
+
+        drop(first);
+        assert_eq!(left.sender_count(), 2);
+
+        let (sender, receiver) = second.split();
+        assert_eq!(left.sender_count(), 2);
+        drop(receiver);
+        assert_eq!(left.receiver_count(), 1);
+        drop(sender);
+        assert_eq!(left.sender_count(), 1);
+    }
+
+    #[test]
+    fn test_buffered_messages_are_received_after_disconnect() {
+        type Receive = fn(&crate::Channel<u8, u8>) -> Vec<u8>;
+        let receives: [(&str, Receive); 4] = [
+            ("recv", |side| {
+                std::iter::from_fn(|| side.recv().ok()).collect()
+            }),
+            ("recv_timeout", |side| {
+                std::iter::from_fn(|| side.recv_timeout(std::time::Duration::from_secs(1)).ok())
+                    .collect()
+            }),
+            ("try_recv", |side| {
+                std::iter::from_fn(|| side.try_recv().ok()).collect()
+            }),
+            ("iter", |side| side.iter().collect()),
+        ];
+
+        for (name, receive) in receives {
+            for (left, right) in [crate::channel(), crate::sync_channel(3)] {
+                right.send(1).unwrap();
+                right.send(2).unwrap();
+                right.send(3).unwrap();
+                drop(right);
+
+                assert!(left.is_disconnected());
+                assert_eq!(receive(&left), [1, 2, 3], "{}", name);
+                assert_eq!(left.recv(), Err(crate::RecvError), "{}", name);
+                let disconnected = Err(crate::TryRecvError::Disconnected);
+                assert_eq!(left.try_recv(), disconnected, "{}", name);
+            }
+        }
+    }
+
+    #[test]
+    fn test_drain_ends_once_the_peer_is_gone() {
+        let (left, right) = crate::channel::<u32, u32>();
+
+        let peer = std::thread::spawn(move || {
+            for i in 0..100 {
+                right.send(i).unwrap();
+            }
+            // Dropping the peer is what lets the drain end.
+        });
+
+        assert_eq!(left.drain(), (0..100).collect::<Vec<_>>());
+        peer.join().unwrap();
+    }
+
+    #[test]
+    #[cfg(feature = "tracing")]
+    fn test_send_and_recv_emit_trace_events() {
+        use std::sync::{Arc, Mutex};
+        use tracing::field::{Field, Visit};
+        use tracing::span::{Attributes, Id, Record};
+        use tracing::{Event, Metadata, Subscriber};
+
+        /// Records the fields of every event as `name=value` pairs.
+        #[derive(Default)]
+        struct Capture(Arc<Mutex<Vec<String>>>);
+
+        struct Fields(Vec<String>);
+
+        impl Visit for Fields {
+            fn record_debug(&mut self, field: &Field, value: &dyn std::fmt::Debug) {
+                self.0.push(format!("{}={:?}", field.name(), value));
+            }
+        }
+
+        impl Subscriber for Capture {
+            fn enabled(&self, _: &Metadata<'_>) -> bool {
+                true
+            }
+            fn new_span(&self, _: &Attributes<'_>) -> Id {
+                Id::from_u64(1)
+            }
+            fn record(&self, _: &Id, _: &Record<'_>) {}
+            fn record_follows_from(&self, _: &Id, _: &Id) {}
+            fn event(&self, event: &Event<'_>) {
+                let mut fields = Fields(Vec::new());
+                event.record(&mut fields);
+                self.0.lock().unwrap().push(fields.0.join(" "));
+            }
+            fn enter(&self, _: &Id) {}
+            fn exit(&self, _: &Id) {}
+        }
+
+        let capture = Capture::default();
+        let events = capture.0.clone();
+        tracing::subscriber::with_default(capture, || {
+            let (left, right) = crate::channel::<u8, u8>();
+            let left = left.with_name("left");
+
+            assert_eq!(left.send(1), Ok(()));
+            assert_eq!(right.recv(), Ok(1));
+            drop(left);
+            assert_eq!(right.send(2), Err(crate::SendError(2)));
+            assert_eq!(right.recv(), Err(crate::RecvError));
+        });
+
+        assert_eq!(
+            *events.lock().unwrap(),
+            [
+                r#"message=send channel="left" ok=true"#,
+                "message=recv ok=true",
+                "message=send ok=false",
+                "message=recv ok=false",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_arc_scenario() {
+        let (l, r) = crate::channel::<i8, i8>();
+        r.send(1).unwrap();
+        r.send(2).unwrap();
+
+        let wrapped = std::sync::Arc::new(crate::SyncChannel::new(l));
+
+        let handle = {
+            let wrapped = wrapped.clone();
+            std::thread::spawn(move || {
+                wrapped.recv().unwrap();
+            })
+        };
+
+        wrapped.recv().unwrap();
+        handle.join().unwrap();
+    }
 
     // fn test_fut_scenario()
 }
@@ -0,0 +1,240 @@
+//! Adapters that transform the values passing through a [`Channel`].
+
+use std::fmt;
+use std::iter::Map;
+use std::marker::PhantomData;
+use std::time::Duration;
+
+use crate::{
+    Channel, IntoIter, Iter, RecvError, RecvTimeoutError, SendError, TryIter, TryRecvError,
+    TrySendError,
+};
+
+/// One side of a bidirectional channel whose sent values are converted before being
+/// sent, created by [`Channel::map_send`].
+///
+/// Values of type `S2` are converted to `S` on the way out. As the conversion consumes
+/// the original value, a failed send hands back the converted value.
+pub struct MapSend<S2, S, R, F> {
+    channel: Channel<S, R>,
+    f: F,
+    _marker: PhantomData<fn(S2)>,
+}
+
+impl<S2, S, R, F> MapSend<S2, S, R, F>
+where
+    F: Fn(S2) -> S,
+{
+    /// Converts a value and sends it like [`Channel::send`].
+    pub fn send(&self, s: S2) -> Result<(), SendError<S>> {
+        self.channel.send((self.f)(s))
+    }
+
+    /// Converts a value and sends it without blocking.
+    pub fn try_send(&self, s: S2) -> Result<(), TrySendError<S>> {
+        self.channel.try_send((self.f)(s))
+    }
+}
+
+impl<S2, S, R, F> MapSend<S2, S, R, F> {
+    /// Returns a reference to the underlying channel, which receives as usual.
+    ///
+    /// Values sent through it must already be converted.
+    pub fn get_ref(&self) -> &Channel<S, R> {
+        &self.channel
+    }
+
+    /// Returns a mutable reference to the underlying channel.
+    ///
+    /// Values sent through it must already be converted.
+    pub fn get_mut(&mut self) -> &mut Channel<S, R> {
+        &mut self.channel
+    }
+
+    /// Unwraps this adapter, returning the underlying channel.
+    pub fn into_inner(self) -> Channel<S, R> {
+        self.channel
+    }
+}
+
+impl<S2, S, R, F> fmt::Debug for MapSend<S2, S, R, F>
+where
+    S: fmt::Debug,
+    R: fmt::Debug,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("MapSend")
+            .field("channel", &self.channel)
+            .finish()
+    }
+}
+
+/// One side of a bidirectional channel whose received values are converted before
+/// being returned, created by [`Channel::map_recv`].
+///
+/// The conversion is applied on every receive path, including the iterators.
+pub struct MapRecv<S, R, F> {
+    channel: Channel<S, R>,
+    f: F,
+}
+
+impl<S, R, R2, F> MapRecv<S, R, F>
+where
+    F: Fn(R) -> R2,
+{
+    /// Blocks until a value is received, converting it.
+    pub fn recv(&self) -> Result<R2, RecvError> {
+        self.channel.recv().map(&self.f)
+    }
+
+    /// Returns a pending value without blocking, converting it.
+    pub fn try_recv(&self) -> Result<R2, TryRecvError> {
+        self.channel.try_recv().map(&self.f)
+    }
+
+    /// Waits for at most `timeout` for a value, converting it.
+    pub fn recv_timeout(&self, timeout: Duration) -> Result<R2, RecvTimeoutError> {
+        self.channel.recv_timeout(timeout).map(&self.f)
+    }
+
+    /// Returns a blocking iterator over the converted messages, like [`Channel::iter`].
+    pub fn iter(&self) -> Map<Iter<'_, R>, &F> {
+        self.channel.iter().map(&self.f)
+    }
+
+    /// Returns an iterator over the converted pending messages that does not block.
+    pub fn try_iter(&self) -> Map<TryIter<'_, R>, &F> {
+        self.channel.try_iter().map(&self.f)
+    }
+}
+
+impl<S, R, F> MapRecv<S, R, F> {
+    /// Returns a reference to the underlying channel, which sends as usual.
+    ///
+    /// Values received through it are not converted.
+    pub fn get_ref(&self) -> &Channel<S, R> {
+        &self.channel
+    }
+
+    /// Returns a mutable reference to the underlying channel.
+    ///
+    /// Values received through it are not converted.
+    pub fn get_mut(&mut self) -> &mut Channel<S, R> {
+        &mut self.channel
+    }
+
+    /// Unwraps this adapter, returning the underlying channel.
+    pub fn into_inner(self) -> Channel<S, R> {
+        self.channel
+    }
+}
+
+impl<S, R, R2, F> IntoIterator for MapRecv<S, R, F>
+where
+    F: Fn(R) -> R2,
+{
+    type Item = R2;
+    type IntoIter = Map<IntoIter<R>, F>;
+
+    /// Consumes this side, dropping its sender, and returns an iterator over the
+    /// remaining converted messages.
+    fn into_iter(self) -> Self::IntoIter {
+        self.channel.into_iter().map(self.f)
+    }
+}
+
+impl<S, R, F> fmt::Debug for MapRecv<S, R, F>
+where
+    S: fmt::Debug,
+    R: fmt::Debug,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("MapRecv")
+            .field("channel", &self.channel)
+            .finish()
+    }
+}
+
+impl<S, R> Channel<S, R> {
+    /// Returns an adapter that converts each value with `f` before sending it, so
+    /// that this side can send values of a different type than the other side
+    /// receives.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// let (left, right) = bichannel::channel::<String, ()>();
+    ///
+    /// let left = left.map_send(|n: i32| n.to_string());
+    ///
+    /// left.send(42).unwrap();
+    /// assert_eq!(right.recv().unwrap(), "42");
+    /// ```
+    pub fn map_send<S2, F>(self, f: F) -> MapSend<S2, S, R, F>
+    where
+        F: Fn(S2) -> S,
+    {
+        MapSend {
+            channel: self,
+            f,
+            _marker: PhantomData,
+        }
+    }
+
+    /// Returns an adapter that converts each received value with `f`, so that this
+    /// side can receive values of a different type than the other side sends.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// let (left, right) = bichannel::channel::<(), String>();
+    ///
+    /// let left = left.map_recv(|frame: String| frame.len());
+    ///
+    /// right.send("four".to_string()).unwrap();
+    /// assert_eq!(left.recv(), Ok(4));
+    /// ```
+    pub fn map_recv<R2, F>(self, f: F) -> MapRecv<S, R, F>
+    where
+        F: Fn(R) -> R2,
+    {
+        MapRecv { channel: self, f }
+    }
+}
+
+#[cfg(test)]
+mod examples {
+
+    #[test]
+    fn test_map_send_scenario() {
+        let (wire, peer) = crate::channel::<String, String>();
+
+        let app = wire.map_send(|n: i32| format!("#{}", n));
+
+        app.send(1).unwrap();
+        app.send(2).unwrap();
+
+        assert_eq!(peer.recv().unwrap(), "#1");
+        assert_eq!(peer.recv().unwrap(), "#2");
+
+        peer.send("reply".to_string()).unwrap();
+        assert_eq!(app.get_ref().recv().unwrap(), "reply");
+    }
+
+    #[test]
+    fn test_map_recv_scenario() {
+        let (wire, peer) = crate::channel::<(), String>();
+
+        let app = wire.map_recv(|frame: String| frame.len());
+
+        for frame in &["a", "bb", "ccc", "dddd"] {
+            peer.send(frame.to_string()).unwrap();
+        }
+        drop(peer);
+
+        assert_eq!(app.recv(), Ok(1));
+        assert_eq!(app.try_recv(), Ok(2));
+        assert_eq!(app.try_iter().next(), Some(3));
+        assert_eq!(app.into_iter().collect::<Vec<_>>(), vec![4]);
+    }
+}
@@ -0,0 +1,188 @@
+//! Receiving from two [`Channel`]s as one stream of messages.
+
+use std::fmt;
+#[cfg(not(feature = "crossbeam"))]
+use std::time::Duration;
+
+use crate::Channel;
+#[cfg(feature = "crossbeam")]
+use crate::{RecvError, Select};
+#[cfg(not(feature = "crossbeam"))]
+use crate::{RecvTimeoutError, TryRecvError};
+
+/// How long to wait on one source before checking the other again.
+#[cfg(not(feature = "crossbeam"))]
+const POLL_INTERVAL: Duration = Duration::from_millis(1);
+
+/// Combines two channels that receive the same type into a single blocking iterator,
+/// yielding messages from whichever is ready.
+///
+/// The iterator ends once both counterparts have hung up and every buffered message
+/// has been received. A source that hangs up early is dropped, and the other keeps
+/// being received from. Dropping the iterator drops both channels.
+///
+/// Sources that both have messages ready are received from in turn, so a busy source
+/// cannot starve the other. With the `crossbeam` feature, both sources are waited on
+/// at once. Otherwise they are polled.
+///
+/// # Examples
+///
+/// ```
+/// let (a, a_peer) = bichannel::channel::<(), u8>();
+/// let (b, b_peer) = bichannel::channel::<(), u8>();
+///
+/// a_peer.send(1).unwrap();
+/// b_peer.send(2).unwrap();
+/// drop((a_peer, b_peer));
+///
+/// let mut merged: Vec<_> = bichannel::merge(a, b).collect();
+/// merged.sort();
+/// assert_eq!(merged, [1, 2]);
+/// ```
+pub fn merge<S1, S2, R>(a: Channel<S1, R>, b: Channel<S2, R>) -> Merge<S1, S2, R> {
+    Merge {
+        a: Some(a),
+        b: Some(b),
+        prefer_b: false,
+    }
+}
+
+/// An iterator over the messages received on two channels, created by [`merge`].
+pub struct Merge<S1, S2, R> {
+    a: Option<Channel<S1, R>>,
+    b: Option<Channel<S2, R>>,
+    /// Which source to try first, alternated so that neither is starved.
+    prefer_b: bool,
+}
+
+impl<S1, S2, R> Merge<S1, S2, R> {
+    #[cfg(feature = "crossbeam")]
+    fn next_message(&mut self) -> Option<R> {
+        loop {
+            let (a, b) = match (&self.a, &self.b) {
+                (Some(a), Some(b)) => (a, b),
+                (Some(a), None) => return a.recv().ok(),
+                (None, Some(b)) => return b.recv().ok(),
+                (None, None) => return None,
+            };
+
+            let mut select = Select::new();
+            self.prefer_b = !self.prefer_b;
+            let a_index = if self.prefer_b {
+                select.add_recv(b);
+                select.add_recv(a)
+            } else {
+                let a_index = select.add_recv(a);
+                select.add_recv(b);
+                a_index
+            };
+
+            let operation = select.select_biased();
+            let from_a = operation.index() == a_index;
+            let received = if from_a {
+                operation.recv(a)
+            } else {
+                operation.recv(b)
+            };
+
+            match received {
+                Ok(r) => return Some(r),
+                Err(RecvError) if from_a => self.a = None,
+                Err(RecvError) => self.b = None,
+            }
+        }
+    }
+
+    #[cfg(not(feature = "crossbeam"))]
+    fn next_message(&mut self) -> Option<R> {
+        loop {
+            let (a, b) = match (&self.a, &self.b) {
+                (Some(a), Some(b)) => (a, b),
+                (Some(a), None) => return a.recv().ok(),
+                (None, Some(b)) => return b.recv().ok(),
+                (None, None) => return None,
+            };
+
+            // Only the first source tried can have a message, so none is lost.
+            self.prefer_b = !self.prefer_b;
+            let (a_received, b_received) = if self.prefer_b {
+                match b.try_recv() {
+                    Ok(r) => return Some(r),
+                    b_received => (a.try_recv(), b_received),
+                }
+            } else {
+                match a.try_recv() {
+                    Ok(r) => return Some(r),
+                    a_received => (a_received, b.try_recv()),
+                }
+            };
+
+            match (a_received, b_received) {
+                (Ok(r), _) | (_, Ok(r)) => return Some(r),
+                (Err(TryRecvError::Disconnected), _) => self.a = None,
+                (_, Err(TryRecvError::Disconnected)) => self.b = None,
+                (Err(TryRecvError::Empty), Err(TryRecvError::Empty)) => {
+                    match a.recv_timeout(POLL_INTERVAL) {
+                        Ok(r) => return Some(r),
+                        Err(RecvTimeoutError::Timeout) => (),
+                        Err(RecvTimeoutError::Disconnected) => self.a = None,
+                    }
+                }
+            }
+        }
+    }
+}
+
+impl<S1, S2, R> Iterator for Merge<S1, S2, R> {
+    type Item = R;
+
+    fn next(&mut self) -> Option<R> {
+        self.next_message()
+    }
+}
+
+impl<S1, S2, R> fmt::Debug for Merge<S1, S2, R> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Merge")
+            .field("a", &self.a.is_some())
+            .field("b", &self.b.is_some())
+            .finish()
+    }
+}
+
+#[cfg(test)]
+mod examples {
+    #[test]
+    fn test_outlives_a_disconnected_source() {
+        let (a, a_peer) = crate::channel::<(), u8>();
+        let (b, b_peer) = crate::channel::<(), u8>();
+
+        a_peer.send(1).unwrap();
+        drop(a_peer);
+
+        let producer = std::thread::spawn(move || {
+            for i in 2..5 {
+                std::thread::sleep(std::time::Duration::from_millis(5));
+                b_peer.send(i).unwrap();
+            }
+        });
+
+        let mut merged: Vec<_> = crate::merge(a, b).collect();
+        merged.sort();
+        assert_eq!(merged, [1, 2, 3, 4]);
+
+        producer.join().unwrap();
+    }
+
+    #[test]
+    fn test_busy_sources_take_turns() {
+        let (a, a_peer) = crate::channel::<(), char>();
+        let (b, b_peer) = crate::channel::<(), char>();
+
+        a_peer.send_all(std::iter::repeat_n('a', 100)).unwrap();
+        b_peer.send_all(std::iter::repeat_n('b', 100)).unwrap();
+
+        let from_a = crate::merge(a, b).take(100).filter(|&c| c == 'a').count();
+        assert!((40..=60).contains(&from_a), "{} of 100 from a", from_a);
+    }
+}
@@ -0,0 +1,401 @@
+//! Carrying several logical streams over one [`Channel`], by tagging each message with
+//! the id of its stream.
+
+use std::collections::HashMap;
+use std::fmt;
+use std::hash::Hash;
+use std::sync::{Arc, Mutex, MutexGuard, PoisonError};
+use std::thread;
+use std::time::Duration;
+
+use crate::{Channel, RecvHalf, RecvTimeoutError, SendError, SendHalf, TrySendError};
+
+/// How often an idle router checks whether its [`Demux`] has been dropped.
+const POLL_INTERVAL: Duration = Duration::from_millis(10);
+
+/// What a [`Demux`] does with a message for a stream that has no subscriber.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum UnroutedPolicy {
+    /// Keep the message, and hand it to the next subscriber of its stream.
+    Buffer,
+    /// Discard the message.
+    Drop,
+}
+
+struct Routes<Id, T> {
+    subscribers: HashMap<Id, SendHalf<T>>,
+    unrouted: HashMap<Id, Vec<T>>,
+    policy: UnroutedPolicy,
+    /// Set once the other side has hung up, after which nothing more is routed.
+    closed: bool,
+    /// Set once the [`Demux`] has been dropped, which stops the router.
+    dropped: bool,
+}
+
+impl<Id: Eq + Hash, T> Routes<Id, T> {
+    fn route(&mut self, id: Id, t: T) {
+        let t = match self.subscribers.get(&id) {
+            Some(subscriber) => match subscriber.send(t) {
+                Ok(()) => return,
+                Err(SendError(t)) => {
+                    self.subscribers.remove(&id);
+                    t
+                }
+            },
+            None => t,
+        };
+
+        if self.policy == UnroutedPolicy::Buffer {
+            self.unrouted.entry(id).or_default().push(t);
+        }
+    }
+}
+
+/// Fans the tagged messages received from the other side of a [`Channel`] out to one
+/// receiver per stream, created by [`Demux::new`].
+///
+/// A background thread routes each message to the subscriber of its stream, and stops
+/// once the other side hangs up, after which every subscriber receives what was
+/// routed to it and then observes the disconnect. Messages for a stream without a
+/// subscriber, including one whose receiver has been dropped, are handled according
+/// to an [`UnroutedPolicy`]. Sending to the other side is unaffected.
+///
+/// Dropping the `Demux` stops the background thread within a short interval, and
+/// drops the receiving half, after which every subscriber observes the disconnect
+/// once it has received what was already routed to it.
+pub struct Demux<S, Id, T> {
+    sender: SendHalf<S>,
+    routes: Arc<Mutex<Routes<Id, T>>>,
+}
+
+impl<S, Id, T> Demux<S, Id, T>
+where
+    Id: Eq + Hash + Send + 'static,
+    T: Send + 'static,
+{
+    /// Starts routing the messages received on `channel`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the background thread cannot be spawned.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use bichannel::{Demux, UnroutedPolicy};
+    ///
+    /// let (local, remote) = bichannel::channel::<(), (u32, &str)>();
+    /// let demux = Demux::new(local, UnroutedPolicy::Buffer);
+    ///
+    /// remote.send((1, "one")).unwrap();
+    /// remote.send((2, "two")).unwrap();
+    ///
+    /// assert_eq!(demux.subscribe(2).recv(), Ok("two"));
+    /// assert_eq!(demux.subscribe(1).recv(), Ok("one"));
+    /// ```
+    pub fn new(channel: Channel<S, (Id, T)>, policy: UnroutedPolicy) -> Self {
+        let (sender, receiver) = channel.split();
+        let routes = Arc::new(Mutex::new(Routes {
+            subscribers: HashMap::new(),
+            unrouted: HashMap::new(),
+            policy,
+            closed: false,
+            dropped: false,
+        }));
+        let router = routes.clone();
+
+        thread::Builder::new()
+            .name("bichannel-demux".into())
+            .spawn(move || route(receiver, &router))
+            .expect("failed to spawn the bichannel demux thread");
+
+        Demux { sender, routes }
+    }
+}
+
+fn lock<Id, T>(routes: &Mutex<Routes<Id, T>>) -> MutexGuard<'_, Routes<Id, T>> {
+    routes.lock().unwrap_or_else(PoisonError::into_inner)
+}
+
+fn route<Id: Eq + Hash, T>(receiver: RecvHalf<(Id, T)>, routes: &Mutex<Routes<Id, T>>) {
+    loop {
+        let received = receiver.recv_timeout(POLL_INTERVAL);
+        let mut routes = lock(routes);
+        match received {
+            Ok((id, t)) if !routes.dropped => routes.route(id, t),
+            Err(RecvTimeoutError::Timeout) if !routes.dropped => {}
+            _ => {
+                // Dropped first, so the other side has seen the disconnect by the time
+                // the subscribers do.
+                drop(receiver);
+                routes.closed = true;
+                routes.subscribers.clear();
+                return;
+            }
+        }
+    }
+}
+
+impl<S, Id: Eq + Hash, T> Demux<S, Id, T> {
+    /// Returns a receiver for the messages of stream `id`, starting with any buffered
+    /// while the stream had no subscriber.
+    ///
+    /// Subscribing to a stream again replaces its previous receiver, which observes
+    /// the disconnect once it has received what was already routed to it.
+    pub fn subscribe(&self, id: Id) -> RecvHalf<T> {
+        let (left, right) = crate::channel::<T, ()>();
+        let (subscriber, receiver) = (left.split().0, right.split().1);
+
+        let mut routes = lock(&self.routes);
+        for t in routes.unrouted.remove(&id).into_iter().flatten() {
+            let _ = subscriber.send(t);
+        }
+        if !routes.closed {
+            routes.subscribers.insert(id, subscriber);
+        }
+
+        receiver
+    }
+
+    /// Sends a value to the other side, blocking while a bounded channel is full.
+    pub fn send(&self, s: S) -> Result<(), SendError<S>> {
+        self.sender.send(s)
+    }
+}
+
+impl<S, Id, T> Drop for Demux<S, Id, T> {
+    fn drop(&mut self) {
+        lock(&self.routes).dropped = true;
+    }
+}
+
+impl<S, Id, T> fmt::Debug for Demux<S, Id, T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Demux").finish_non_exhaustive()
+    }
+}
+
+/// Merges several logical streams onto the sending half of one [`Channel`], by handing
+/// out a sender per stream, created by [`Mux::new`].
+///
+/// Each [`MuxSender`] tags its values with the id of its stream and sends them straight
+/// to the underlying channel, so a full bounded channel blocks every stream's sender.
+/// Receiving from the other side is unaffected, and is done through
+/// [`get_ref`](Self::get_ref).
+pub struct Mux<Id, T, R> {
+    channel: Channel<(Id, T), R>,
+}
+
+impl<Id: Clone, T, R> Mux<Id, T, R> {
+    /// Wraps a channel carrying tagged messages.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use bichannel::Mux;
+    ///
+    /// let (local, remote) = bichannel::channel::<(&str, u8), ()>();
+    /// let mux = Mux::new(local);
+    ///
+    /// let temperature = mux.channel("temperature");
+    /// let humidity = mux.channel("humidity");
+    ///
+    /// temperature.send(21).unwrap();
+    /// humidity.send(40).unwrap();
+    ///
+    /// assert_eq!(remote.recv(), Ok(("temperature", 21)));
+    /// assert_eq!(remote.recv(), Ok(("humidity", 40)));
+    /// ```
+    pub fn new(channel: Channel<(Id, T), R>) -> Self {
+        Mux { channel }
+    }
+
+    /// Returns a sender for stream `id`. Several senders may share a stream.
+    pub fn channel(&self, id: Id) -> MuxSender<Id, T> {
+        MuxSender {
+            id,
+            sender: self.channel.clone_sender(),
+        }
+    }
+}
+
+impl<Id, T, R> Mux<Id, T, R> {
+    /// Returns a reference to the underlying channel, which receives as usual.
+    ///
+    /// Values sent through it must already be tagged.
+    pub fn get_ref(&self) -> &Channel<(Id, T), R> {
+        &self.channel
+    }
+
+    /// Returns a mutable reference to the underlying channel.
+    ///
+    /// Values sent through it must already be tagged.
+    pub fn get_mut(&mut self) -> &mut Channel<(Id, T), R> {
+        &mut self.channel
+    }
+
+    /// Unwraps this adapter, returning the underlying channel. Senders already handed
+    /// out keep working.
+    pub fn into_inner(self) -> Channel<(Id, T), R> {
+        self.channel
+    }
+}
+
+impl<Id, T, R> fmt::Debug for Mux<Id, T, R> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Mux")
+            .field("channel", &self.channel)
+            .finish()
+    }
+}
+
+/// Sends the values of one stream onto the channel of a [`Mux`], created by
+/// [`Mux::channel`].
+pub struct MuxSender<Id, T> {
+    id: Id,
+    sender: SendHalf<(Id, T)>,
+}
+
+impl<Id: Clone, T> MuxSender<Id, T> {
+    /// Returns the id attached to every value sent.
+    pub fn id(&self) -> &Id {
+        &self.id
+    }
+
+    /// Tags a value with this stream's id and sends it, blocking while the channel is
+    /// full.
+    pub fn send(&self, t: T) -> Result<(), SendError<T>> {
+        self.sender
+            .send((self.id.clone(), t))
+            .map_err(|SendError((_, t))| SendError(t))
+    }
+
+    /// Tags a value with this stream's id and sends it without blocking.
+    pub fn try_send(&self, t: T) -> Result<(), TrySendError<T>> {
+        self.sender
+            .try_send((self.id.clone(), t))
+            .map_err(|err| match err {
+                TrySendError::Full((_, t)) => TrySendError::Full(t),
+                TrySendError::Disconnected((_, t)) => TrySendError::Disconnected(t),
+            })
+    }
+}
+
+impl<Id: Clone, T> Clone for MuxSender<Id, T> {
+    fn clone(&self) -> Self {
+        MuxSender {
+            id: self.id.clone(),
+            sender: self.sender.clone(),
+        }
+    }
+}
+
+impl<Id: fmt::Debug, T> fmt::Debug for MuxSender<Id, T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("MuxSender")
+            .field("id", &self.id)
+            .finish_non_exhaustive()
+    }
+}
+
+#[cfg(test)]
+mod examples {
+    use std::time::Duration;
+
+    use crate::{
+        Demux, Mux, RecvError, RecvTimeoutError, TryRecvError, TrySendError, UnroutedPolicy,
+    };
+
+    #[test]
+    fn test_routes_by_id() {
+        let (local, remote) = crate::channel::<(), (u32, u8)>();
+        let demux = Demux::new(local, UnroutedPolicy::Buffer);
+
+        let one = demux.subscribe(1);
+        let two = demux.subscribe(2);
+
+        for (id, t) in [(1, 10), (2, 20), (1, 11), (3, 30), (2, 21)] {
+            remote.send((id, t)).unwrap();
+        }
+        drop(remote);
+
+        assert_eq!(one.iter().collect::<Vec<_>>(), [10, 11]);
+        assert_eq!(two.iter().collect::<Vec<_>>(), [20, 21]);
+
+        // The router has stopped, so a late subscriber only gets what was buffered.
+        let three = demux.subscribe(3);
+        assert_eq!(three.recv(), Ok(30));
+        assert_eq!(three.recv(), Err(RecvError));
+    }
+
+    #[test]
+    fn test_drops_unrouted() {
+        let (local, remote) = crate::channel::<(), (u32, u8)>();
+        let demux = Demux::new(local, UnroutedPolicy::Drop);
+
+        let two = demux.subscribe(2);
+        remote.send((1, 10)).unwrap();
+        remote.send((2, 20)).unwrap();
+        // Once stream 2 has its message, the router is done with the one before it.
+        assert_eq!(two.recv(), Ok(20));
+
+        let one = demux.subscribe(1);
+        remote.send((1, 11)).unwrap();
+
+        assert_eq!(one.recv_timeout(Duration::from_secs(1)), Ok(11));
+        assert_eq!(one.try_recv(), Err(TryRecvError::Empty));
+    }
+
+    #[test]
+    fn test_dropping_stops_the_router() {
+        let (local, remote) = crate::channel::<(), (u32, u8)>();
+        let demux = Demux::new(local, UnroutedPolicy::Buffer);
+
+        let one = demux.subscribe(1);
+        remote.send((1, 10)).unwrap();
+        assert_eq!(one.recv(), Ok(10));
+
+        drop(demux);
+        assert_eq!(
+            one.recv_timeout(Duration::from_secs(5)),
+            Err(RecvTimeoutError::Disconnected)
+        );
+
+        // The router has dropped the receiving half.
+        assert!(remote.is_disconnected());
+        assert!(remote.send((1, 11)).is_err());
+    }
+
+    #[test]
+    fn test_mux_tags_and_blocks() {
+        let (local, remote) = crate::sync_channel::<(u32, u8), ()>(1);
+        let mux = Mux::new(local);
+
+        let one = mux.channel(1);
+        let two = mux.channel(2);
+
+        let producers = [(one.clone(), 10), (two, 20)].map(|(sender, base)| {
+            std::thread::spawn(move || {
+                for i in 0..3 {
+                    sender.send(base + i).unwrap();
+                }
+            })
+        });
+
+        let mut received: Vec<_> = (0..6).map(|_| remote.recv().unwrap()).collect();
+        for producer in producers {
+            producer.join().unwrap();
+        }
+
+        received.sort_unstable();
+        assert_eq!(
+            received,
+            [(1, 10), (1, 11), (1, 12), (2, 20), (2, 21), (2, 22)]
+        );
+
+        // With the buffer full, the per-stream senders feel the backpressure.
+        one.send(13).unwrap();
+        assert_eq!(one.try_send(14), Err(TrySendError::Full(14)));
+        assert_eq!(remote.recv(), Ok((1, 13)));
+    }
+}
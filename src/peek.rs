@@ -0,0 +1,177 @@
+//! Looking at the next message on a [`Channel`] without consuming it.
+
+use std::fmt;
+use std::time::Duration;
+
+use crate::{Channel, RecvError, RecvGuard, RecvTimeoutError, TryRecvError};
+
+/// One side of a bidirectional channel that can look at its next message before
+/// receiving it, created by [`Channel::peekable`].
+///
+/// A peeked message is held by this adapter and returned by the next receive, so
+/// peeking never reorders messages.
+pub struct Peekable<S, R> {
+    channel: Channel<S, R>,
+    peeked: Option<R>,
+}
+
+impl<S, R> Peekable<S, R> {
+    /// Blocks until a message is available and returns a reference to it without
+    /// consuming it, or returns `None` once the other side has hung up.
+    pub fn peek(&mut self) -> Option<&R> {
+        if self.peeked.is_none() {
+            self.peeked = self.channel.recv().ok();
+        }
+        self.peeked.as_ref()
+    }
+
+    /// Returns a reference to the next message without consuming it or blocking, or
+    /// `None` if no message is pending.
+    pub fn try_peek(&mut self) -> Option<&R> {
+        if self.peeked.is_none() {
+            self.peeked = self.channel.try_recv().ok();
+        }
+        self.peeked.as_ref()
+    }
+
+    /// Returns the peeked message if there is one, or else blocks until the next
+    /// message arrives like [`Channel::recv`].
+    pub fn recv(&mut self) -> Result<R, RecvError> {
+        match self.peeked.take() {
+            Some(r) => Ok(r),
+            None => self.channel.recv(),
+        }
+    }
+
+    /// Returns the peeked message if there is one, or else the next pending message
+    /// without blocking.
+    pub fn try_recv(&mut self) -> Result<R, TryRecvError> {
+        match self.peeked.take() {
+            Some(r) => Ok(r),
+            None => self.channel.try_recv(),
+        }
+    }
+
+    /// Waits for a value from the other side like [`recv`](Self::recv), returning it
+    /// behind a guard that lends it out in place.
+    ///
+    /// The message is held in the peeked slot while the guard lives, and is consumed
+    /// when the guard is dropped, unless [`RecvGuard::take`] moves it out. A message
+    /// already peeked is lent out first.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// let (left, right) = bichannel::channel::<(), Vec<u8>>();
+    /// let mut left = left.peekable();
+    ///
+    /// right.send(vec![0; 1024]).unwrap();
+    /// right.send(vec![1; 16]).unwrap();
+    ///
+    /// // Too large, so dropped without being moved out.
+    /// let large = left.recv_ref().unwrap();
+    /// assert_eq!(large.len(), 1024);
+    /// drop(large);
+    ///
+    /// let small = left.recv_ref().unwrap();
+    /// assert_eq!(small.len(), 16);
+    /// assert_eq!(small.take(), [1; 16]);
+    /// ```
+    pub fn recv_ref(&mut self) -> Result<RecvGuard<'_, R>, RecvError> {
+        if self.peeked.is_none() {
+            self.peeked = Some(self.channel.recv()?);
+        }
+        Ok(RecvGuard::new(&mut self.peeked))
+    }
+
+    /// Returns the peeked message if there is one, or else waits for at most `timeout`
+    /// for the next message.
+    pub fn recv_timeout(&mut self, timeout: Duration) -> Result<R, RecvTimeoutError> {
+        match self.peeked.take() {
+            Some(r) => Ok(r),
+            None => self.channel.recv_timeout(timeout),
+        }
+    }
+
+    /// Returns a reference to the underlying channel, which sends as usual.
+    ///
+    /// Receiving through it skips over the peeked message, if any.
+    pub fn get_ref(&self) -> &Channel<S, R> {
+        &self.channel
+    }
+
+    /// Returns a mutable reference to the underlying channel.
+    ///
+    /// Receiving through it skips over the peeked message, if any.
+    pub fn get_mut(&mut self) -> &mut Channel<S, R> {
+        &mut self.channel
+    }
+
+    /// Unwraps this adapter, returning the underlying channel along with the peeked
+    /// message, if any.
+    pub fn into_inner(self) -> (Channel<S, R>, Option<R>) {
+        (self.channel, self.peeked)
+    }
+}
+
+impl<S, R> fmt::Debug for Peekable<S, R>
+where
+    S: fmt::Debug,
+    R: fmt::Debug,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Peekable")
+            .field("channel", &self.channel)
+            .field("peeked", &self.peeked)
+            .finish()
+    }
+}
+
+impl<S, R> Channel<S, R> {
+    /// Returns an adapter that can look at the next message before receiving it.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// let (left, right) = bichannel::channel::<(), &str>();
+    /// let mut left = left.peekable();
+    ///
+    /// right.send("route me").unwrap();
+    ///
+    /// assert_eq!(left.peek(), Some(&"route me"));
+    /// assert_eq!(left.recv(), Ok("route me"));
+    /// ```
+    pub fn peekable(self) -> Peekable<S, R> {
+        Peekable {
+            channel: self,
+            peeked: None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod examples {
+
+    #[test]
+    fn test_peek_does_not_advance() {
+        let (wire, peer) = crate::channel::<(), u8>();
+        let mut wire = wire.peekable();
+
+        assert_eq!(wire.try_peek(), None);
+
+        peer.send(1).unwrap();
+        peer.send(2).unwrap();
+
+        let first = wire.peek().copied();
+        let second = wire.try_peek().copied();
+        let received = wire.recv().ok();
+        assert_eq!(first, Some(1));
+        assert_eq!(second, first);
+        assert_eq!(received, first);
+
+        assert_eq!(wire.try_recv(), Ok(2));
+
+        drop(peer);
+        assert_eq!(wire.peek(), None);
+    }
+}
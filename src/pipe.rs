@@ -0,0 +1,110 @@
+//! Relaying messages between two [`Channel`]s.
+
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::thread::{self, JoinHandle};
+use std::time::Duration;
+
+use crate::{Channel, RecvHalf, RecvTimeoutError, SendHalf};
+
+/// How often an idle direction checks whether the other direction has stopped.
+const POLL_INTERVAL: Duration = Duration::from_millis(10);
+
+/// Connects two channels on a background thread, so that messages received on `from`
+/// are sent on `to` and vice versa, returning a handle to the relay.
+///
+/// The relay stops once either counterpart hangs up, and then drops both `from` and
+/// `to` so that the remaining counterpart observes the disconnect. See
+/// [`pipe_until_closed`] to run the relay on the current thread instead.
+///
+/// # Examples
+///
+/// ```
+/// let (client, from) = bichannel::channel::<&str, usize>();
+/// let (to, server) = bichannel::channel::<&str, usize>();
+///
+/// let relay = bichannel::pipe(from, to);
+///
+/// client.send("ping").unwrap();
+/// assert_eq!(server.recv(), Ok("ping"));
+///
+/// server.send(4).unwrap();
+/// assert_eq!(client.recv(), Ok(4));
+///
+/// drop(client);
+/// relay.join().unwrap();
+/// assert!(server.recv().is_err());
+/// ```
+pub fn pipe<A, B>(from: Channel<A, B>, to: Channel<B, A>) -> JoinHandle<()>
+where
+    A: Send + 'static,
+    B: Send + 'static,
+{
+    thread::spawn(move || pipe_until_closed(from, to))
+}
+
+/// Connects two channels on the current thread, so that messages received on `from`
+/// are sent on `to` and vice versa, returning once either counterpart hangs up.
+///
+/// Both `from` and `to` are dropped before returning, so the remaining counterpart
+/// observes the disconnect. One of the two directions is relayed on a scoped helper
+/// thread. See [`pipe`] to run the relay in the background.
+pub fn pipe_until_closed<A, B>(from: Channel<A, B>, to: Channel<B, A>)
+where
+    A: Send,
+    B: Send,
+{
+    let (from_sender, from_receiver) = from.split();
+    let (to_sender, to_receiver) = to.split();
+    let closed = AtomicBool::new(false);
+
+    thread::scope(|scope| {
+        scope.spawn(|| forward(to_receiver, from_sender, &closed));
+        forward(from_receiver, to_sender, &closed);
+    });
+}
+
+/// Forwards messages in one direction until a counterpart hangs up, or the other
+/// direction has stopped.
+fn forward<T>(receiver: RecvHalf<T>, sender: SendHalf<T>, closed: &AtomicBool) {
+    loop {
+        match receiver.recv_timeout(POLL_INTERVAL) {
+            Ok(t) => {
+                if sender.send(t).is_err() {
+                    break;
+                }
+            }
+            Err(RecvTimeoutError::Timeout) => {
+                if closed.load(Ordering::Acquire) {
+                    break;
+                }
+            }
+            Err(RecvTimeoutError::Disconnected) => break,
+        }
+    }
+
+    closed.store(true, Ordering::Release);
+}
+
+#[cfg(test)]
+mod examples {
+    #[test]
+    fn test_relays_both_ways() {
+        let (client, from) = crate::channel::<u8, &str>();
+        let (to, server) = crate::channel::<u8, &str>();
+
+        let relay = crate::pipe(from, to);
+
+        for i in 0..3 {
+            client.send(i).unwrap();
+        }
+        assert_eq!(server.iter().take(3).collect::<Vec<_>>(), [0, 1, 2]);
+
+        server.send("pong").unwrap();
+        assert_eq!(client.recv(), Ok("pong"));
+
+        drop(server);
+        relay.join().unwrap();
+        assert!(client.is_disconnected());
+        assert!(client.recv().is_err());
+    }
+}
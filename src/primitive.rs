@@ -0,0 +1,58 @@
+//! The locks, atomics and unbounded channel that the state shared between handles is
+//! built from.
+//!
+//! When testing with the `loom` cfg, these are loom's, so that the model tests can
+//! explore every interleaving of the backend, the liveness of each side, corking and
+//! disconnect callbacks. They then only work inside `loom::model`, so the cfg is for
+//! running those tests alone, as described in the crate documentation. Builds outside
+//! of tests always use std's. The timer keeps std's, as it is global rather than part
+//! of a model.
+
+#[cfg(all(loom, test))]
+pub(crate) use loom::sync::atomic::{fence, AtomicBool, AtomicU64, AtomicUsize};
+#[cfg(all(loom, test))]
+pub(crate) use loom::sync::{Mutex, MutexGuard};
+
+#[cfg(not(all(loom, test)))]
+pub(crate) use std::sync::atomic::{fence, AtomicBool, AtomicU64, AtomicUsize};
+#[cfg(not(all(loom, test)))]
+pub(crate) use std::sync::{Mutex, MutexGuard};
+
+#[cfg(not(any(all(loom, test), feature = "crossbeam", feature = "flume")))]
+pub(crate) use std::sync::mpsc;
+
+/// loom's unbounded channel, with std's errors. loom has no bounded channel, so
+/// creating one panics, and its sender can never be constructed.
+#[cfg(all(loom, test, not(any(feature = "crossbeam", feature = "flume"))))]
+pub(crate) mod mpsc {
+    use std::convert::Infallible;
+    use std::marker::PhantomData;
+
+    pub(crate) use loom::sync::mpsc::{channel, Receiver, Sender};
+    pub(crate) use std::sync::mpsc::{
+        RecvError, RecvTimeoutError, SendError, TryRecvError, TrySendError,
+    };
+
+    #[derive(Debug)]
+    pub struct SyncSender<T>(Infallible, PhantomData<T>);
+
+    impl<T> SyncSender<T> {
+        pub(crate) fn send(&self, _: T) -> Result<(), SendError<T>> {
+            match self.0 {}
+        }
+
+        pub(crate) fn try_send(&self, _: T) -> Result<(), TrySendError<T>> {
+            match self.0 {}
+        }
+    }
+
+    impl<T> Clone for SyncSender<T> {
+        fn clone(&self) -> Self {
+            match self.0 {}
+        }
+    }
+
+    pub(crate) fn sync_channel<T>(_: usize) -> (SyncSender<T>, Receiver<T>) {
+        unimplemented!("loom does not model bounded channels")
+    }
+}
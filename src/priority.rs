@@ -0,0 +1,250 @@
+//! Letting urgent messages overtake the rest.
+
+use std::collections::VecDeque;
+use std::fmt;
+use std::sync::{Mutex, MutexGuard, PoisonError};
+use std::time::Duration;
+
+use crate::{Channel, RecvError, RecvTimeoutError, SendError, TryRecvError};
+
+/// Which lane of a [`PriorityChannel`] a message travels in, sent along with it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Lane {
+    /// Sent with [`PriorityChannel::send`].
+    Normal,
+    /// Sent with [`PriorityChannel::send_urgent`], and received ahead of normal
+    /// messages.
+    Urgent,
+}
+
+/// Messages taken off the channel but not yet received, sorted by lane.
+struct Lanes<R> {
+    urgent: VecDeque<R>,
+    normal: VecDeque<R>,
+}
+
+impl<R> Lanes<R> {
+    fn push(&mut self, (lane, r): (Lane, R)) {
+        match lane {
+            Lane::Urgent => self.urgent.push_back(r),
+            Lane::Normal => self.normal.push_back(r),
+        }
+    }
+
+    fn pop(&mut self) -> Option<R> {
+        self.urgent.pop_front().or_else(|| self.normal.pop_front())
+    }
+}
+
+/// One side of a bidirectional channel with two lanes, created by
+/// [`priority_channel`].
+///
+/// Messages sent with [`send_urgent`] are received before any message sent with
+/// [`send`] that is waiting at the same time, even one sent earlier. Within a lane,
+/// messages are received in the order they were sent. To find the urgent messages,
+/// receiving takes everything pending off the channel, so the other side's messages are
+/// buffered on this side until received.
+///
+/// [`send_urgent`]: Self::send_urgent
+/// [`send`]: Self::send
+pub struct PriorityChannel<S, R> {
+    channel: Channel<(Lane, S), (Lane, R)>,
+    lanes: Mutex<Lanes<R>>,
+}
+
+impl<S, R> PriorityChannel<S, R> {
+    /// Wraps a channel carrying messages tagged with their lane, such as one that relays
+    /// messages between two `PriorityChannel`s.
+    pub fn new(channel: Channel<(Lane, S), (Lane, R)>) -> Self {
+        PriorityChannel {
+            channel,
+            lanes: Mutex::new(Lanes {
+                urgent: VecDeque::new(),
+                normal: VecDeque::new(),
+            }),
+        }
+    }
+
+    fn send_in(&self, lane: Lane, s: S) -> Result<(), SendError<S>> {
+        self.channel
+            .send((lane, s))
+            .map_err(|SendError((_, s))| SendError(s))
+    }
+
+    /// Sends a value to the other side in the normal lane.
+    pub fn send(&self, s: S) -> Result<(), SendError<S>> {
+        self.send_in(Lane::Normal, s)
+    }
+
+    /// Sends a value to the other side in the urgent lane, ahead of any normal
+    /// messages it has yet to receive.
+    pub fn send_urgent(&self, s: S) -> Result<(), SendError<S>> {
+        self.send_in(Lane::Urgent, s)
+    }
+
+    /// Takes every pending message off the channel, returning the buffered lanes.
+    fn drain(&self) -> MutexGuard<'_, Lanes<R>> {
+        let mut lanes = self.lanes.lock().unwrap_or_else(PoisonError::into_inner);
+        for message in self.channel.try_iter() {
+            lanes.push(message);
+        }
+        lanes
+    }
+
+    /// Blocks until a value is available, returning the oldest urgent one if any are
+    /// waiting.
+    pub fn recv(&self) -> Result<R, RecvError> {
+        loop {
+            if let Some(r) = self.drain().pop() {
+                return Ok(r);
+            }
+            let message = self.channel.recv()?;
+            self.lanes
+                .lock()
+                .unwrap_or_else(PoisonError::into_inner)
+                .push(message);
+        }
+    }
+
+    /// Returns the oldest pending urgent value without blocking, or else the oldest
+    /// normal one.
+    pub fn try_recv(&self) -> Result<R, TryRecvError> {
+        match self.drain().pop() {
+            Some(r) => Ok(r),
+            None => self.channel.try_recv().map(|(_, r)| r),
+        }
+    }
+
+    /// Waits for at most `timeout` for a value to be available, returning the oldest
+    /// urgent one if any are waiting.
+    pub fn recv_timeout(&self, timeout: Duration) -> Result<R, RecvTimeoutError> {
+        let deadline = crate::deadline(timeout);
+        loop {
+            if let Some(r) = self.drain().pop() {
+                return Ok(r);
+            }
+            let message = self.channel.recv_until(deadline)?;
+            self.lanes
+                .lock()
+                .unwrap_or_else(PoisonError::into_inner)
+                .push(message);
+        }
+    }
+
+    /// Returns a reference to the underlying channel.
+    ///
+    /// Messages go through it with their lanes, and receiving through it skips over
+    /// those already buffered by this side.
+    pub fn get_ref(&self) -> &Channel<(Lane, S), (Lane, R)> {
+        &self.channel
+    }
+
+    /// Returns a mutable reference to the underlying channel.
+    ///
+    /// Messages go through it with their lanes, and receiving through it skips over
+    /// those already buffered by this side.
+    pub fn get_mut(&mut self) -> &mut Channel<(Lane, S), (Lane, R)> {
+        &mut self.channel
+    }
+
+    /// Unwraps this adapter, returning the underlying channel along with the messages
+    /// already buffered by this side, in the order they would have been received.
+    #[allow(clippy::type_complexity)]
+    pub fn into_inner(self) -> (Channel<(Lane, S), (Lane, R)>, Vec<R>) {
+        let lanes = self
+            .lanes
+            .into_inner()
+            .unwrap_or_else(PoisonError::into_inner);
+        let buffered = lanes.urgent.into_iter().chain(lanes.normal).collect();
+        (self.channel, buffered)
+    }
+}
+
+impl<S, R> fmt::Debug for PriorityChannel<S, R> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("PriorityChannel")
+            .field("channel", &self.channel)
+            .finish_non_exhaustive()
+    }
+}
+
+/// Creates a bidirectional channel whose sides can send urgent messages that overtake
+/// the rest, returning the left and right sides.
+///
+/// # Examples
+///
+/// ```
+/// let (left, right) = bichannel::priority_channel::<&str, ()>();
+///
+/// left.send("bulk").unwrap();
+/// left.send_urgent("stop").unwrap();
+///
+/// assert_eq!(right.recv(), Ok("stop"));
+/// assert_eq!(right.recv(), Ok("bulk"));
+/// ```
+pub fn priority_channel<T, U>() -> (PriorityChannel<T, U>, PriorityChannel<U, T>) {
+    let (left, right) = crate::channel();
+    (PriorityChannel::new(left), PriorityChannel::new(right))
+}
+
+#[cfg(test)]
+mod examples {
+    use std::time::Duration;
+
+    use crate::{Lane, RecvTimeoutError, TryRecvError};
+
+    #[test]
+    fn test_urgent_first() {
+        let (left, right) = crate::priority_channel::<u8, ()>();
+
+        left.send(1).unwrap();
+        left.send_urgent(10).unwrap();
+        left.send(2).unwrap();
+        left.send_urgent(11).unwrap();
+
+        assert_eq!(right.recv(), Ok(10));
+
+        left.send_urgent(12).unwrap();
+        left.send(3).unwrap();
+
+        assert_eq!(right.try_recv(), Ok(11));
+        assert_eq!(right.recv_timeout(Duration::from_millis(10)), Ok(12));
+        assert_eq!(right.recv(), Ok(1));
+        assert_eq!(right.recv_timeout(Duration::MAX), Ok(2));
+        assert_eq!(right.recv(), Ok(3));
+        assert_eq!(right.try_recv(), Err(TryRecvError::Empty));
+        assert_eq!(
+            right.recv_timeout(Duration::from_millis(10)),
+            Err(RecvTimeoutError::Timeout)
+        );
+    }
+
+    #[test]
+    fn test_blocking_recv() {
+        let (left, right) = crate::priority_channel::<u8, ()>();
+
+        let sender = std::thread::spawn(move || {
+            std::thread::sleep(Duration::from_millis(10));
+            left.send_urgent(1).unwrap();
+        });
+
+        assert_eq!(right.recv(), Ok(1));
+        sender.join().unwrap();
+        assert_eq!(right.recv(), Err(crate::RecvError));
+    }
+
+    #[test]
+    fn test_into_inner_keeps_buffered() {
+        let (left, right) = crate::priority_channel::<u8, ()>();
+
+        left.send(1).unwrap();
+        left.send_urgent(10).unwrap();
+        left.send(2).unwrap();
+        assert_eq!(right.recv(), Ok(10));
+
+        left.send_urgent(11).unwrap();
+        let (right, buffered) = right.into_inner();
+        assert_eq!(buffered, [1, 2]);
+        assert_eq!(right.try_recv(), Ok((Lane::Urgent, 11)));
+    }
+}
@@ -0,0 +1,182 @@
+//! Limiting how fast a [`Channel`] sends.
+
+use std::convert::TryFrom;
+use std::fmt;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::thread;
+use std::time::{Duration, Instant};
+
+use crate::{Channel, RateLimitError};
+
+/// What [`RateLimited::send`] does once the rate limit has been reached.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum RateLimitPolicy {
+    /// Wait until the message can be sent within the limit.
+    Block,
+    /// Fail with [`RateLimitError::RateLimited`], handing back the message.
+    Error,
+}
+
+/// One side of a bidirectional channel that sends at most a fixed number of messages per
+/// second, created by [`Channel::rate_limited`].
+///
+/// The limit is a token bucket holding one second's worth of messages, so bursts of up to
+/// that many messages are sent immediately after a quiet second. Receiving is not
+/// limited, and is done through [`get_ref`](Self::get_ref).
+pub struct RateLimited<S, R> {
+    channel: Channel<S, R>,
+    policy: RateLimitPolicy,
+    start: Instant,
+    /// Nanoseconds since `start` per message.
+    interval: u64,
+    /// Nanoseconds since `start` at which the bucket will next be full, in the style of
+    /// the generic cell rate algorithm. Kept in an atomic so sending never locks.
+    full_at: AtomicU64,
+    burst: u64,
+}
+
+impl<S, R> RateLimited<S, R> {
+    fn now(&self) -> u64 {
+        u64::try_from(self.start.elapsed().as_nanos()).unwrap_or(u64::MAX)
+    }
+
+    /// Takes a token, returning how long to wait before it may be used, or `None` if
+    /// waiting is required and the policy forbids it.
+    fn acquire(&self) -> Option<Duration> {
+        let now = self.now();
+        let mut full_at = self.full_at.load(Ordering::Relaxed);
+
+        loop {
+            let next = full_at.max(now) + self.interval;
+            let wait = next.saturating_sub(self.burst * self.interval + now);
+            if wait > 0 && self.policy == RateLimitPolicy::Error {
+                return None;
+            }
+
+            match self.full_at.compare_exchange_weak(
+                full_at,
+                next,
+                Ordering::Relaxed,
+                Ordering::Relaxed,
+            ) {
+                Ok(_) => return Some(Duration::from_nanos(wait)),
+                Err(actual) => full_at = actual,
+            }
+        }
+    }
+
+    /// Sends a value to the other side once the rate limit allows it, or fails
+    /// immediately under [`RateLimitPolicy::Error`].
+    pub fn send(&self, s: S) -> Result<(), RateLimitError<S>> {
+        match self.acquire() {
+            Some(wait) => thread::sleep(wait),
+            None => return Err(RateLimitError::RateLimited(s)),
+        }
+
+        self.channel.send(s).map_err(Into::into)
+    }
+
+    /// Returns a reference to the underlying channel, which receives as usual.
+    ///
+    /// Messages sent through it are neither limited nor counted against the limit.
+    pub fn get_ref(&self) -> &Channel<S, R> {
+        &self.channel
+    }
+
+    /// Returns a mutable reference to the underlying channel.
+    ///
+    /// Messages sent through it are neither limited nor counted against the limit.
+    pub fn get_mut(&mut self) -> &mut Channel<S, R> {
+        &mut self.channel
+    }
+
+    /// Unwraps this adapter, returning the underlying channel.
+    pub fn into_inner(self) -> Channel<S, R> {
+        self.channel
+    }
+}
+
+impl<S, R> fmt::Debug for RateLimited<S, R> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("RateLimited")
+            .field("channel", &self.channel)
+            .field("policy", &self.policy)
+            .field("max_per_sec", &self.burst)
+            .finish()
+    }
+}
+
+impl<S, R> Channel<S, R> {
+    /// Returns an adapter that sends at most `max_per_sec` messages per second, applying
+    /// `policy` to messages beyond that.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `max_per_sec` is zero.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use bichannel::{RateLimitError, RateLimitPolicy};
+    ///
+    /// let (left, right) = bichannel::channel::<u8, ()>();
+    /// let left = left.rate_limited(2, RateLimitPolicy::Error);
+    ///
+    /// left.send(1).unwrap();
+    /// left.send(2).unwrap();
+    /// assert_eq!(left.send(3), Err(RateLimitError::RateLimited(3)));
+    ///
+    /// assert_eq!(right.try_iter().collect::<Vec<_>>(), [1, 2]);
+    /// ```
+    pub fn rate_limited(self, max_per_sec: u32, policy: RateLimitPolicy) -> RateLimited<S, R> {
+        assert!(
+            max_per_sec > 0,
+            "rate limit must allow at least one message"
+        );
+
+        RateLimited {
+            channel: self,
+            policy,
+            start: Instant::now(),
+            interval: 1_000_000_000 / u64::from(max_per_sec),
+            full_at: AtomicU64::new(0),
+            burst: u64::from(max_per_sec),
+        }
+    }
+}
+
+#[cfg(test)]
+mod examples {
+    use std::time::{Duration, Instant};
+
+    use crate::RateLimitPolicy;
+
+    #[test]
+    fn test_blocking_limit() {
+        let (left, right) = crate::channel::<u8, ()>();
+        let left = left.rate_limited(5, RateLimitPolicy::Block);
+
+        let start = Instant::now();
+        for i in 0..10 {
+            left.send(i).unwrap();
+        }
+        let elapsed = start.elapsed();
+
+        assert!(elapsed >= Duration::from_millis(950), "{:?}", elapsed);
+        assert!(elapsed < Duration::from_millis(1500), "{:?}", elapsed);
+        assert_eq!(right.try_iter().count(), 10);
+    }
+
+    #[test]
+    fn test_error_limit_refills() {
+        let (left, right) = crate::channel::<u8, ()>();
+        let left = left.rate_limited(20, RateLimitPolicy::Error);
+
+        let sent = (0..30).filter(|&i| left.send(i).is_ok()).count();
+        assert_eq!(sent, 20);
+
+        std::thread::sleep(Duration::from_millis(120));
+        assert!(left.send(30).is_ok());
+        assert_eq!(right.try_iter().last(), Some(30));
+    }
+}
@@ -0,0 +1,291 @@
+//! At-least-once delivery over a channel that may lose messages.
+
+use std::collections::{BTreeSet, HashMap};
+use std::fmt;
+use std::sync::{Arc, Mutex, MutexGuard, PoisonError};
+use std::thread;
+use std::time::{Duration, Instant};
+
+use crate::{Channel, RecvError, RecvHalf, RecvTimeoutError, SendError, SendHalf, TryRecvError};
+
+/// A message exchanged between two [`Reliable`] sides.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Packet<T> {
+    /// A value sent with [`Reliable::send`], numbered so it can be acknowledged.
+    Data {
+        /// Numbers the values sent by one side, from zero.
+        seq: u64,
+        /// The value itself.
+        payload: T,
+    },
+    /// Acknowledges the `Data` packet with this sequence number.
+    Ack(u64),
+}
+
+/// The packets sent but not yet acknowledged, with when each was last sent.
+struct Unacked<T> {
+    next_seq: u64,
+    packets: HashMap<u64, (T, Instant)>,
+}
+
+/// The sequence numbers of the packets already received, so that retransmissions
+/// are not delivered twice.
+struct Delivered {
+    /// Every sequence number below this has been received.
+    below: u64,
+    /// Sequence numbers above `below` that have been received.
+    above: BTreeSet<u64>,
+}
+
+impl Delivered {
+    /// Records `seq` as received, returning `false` if it already was.
+    fn insert(&mut self, seq: u64) -> bool {
+        if seq < self.below || !self.above.insert(seq) {
+            return false;
+        }
+        while self.above.remove(&self.below) {
+            self.below += 1;
+        }
+        true
+    }
+}
+
+/// One side of a bidirectional channel that delivers every value at least once, and
+/// surfaces it exactly once, over a channel that may lose messages, created by
+/// [`Reliable::new`].
+///
+/// Each value is numbered and kept until the other side acknowledges it, and sent
+/// again every time the retransmission timeout passes without an acknowledgement.
+/// The receiving side acknowledges every copy it receives, but only surfaces the
+/// first. A background thread handles acknowledgements and retransmissions, so
+/// neither is visible to the user. A retransmitted value may be received after
+/// values sent later.
+///
+/// The wrapped channel is split between this side and its background thread, so
+/// unlike the other adapters it cannot be borrowed or unwrapped.
+pub struct Reliable<T> {
+    sender: SendHalf<Packet<T>>,
+    /// Fed by the background thread, which stops once this side is dropped.
+    receiver: Channel<(), T>,
+    unacked: Arc<Mutex<Unacked<T>>>,
+}
+
+fn lock<T>(unacked: &Mutex<Unacked<T>>) -> MutexGuard<'_, Unacked<T>> {
+    unacked.lock().unwrap_or_else(PoisonError::into_inner)
+}
+
+impl<T: Clone + Send + 'static> Reliable<T> {
+    /// Wraps a channel that may lose messages, such as one bridging an unreliable
+    /// transport, retransmitting unacknowledged values every `timeout`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the background thread cannot be spawned.
+    pub fn new(channel: Channel<Packet<T>, Packet<T>>, timeout: Duration) -> Self {
+        let (sender, incoming) = channel.split();
+        let (values, receiver) = crate::channel();
+        let unacked = Arc::new(Mutex::new(Unacked {
+            next_seq: 0,
+            packets: HashMap::new(),
+        }));
+
+        let (retransmit, shared) = (sender.clone(), unacked.clone());
+        thread::Builder::new()
+            .name("bichannel-reliable".into())
+            .spawn(move || deliver(incoming, retransmit, values, &shared, timeout))
+            .expect("failed to spawn the bichannel reliability thread");
+
+        Reliable {
+            sender,
+            receiver,
+            unacked,
+        }
+    }
+
+    /// Sends a value to the other side, retransmitting it until it is acknowledged.
+    ///
+    /// Fails, handing back the value, only if the underlying channel has hung up.
+    pub fn send(&self, t: T) -> Result<(), SendError<T>> {
+        // Sent without holding the lock, so that a send blocked on a full channel holds
+        // back neither other sends nor the acknowledgements handled in the background.
+        let seq = {
+            let mut unacked = lock(&self.unacked);
+            unacked.next_seq += 1;
+            unacked.next_seq - 1
+        };
+
+        let packet = Packet::Data {
+            seq,
+            payload: t.clone(),
+        };
+        if self.sender.send(packet).is_err() {
+            return Err(SendError(t));
+        }
+
+        // Should the acknowledgement have been handled already, the value is sent once
+        // more, and acknowledged again.
+        lock(&self.unacked).packets.insert(seq, (t, Instant::now()));
+        Ok(())
+    }
+}
+
+/// Acknowledges and delivers incoming values, and retransmits outgoing ones, until
+/// either side hangs up.
+fn deliver<T: Clone>(
+    incoming: RecvHalf<Packet<T>>,
+    sender: SendHalf<Packet<T>>,
+    values: Channel<T, ()>,
+    unacked: &Mutex<Unacked<T>>,
+    timeout: Duration,
+) {
+    let mut delivered = Delivered {
+        below: 0,
+        above: BTreeSet::new(),
+    };
+    // Without a representable deadline, values are never retransmitted.
+    let mut next_check = crate::deadline(timeout);
+
+    loop {
+        // Checked on every packet, so that a steady stream of them does not hold back
+        // the retransmissions.
+        if next_check.is_some_and(|check| Instant::now() >= check) {
+            if values.is_disconnected() {
+                break;
+            }
+
+            let now = Instant::now();
+            for (&seq, (payload, sent)) in lock(unacked).packets.iter_mut() {
+                if now.duration_since(*sent) >= timeout {
+                    let payload = payload.clone();
+                    let _ = sender.send(Packet::Data { seq, payload });
+                    *sent = now;
+                }
+            }
+            next_check = crate::deadline(timeout);
+        }
+
+        match incoming.receiver.recv_until(next_check) {
+            Ok(Packet::Data { seq, payload }) => {
+                let _ = sender.send(Packet::Ack(seq));
+                if delivered.insert(seq) && values.send(payload).is_err() {
+                    break;
+                }
+            }
+            Ok(Packet::Ack(seq)) => {
+                lock(unacked).packets.remove(&seq);
+            }
+            Err(RecvTimeoutError::Timeout) => (),
+            Err(RecvTimeoutError::Disconnected) => break,
+        }
+    }
+}
+
+impl<T> Reliable<T> {
+    /// Blocks until the background thread delivers a value, which is the first copy of
+    /// it to arrive.
+    pub fn recv(&self) -> Result<T, RecvError> {
+        self.receiver.recv()
+    }
+
+    /// Returns a value already delivered by the background thread, without blocking.
+    pub fn try_recv(&self) -> Result<T, TryRecvError> {
+        self.receiver.try_recv()
+    }
+
+    /// Waits for at most `timeout` for the background thread to deliver a value.
+    pub fn recv_timeout(&self, timeout: Duration) -> Result<T, RecvTimeoutError> {
+        self.receiver.recv_timeout(timeout)
+    }
+
+    /// Returns the number of values sent that the other side has yet to acknowledge.
+    pub fn unacked(&self) -> usize {
+        lock(&self.unacked).packets.len()
+    }
+}
+
+impl<T> fmt::Debug for Reliable<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Reliable")
+            .field("unacked", &self.unacked())
+            .finish_non_exhaustive()
+    }
+}
+
+#[cfg(test)]
+mod examples {
+    use std::collections::HashSet;
+    use std::thread;
+    use std::time::Duration;
+
+    use crate::{Packet, RecvHalf, Reliable, SendHalf};
+
+    /// Relays packets from `from` to `to`, losing the first copy of every value.
+    fn lossy<T: Send + 'static>(from: RecvHalf<Packet<T>>, to: SendHalf<Packet<T>>) {
+        thread::spawn(move || {
+            let mut seen = HashSet::new();
+            while let Ok(packet) = from.recv() {
+                if let Packet::Data { seq, .. } = packet {
+                    if seen.insert(seq) {
+                        continue;
+                    }
+                }
+                if to.send(packet).is_err() {
+                    break;
+                }
+            }
+        });
+    }
+
+    #[test]
+    fn test_exactly_once_over_lossy_link() {
+        let (left, left_wire) = crate::channel::<Packet<u32>, Packet<u32>>();
+        let (right, right_wire) = crate::channel::<Packet<u32>, Packet<u32>>();
+
+        let (left_out, left_in) = left_wire.split();
+        let (right_out, right_in) = right_wire.split();
+        lossy(left_in, right_out);
+        lossy(right_in, left_out);
+
+        let timeout = Duration::from_millis(20);
+        let left = Reliable::new(left, timeout);
+        let right = Reliable::new(right, timeout);
+
+        for i in 0..10 {
+            left.send(i).unwrap();
+        }
+        right.send(100).unwrap();
+
+        let mut received: Vec<_> = (0..10).map(|_| right.recv().unwrap()).collect();
+        received.sort_unstable();
+        assert_eq!(received, (0..10).collect::<Vec<_>>());
+        assert_eq!(left.recv(), Ok(100));
+
+        // Further retransmissions are acknowledged, but not delivered again.
+        thread::sleep(timeout * 5);
+        assert!(right.try_recv().is_err());
+        assert!(left.try_recv().is_err());
+        assert_eq!(left.unacked(), 0);
+        assert_eq!(right.unacked(), 0);
+    }
+
+    #[test]
+    fn test_retransmits_under_steady_traffic() {
+        let (local, remote) = crate::channel::<Packet<u32>, Packet<u32>>();
+        let local = Reliable::new(local, Duration::from_millis(20));
+        let (remote_out, remote_in) = remote.split();
+
+        // Packets keep arriving, more often than the retransmission timeout.
+        thread::spawn(move || {
+            while remote_out.send(Packet::Ack(u64::MAX)).is_ok() {
+                thread::sleep(Duration::from_millis(1));
+            }
+        });
+
+        local.send(7).unwrap();
+        let packet = Packet::Data { seq: 0, payload: 7 };
+        assert_eq!(remote_in.recv(), Ok(packet));
+        // The first copy went unacknowledged, so another follows.
+        assert_eq!(remote_in.recv_timeout(Duration::from_secs(5)), Ok(packet));
+        assert_eq!(local.unacked(), 1);
+    }
+}
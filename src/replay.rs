@@ -0,0 +1,69 @@
+//! Snapshotting buffered messages, to hand them off across a restart.
+
+use crate::{Channel, SendError};
+
+impl<S, R> Channel<S, R> {
+    /// Receives every message already buffered from the other side without blocking,
+    /// returning them in order. Another name for [`try_recv_all`].
+    ///
+    /// [`try_recv_all`]: Self::try_recv_all
+    #[deprecated(note = "use `try_recv_all`, which this is another name for")]
+    pub fn drain_to_vec(&self) -> Vec<R> {
+        self.try_recv_all()
+    }
+}
+
+/// Sends a batch saved with [`Channel::try_recv_all`] to the other side of `channel`,
+/// in order.
+///
+/// Together with serde on the saved `Vec`, this lets in-flight messages outlive the
+/// process that held them.
+///
+/// If a value cannot be sent, it is handed back inside the error along with every
+/// value after it, so nothing in the batch is lost.
+///
+/// # Examples
+///
+/// ```
+/// let (old, saved) = bichannel::channel::<&str, ()>();
+/// old.send_all(vec!["a", "b"]).unwrap();
+/// let batch = saved.try_recv_all();
+///
+/// let (fresh, restored) = bichannel::channel::<&str, ()>();
+/// bichannel::replay(&fresh, batch).unwrap();
+///
+/// assert_eq!(restored.try_iter().collect::<Vec<_>>(), ["a", "b"]);
+/// ```
+pub fn replay<S, R>(channel: &Channel<S, R>, batch: Vec<S>) -> Result<(), SendError<Vec<S>>> {
+    let mut batch = batch.into_iter();
+    while let Some(s) = batch.next() {
+        if let Err(SendError(s)) = channel.send(s) {
+            return Err(SendError(std::iter::once(s).chain(batch).collect()));
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod examples {
+    use crate::SendError;
+
+    #[test]
+    fn test_drain_and_replay_keep_order() {
+        let (left, right) = crate::sync_channel::<u32, ()>(16);
+        left.send_all(0..10).unwrap();
+
+        let batch = right.try_recv_all();
+        assert_eq!(batch, (0..10).collect::<Vec<_>>());
+
+        let (fresh, restored) = crate::channel::<u32, ()>();
+        crate::replay(&fresh, batch.clone()).unwrap();
+        assert_eq!(restored.try_recv_all(), batch);
+
+        drop(restored);
+        assert_eq!(
+            crate::replay(&fresh, vec![1, 2]),
+            Err(SendError(vec![1, 2]))
+        );
+    }
+}
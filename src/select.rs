@@ -0,0 +1,335 @@
+//! Waiting on several channels at once, enabled with the `crossbeam` feature.
+//!
+//! This exposes `crossbeam_channel::Select` over the receiving halves of
+//! [`Channel`]s.
+
+use std::time::Duration;
+
+use crate::{Channel, RecvError, SelectTimeoutError};
+
+/// Waits until one of several channels has a message ready to be received.
+///
+/// Channels are registered with [`add_recv`], which returns the index used to identify
+/// the operation once it fires.
+///
+/// [`add_recv`]: Self::add_recv
+///
+/// When several channels are ready at once, [`select`] picks one at random,
+/// [`select_biased`] always picks the one registered first, and [`select_fair`] takes
+/// them in turn, so that a busy channel cannot starve the others.
+///
+/// [`select`]: Self::select
+/// [`select_biased`]: Self::select_biased
+/// [`select_fair`]: Self::select_fair
+///
+/// # Examples
+///
+/// ```
+/// use bichannel::Select;
+///
+/// let (a, _a_peer) = bichannel::channel::<(), u8>();
+/// let (b, b_peer) = bichannel::channel::<(), u8>();
+///
+/// b_peer.send(2).unwrap();
+///
+/// let mut select = Select::new();
+/// select.add_recv(&a);
+/// let b_index = select.add_recv(&b);
+///
+/// let operation = select.select();
+/// assert_eq!(operation.index(), b_index);
+/// assert_eq!(operation.recv(&b), Ok(2));
+/// ```
+#[derive(Debug)]
+pub struct Select<'a> {
+    inner: crossbeam_channel::Select<'a>,
+    /// Each operation registered on its own, so that they can be tried in order.
+    singles: Vec<crossbeam_channel::Select<'a>>,
+    /// The operation [`select_fair`](Self::select_fair) tries first.
+    next: usize,
+}
+
+impl<'a> Select<'a> {
+    /// Creates an empty list of channels to wait on.
+    pub fn new() -> Self {
+        Select {
+            inner: crossbeam_channel::Select::new(),
+            singles: Vec::new(),
+            next: 0,
+        }
+    }
+
+    /// Registers a receive on `channel`, returning the index of the operation.
+    pub fn add_recv<S, R>(&mut self, channel: &'a Channel<S, R>) -> usize {
+        let mut single = crossbeam_channel::Select::new();
+        channel.receiver.select(&mut single);
+        self.singles.push(single);
+
+        channel.receiver.select(&mut self.inner)
+    }
+
+    /// Blocks until one of the registered channels is ready, and selects it.
+    ///
+    /// The returned operation must be completed with [`SelectedOperation::recv`]. If
+    /// several channels are ready, one of them is chosen at random.
+    pub fn select(&mut self) -> SelectedOperation<'a> {
+        SelectedOperation::new(self.inner.select())
+    }
+
+    /// Blocks until one of the registered channels is ready, and selects the ready
+    /// channel registered first.
+    ///
+    /// Useful when some channels should take priority, such as a control channel
+    /// registered ahead of a data channel. A channel registered later is only
+    /// selected while every one before it is empty, so a busy channel can starve
+    /// those after it.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use bichannel::Select;
+    ///
+    /// let (a, a_peer) = bichannel::channel::<(), u8>();
+    /// let (b, b_peer) = bichannel::channel::<(), u8>();
+    ///
+    /// a_peer.send(1).unwrap();
+    /// b_peer.send(2).unwrap();
+    ///
+    /// let mut select = Select::new();
+    /// select.add_recv(&a);
+    /// select.add_recv(&b);
+    ///
+    /// let operation = select.select_biased();
+    /// assert_eq!(operation.index(), 0);
+    /// assert_eq!(operation.recv(&a), Ok(1));
+    /// ```
+    pub fn select_biased(&mut self) -> SelectedOperation<'a> {
+        self.select_from(0)
+    }
+
+    /// Blocks until one of the registered channels is ready, and selects a ready
+    /// channel in round-robin order.
+    ///
+    /// Each call starts looking from the channel after the one last selected this way,
+    /// so channels that stay ready are selected in turn, and none is starved.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use bichannel::Select;
+    ///
+    /// let (a, a_peer) = bichannel::channel::<(), u8>();
+    /// let (b, b_peer) = bichannel::channel::<(), u8>();
+    ///
+    /// a_peer.send_all(vec![1, 2]).unwrap();
+    /// b_peer.send_all(vec![3, 4]).unwrap();
+    ///
+    /// let mut select = Select::new();
+    /// select.add_recv(&a);
+    /// select.add_recv(&b);
+    ///
+    /// let indices: Vec<_> = (0..4)
+    ///     .map(|_| {
+    ///         let operation = select.select_fair();
+    ///         let index = operation.index();
+    ///         operation.recv(if index == 0 { &a } else { &b }).unwrap();
+    ///         index
+    ///     })
+    ///     .collect();
+    /// assert_eq!(indices, [0, 1, 0, 1]);
+    /// ```
+    pub fn select_fair(&mut self) -> SelectedOperation<'a> {
+        let operation = self.select_from(self.next);
+        self.next = operation.index + 1;
+        operation
+    }
+
+    /// Selects the first ready operation at or after `start`, wrapping around, and
+    /// blocking until one is ready.
+    fn select_from(&mut self, start: usize) -> SelectedOperation<'a> {
+        let count = self.singles.len();
+        loop {
+            for index in (0..count).map(|offset| (start + offset) % count) {
+                if let Ok(inner) = self.singles[index].try_select() {
+                    return SelectedOperation { inner, index };
+                }
+            }
+            self.inner.ready();
+        }
+    }
+
+    /// Blocks until one of the registered channels is ready, or `timeout` elapses, and
+    /// selects the ready channel.
+    ///
+    /// A channel that is ready is always selected in preference to timing out, even if
+    /// the timeout has already elapsed. As with [`select`], the returned operation must
+    /// be completed with [`SelectedOperation::recv`].
+    ///
+    /// [`select`]: Self::select
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::time::Duration;
+    /// use bichannel::{Select, SelectTimeoutError};
+    ///
+    /// let (a, a_peer) = bichannel::channel::<(), u8>();
+    ///
+    /// let mut select = Select::new();
+    /// select.add_recv(&a);
+    /// assert_eq!(
+    ///     select.select_timeout(Duration::from_millis(10)).unwrap_err(),
+    ///     SelectTimeoutError
+    /// );
+    ///
+    /// a_peer.send(1).unwrap();
+    /// let operation = select.select_timeout(Duration::ZERO).unwrap();
+    /// assert_eq!(operation.recv(&a), Ok(1));
+    /// ```
+    pub fn select_timeout(
+        &mut self,
+        timeout: Duration,
+    ) -> Result<SelectedOperation<'a>, SelectTimeoutError> {
+        self.inner
+            .select_timeout(timeout)
+            .map(SelectedOperation::new)
+            .map_err(|_| SelectTimeoutError)
+    }
+
+    /// Blocks until one of the registered channels is ready, returning its index.
+    ///
+    /// Unlike [`select`], no operation is selected, so the message still has to be
+    /// received from the channel, and another thread may take it first.
+    ///
+    /// [`select`]: Self::select
+    pub fn ready(&mut self) -> usize {
+        self.inner.ready()
+    }
+}
+
+impl<'a> Default for Select<'a> {
+    fn default() -> Self {
+        Select::new()
+    }
+}
+
+/// A receive chosen by [`Select::select`], which must be completed with [`recv`].
+///
+/// [`recv`]: Self::recv
+#[derive(Debug)]
+pub struct SelectedOperation<'a> {
+    inner: crossbeam_channel::SelectedOperation<'a>,
+    index: usize,
+}
+
+impl<'a> SelectedOperation<'a> {
+    fn new(inner: crossbeam_channel::SelectedOperation<'a>) -> Self {
+        let index = inner.index();
+        SelectedOperation { inner, index }
+    }
+
+    /// Returns the index of the selected operation, as returned by
+    /// [`Select::add_recv`].
+    pub fn index(&self) -> usize {
+        self.index
+    }
+
+    /// Completes the selected receive on `channel`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `channel` is not the channel registered at [`index`].
+    ///
+    /// [`index`]: Self::index
+    pub fn recv<S, R>(self, channel: &'a Channel<S, R>) -> Result<R, RecvError> {
+        channel.receiver.complete(self.inner)
+    }
+}
+
+#[cfg(test)]
+mod examples {
+    use std::time::{Duration, Instant};
+
+    use crate::{Select, SelectTimeoutError};
+
+    #[test]
+    fn test_select_scenario() {
+        let (first, first_peer) = crate::channel::<(), u8>();
+        let (second, second_peer) = crate::channel::<(), u8>();
+
+        second_peer.send(2).unwrap();
+
+        let mut select = Select::new();
+        select.add_recv(&first);
+        select.add_recv(&second);
+
+        let operation = select.select();
+        assert_eq!(operation.index(), 1);
+        assert_eq!(operation.recv(&second), Ok(2));
+
+        drop(first_peer);
+    }
+
+    #[test]
+    fn test_select_timeout() {
+        let (first, _first_peer) = crate::channel::<(), u8>();
+        let (second, second_peer) = crate::channel::<(), u8>();
+
+        let mut select = Select::new();
+        select.add_recv(&first);
+        select.add_recv(&second);
+
+        let start = Instant::now();
+        assert_eq!(
+            select
+                .select_timeout(Duration::from_millis(50))
+                .unwrap_err(),
+            SelectTimeoutError
+        );
+        assert!(start.elapsed() >= Duration::from_millis(50));
+
+        second_peer.send(2).unwrap();
+        let operation = select.select_timeout(Duration::ZERO).unwrap();
+        assert_eq!(operation.index(), 1);
+        assert_eq!(operation.recv(&second), Ok(2));
+    }
+
+    #[test]
+    fn test_fair_and_biased_selection() {
+        let (first, first_peer) = crate::channel::<(), u32>();
+        let (second, second_peer) = crate::channel::<(), u32>();
+        let (third, third_peer) = crate::channel::<(), u32>();
+        for peer in [&first_peer, &second_peer, &third_peer] {
+            peer.send_all(0..1000).unwrap();
+        }
+
+        let channels = [&first, &second, &third];
+        let mut select = Select::new();
+        for channel in channels {
+            select.add_recv(channel);
+        }
+
+        let mut counts = [0; 3];
+        for _ in 0..900 {
+            let operation = select.select_fair();
+            let index = operation.index();
+            operation.recv(channels[index]).unwrap();
+            counts[index] += 1;
+        }
+        for count in counts {
+            assert!((270..=330).contains(&count), "unfair counts {:?}", counts);
+        }
+
+        for _ in 0..10 {
+            let operation = select.select_biased();
+            assert_eq!(operation.index(), 0);
+            operation.recv(&first).unwrap();
+        }
+
+        // Once the first channel is empty, a biased select falls through to the next.
+        first.try_iter().for_each(drop);
+        let operation = select.select_biased();
+        assert_eq!(operation.index(), 1);
+        operation.recv(&second).unwrap();
+    }
+}
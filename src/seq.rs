@@ -0,0 +1,181 @@
+//! Checking that messages arrive in the order they were sent.
+
+use std::cell::Cell;
+use std::time::Duration;
+
+use crate::{Channel, RecvTimeoutError, SendError, SequenceError, TrySendError};
+
+/// One side of a bidirectional channel that numbers every message it sends, and
+/// checks that the messages it receives are numbered contiguously, created by
+/// [`seq_channel`].
+///
+/// Channels deliver messages in the order they were sent, so a gap points at a bug
+/// in whatever sits between the two sides. Each side counts from zero.
+#[derive(Debug)]
+pub struct SeqChannel<S, R> {
+    channel: Channel<(u64, S), (u64, R)>,
+    next_send: Cell<u64>,
+    next_recv: Cell<u64>,
+}
+
+impl<S, R> SeqChannel<S, R> {
+    /// Wraps a channel carrying sequence numbered messages, such as one that relays
+    /// messages between two `SeqChannel`s.
+    pub fn new(channel: Channel<(u64, S), (u64, R)>) -> Self {
+        SeqChannel {
+            channel,
+            next_send: Cell::new(0),
+            next_recv: Cell::new(0),
+        }
+    }
+
+    /// Numbers a value and sends it like [`Channel::send`]. The number is only used
+    /// up if the send succeeds.
+    pub fn send(&self, s: S) -> Result<(), SendError<S>> {
+        let seq = self.next_send.get();
+        self.channel
+            .send((seq, s))
+            .map_err(|SendError((_, s))| SendError(s))?;
+        self.next_send.set(seq.wrapping_add(1));
+        Ok(())
+    }
+
+    /// Numbers a value and sends it without blocking.
+    pub fn try_send(&self, s: S) -> Result<(), TrySendError<S>> {
+        let seq = self.next_send.get();
+        self.channel.try_send((seq, s)).map_err(|err| match err {
+            TrySendError::Full((_, s)) => TrySendError::Full(s),
+            TrySendError::Disconnected((_, s)) => TrySendError::Disconnected(s),
+        })?;
+        self.next_send.set(seq.wrapping_add(1));
+        Ok(())
+    }
+
+    /// Blocks until a value is received, checking that it is the next in sequence.
+    pub fn recv(&self) -> Result<R, SequenceError> {
+        let (seq, r) = self.channel.recv()?;
+        self.check(seq).map(|()| r)
+    }
+
+    /// Waits for at most `timeout` for a value, checking that it is the next in
+    /// sequence. A timeout is reported as `Ok(None)`.
+    pub fn recv_timeout(&self, timeout: Duration) -> Result<Option<R>, SequenceError> {
+        match self.channel.recv_timeout(timeout) {
+            Ok((seq, r)) => self.check(seq).map(|()| Some(r)),
+            Err(RecvTimeoutError::Timeout) => Ok(None),
+            Err(RecvTimeoutError::Disconnected) => Err(SequenceError::Disconnected),
+        }
+    }
+
+    fn check(&self, found: u64) -> Result<(), SequenceError> {
+        // The number comes from the other side, so it may be anything.
+        let expected = self.next_recv.replace(found.wrapping_add(1));
+        if found == expected {
+            Ok(())
+        } else {
+            Err(SequenceError::Gap { expected, found })
+        }
+    }
+
+    /// Returns a reference to the underlying channel.
+    ///
+    /// Messages go through it with their numbers, which are neither assigned nor
+    /// checked, so sending through it leaves a gap unless it numbers messages the
+    /// same way.
+    pub fn get_ref(&self) -> &Channel<(u64, S), (u64, R)> {
+        &self.channel
+    }
+
+    /// Returns a mutable reference to the underlying channel.
+    ///
+    /// Messages go through it with their numbers, which are neither assigned nor
+    /// checked.
+    pub fn get_mut(&mut self) -> &mut Channel<(u64, S), (u64, R)> {
+        &mut self.channel
+    }
+
+    /// Unwraps this adapter, returning the underlying channel.
+    pub fn into_inner(self) -> Channel<(u64, S), (u64, R)> {
+        self.channel
+    }
+}
+
+/// Creates a bidirectional channel whose sides number their messages and check the
+/// numbering on receipt, returning the left and right sides.
+///
+/// # Examples
+///
+/// ```
+/// let (left, right) = bichannel::seq_channel::<&str, ()>();
+///
+/// left.send("first").unwrap();
+/// left.send("second").unwrap();
+///
+/// assert_eq!(right.recv(), Ok("first"));
+/// assert_eq!(right.recv(), Ok("second"));
+/// ```
+pub fn seq_channel<T, U>() -> (SeqChannel<T, U>, SeqChannel<U, T>) {
+    let (left, right) = crate::channel();
+    (SeqChannel::new(left), SeqChannel::new(right))
+}
+
+#[cfg(test)]
+mod examples {
+    use crate::{SeqChannel, SequenceError};
+
+    #[test]
+    fn test_sequence_integrity() {
+        let (left, right) = crate::seq_channel::<u32, ()>();
+
+        let producer = std::thread::spawn(move || {
+            for i in 0..1000 {
+                left.send(i).unwrap();
+            }
+        });
+
+        for i in 0..1000 {
+            assert_eq!(right.recv(), Ok(i));
+        }
+        assert_eq!(right.recv(), Err(SequenceError::Disconnected));
+
+        producer.join().unwrap();
+    }
+
+    #[test]
+    fn test_detects_gap() {
+        let (wire, peer) = crate::channel::<(u64, ()), (u64, &str)>();
+        let wire = SeqChannel::new(wire);
+
+        peer.send((0, "a")).unwrap();
+        peer.send((2, "c")).unwrap();
+        peer.send((3, "d")).unwrap();
+
+        assert_eq!(wire.recv(), Ok("a"));
+        assert_eq!(
+            wire.recv(),
+            Err(SequenceError::Gap {
+                expected: 1,
+                found: 2
+            })
+        );
+        assert_eq!(wire.recv(), Ok("d"));
+    }
+
+    #[test]
+    fn test_wraps_after_the_last_number() {
+        let (wire, peer) = crate::channel::<(u64, ()), (u64, &str)>();
+        let wire = SeqChannel::new(wire);
+
+        peer.send((u64::MAX, "z")).unwrap();
+        peer.send((0, "a")).unwrap();
+
+        assert_eq!(
+            wire.recv(),
+            Err(SequenceError::Gap {
+                expected: 0,
+                found: u64::MAX
+            })
+        );
+        assert_eq!(wire.recv(), Ok("a"));
+    }
+}
@@ -0,0 +1,145 @@
+//! [`Sink`] support for the sending side of a channel, enabled with the `futures`
+//! feature.
+//!
+//! Channels created with [`channel`] are always ready to accept a value. For channels
+//! created with [`sync_channel`], a value that does not fit in the buffer is held until
+//! the other side receives, and the task is woken once there is room.
+//!
+//! [`channel`]: crate::channel
+//! [`sync_channel`]: crate::sync_channel
+
+use std::pin::Pin;
+use std::task::{Context, Poll};
+
+use futures_sink::Sink;
+
+use crate::{Channel, SendError};
+
+/// Sends values to the other side, unless this side is corked, in which case they are
+/// held back as by [`Channel::send`]. Closing the sink closes this side's sender, as
+/// in [`Channel::close_sender`].
+///
+/// # Examples
+///
+/// ```
+/// use futures::executor::block_on;
+/// use futures::stream::{self, StreamExt};
+///
+/// let (left, right) = bichannel::channel::<u8, u8>();
+///
+/// block_on(stream::iter(vec![1, 2, 3]).map(Ok).forward(left)).unwrap();
+///
+/// assert_eq!(right.into_iter().collect::<Vec<_>>(), vec![1, 2, 3]);
+/// ```
+impl<S, R> Sink<S> for Channel<S, R> {
+    type Error = SendError<S>;
+
+    fn poll_ready(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.get_mut().sender.poll_flush(cx)
+    }
+
+    fn start_send(self: Pin<&mut Self>, item: S) -> Result<(), Self::Error> {
+        let this = self.get_mut();
+        match this.cork.hold(item) {
+            Some(item) => this.sender.start_send(item),
+            None => Ok(()),
+        }
+    }
+
+    fn poll_flush(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.get_mut().sender.poll_flush(cx)
+    }
+
+    fn poll_close(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        let this = self.get_mut();
+
+        match this.sender.poll_flush(cx) {
+            Poll::Ready(Ok(())) => {
+                this.close_sender();
+                Poll::Ready(Ok(()))
+            }
+            poll => poll,
+        }
+    }
+}
+
+#[cfg(test)]
+mod examples {
+    use futures::stream::{self, StreamExt};
+
+    #[tokio::test]
+    async fn test_forward_scenario() {
+        let (left, right) = crate::sync_channel::<u8, u8>(1);
+
+        let handle = std::thread::spawn(move || {
+            std::thread::sleep(std::time::Duration::from_millis(10));
+            right.into_iter().collect::<Vec<_>>()
+        });
+
+        stream::iter(vec![1, 2, 3, 4, 5])
+            .map(Ok)
+            .forward(left)
+            .await
+            .unwrap();
+
+        assert_eq!(handle.join().unwrap(), vec![1, 2, 3, 4, 5]);
+    }
+
+    #[tokio::test]
+    async fn test_sink_respects_cork() {
+        use futures::SinkExt;
+
+        use crate::TryRecvError;
+
+        let (mut left, right) = crate::channel::<u8, u8>();
+
+        left.cork();
+        // Sending through the sink also flushes it, which does not uncork it.
+        SinkExt::send(&mut left, 1).await.unwrap();
+        left.feed(2).await.unwrap();
+        assert_eq!(right.try_recv(), Err(TryRecvError::Empty));
+
+        left.uncork().unwrap();
+        assert_eq!(right.try_iter().collect::<Vec<_>>(), [1, 2]);
+    }
+
+    #[test]
+    #[cfg(any(feature = "crossbeam", feature = "flume"))]
+    fn test_wakes_every_task_flushing_on_clones() {
+        use std::sync::mpsc;
+        use std::thread;
+        use std::time::Duration;
+
+        use futures::executor::block_on;
+        use futures::SinkExt;
+
+        let (left, right) = crate::sync_channel::<u8, ()>(1);
+        left.send(0).unwrap();
+        let (done, finished) = mpsc::channel();
+
+        // Each thread runs its own task, with its own waker, on a clone of the side,
+        // and both wait for room in the full buffer.
+        let tasks: Vec<_> = (1..=2)
+            .map(|n| {
+                let mut left = left.clone();
+                let done = done.clone();
+                thread::spawn(move || done.send(block_on(SinkExt::send(&mut left, n))).unwrap())
+            })
+            .collect();
+
+        thread::sleep(Duration::from_millis(50));
+        let mut received = Vec::new();
+        for _ in 0..3 {
+            received.push(right.recv_timeout(Duration::from_secs(5)).unwrap());
+        }
+        received[1..].sort_unstable();
+        assert_eq!(received, [0, 1, 2]);
+
+        for _ in 0..2 {
+            assert_eq!(finished.recv_timeout(Duration::from_secs(5)), Ok(Ok(())));
+        }
+        for task in tasks {
+            task.join().unwrap();
+        }
+    }
+}
@@ -0,0 +1,130 @@
+//! Counting the messages that pass through a [`Channel`].
+
+use crate::Channel;
+
+/// Message totals for one side of a channel, returned by [`Channel::stats`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct ChannelStats {
+    /// The number of messages this side has sent to the other side.
+    pub sent: u64,
+    /// The number of messages this side has received from the other side.
+    pub received: u64,
+}
+
+impl<S, R> Channel<S, R> {
+    /// Returns the number of messages that this side has sent and received so far.
+    ///
+    /// Only successful operations are counted, including those made through a
+    /// [`SendHalf`](crate::SendHalf) or, with the `crossbeam` or `flume` feature, a clone
+    /// of this side. A message discarded by an [`OverflowPolicy`](crate::OverflowPolicy)
+    /// is not counted as sent.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use bichannel::ChannelStats;
+    ///
+    /// let (left, right) = bichannel::channel::<u8, u8>();
+    ///
+    /// left.send(1).unwrap();
+    /// left.send(2).unwrap();
+    /// right.recv().unwrap();
+    ///
+    /// assert_eq!(left.stats(), ChannelStats { sent: 2, received: 0 });
+    /// assert_eq!(right.stats(), ChannelStats { sent: 0, received: 1 });
+    /// ```
+    pub fn stats(&self) -> ChannelStats {
+        ChannelStats {
+            sent: self.sender.sent_count(),
+            received: self.receiver.received_count(),
+        }
+    }
+
+    /// Returns the largest number of messages from the other side that have ever been
+    /// waiting to be received on this side at once. See [`Channel::len`].
+    ///
+    /// The depth is sampled after each send, so the mark never decreases. Only
+    /// available with the `crossbeam` or `flume` feature, as `std::sync::mpsc` does not
+    /// expose its queue depth.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// let (left, right) = bichannel::channel::<u8, u8>();
+    ///
+    /// right.send(1).unwrap();
+    /// right.send(2).unwrap();
+    /// left.recv().unwrap();
+    ///
+    /// assert_eq!(left.len(), 1);
+    /// assert_eq!(left.high_water_mark(), 2);
+    /// ```
+    #[cfg(any(feature = "crossbeam", feature = "flume"))]
+    pub fn high_water_mark(&self) -> usize {
+        self.receiver.high_water_mark()
+    }
+}
+
+#[cfg(test)]
+mod examples {
+    use crate::{ChannelStats, OverflowPolicy, TryRecvError};
+
+    #[test]
+    fn test_counts_successes_only() {
+        let (left, right) = crate::channel::<u32, u32>();
+
+        for i in 0..100 {
+            left.send(i).unwrap();
+        }
+        for _ in 0..60 {
+            right.recv().unwrap();
+        }
+        right.send(0).unwrap();
+
+        assert_eq!(left.try_recv(), Ok(0));
+        assert_eq!(left.try_recv(), Err(TryRecvError::Empty));
+
+        assert_eq!(
+            left.stats(),
+            ChannelStats {
+                sent: 100,
+                received: 1
+            }
+        );
+        assert_eq!(
+            right.stats(),
+            ChannelStats {
+                sent: 1,
+                received: 60
+            }
+        );
+
+        drop(left);
+        assert!(right.send(1).is_err());
+        assert_eq!(right.stats().sent, 1);
+
+        let (left, _right) = crate::bounded_with::<u8, ()>(1, OverflowPolicy::DropNewest);
+        left.send(1).unwrap();
+        left.send(2).unwrap();
+        assert_eq!(left.stats().sent, 1);
+    }
+
+    #[test]
+    #[cfg(any(feature = "crossbeam", feature = "flume"))]
+    fn test_high_water_mark_never_decreases() {
+        let (left, right) = crate::channel::<u8, ()>();
+        assert_eq!(right.high_water_mark(), 0);
+
+        for i in 0..5 {
+            left.send(i).unwrap();
+        }
+        right.try_iter().for_each(drop);
+        for i in 0..2 {
+            left.send(i).unwrap();
+        }
+
+        assert_eq!(right.len(), 2);
+        assert_eq!(right.high_water_mark(), 5);
+        assert_eq!(left.high_water_mark(), 0);
+    }
+}
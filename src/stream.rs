@@ -0,0 +1,317 @@
+//! [`Stream`] support for the receiving side of a channel, enabled with the `futures`
+//! feature.
+//!
+//! Polling registers the task's waker with the other side, which wakes it on the next
+//! send or when it hangs up, so awaiting a message never spins or blocks the executor.
+//! The same polling is available as [`Channel::poll_recv`], for executors that drive
+//! futures by hand rather than through `Stream`.
+
+use std::pin::Pin;
+use std::task::{Context, Poll};
+
+use futures_core::Stream;
+
+use crate::{Channel, RecvHalf};
+
+impl<S, R> Channel<S, R> {
+    /// Attempts to receive a message from the other side without blocking, registering
+    /// the task to be woken if none is buffered.
+    ///
+    /// Returns `Ready(Some(_))` with a message, `Ready(None)` once the other side has
+    /// hung up, and otherwise `Pending`, after which the task is woken by the next send
+    /// or when the other side hangs up. This is the primitive [`Stream`] is built on,
+    /// for executors that poll with a [`Context`] directly.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::task::{Context, Poll};
+    /// use futures::task::noop_waker;
+    ///
+    /// let (left, right) = bichannel::channel::<u8, u8>();
+    /// let waker = noop_waker();
+    /// let mut cx = Context::from_waker(&waker);
+    ///
+    /// assert_eq!(left.poll_recv(&mut cx), Poll::Pending);
+    ///
+    /// right.send(1).unwrap();
+    /// assert_eq!(left.poll_recv(&mut cx), Poll::Ready(Some(1)));
+    ///
+    /// drop(right);
+    /// assert_eq!(left.poll_recv(&mut cx), Poll::Ready(None));
+    /// ```
+    pub fn poll_recv(&self, cx: &mut Context<'_>) -> Poll<Option<R>> {
+        let poll = self.receiver.poll_recv(cx);
+        if let Poll::Ready(None) = poll {
+            self.on_disconnect.fire();
+        }
+        poll
+    }
+
+    /// Consumes this side, returning a stream of the messages from the other side
+    /// converted with `f`, without needing `StreamExt`.
+    ///
+    /// Like the channel's own stream, it ends once the other side has hung up and
+    /// every message has been received.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use futures::executor::block_on;
+    /// use futures::StreamExt;
+    ///
+    /// let (left, right) = bichannel::channel::<(), u8>();
+    /// let mut doubled = left.into_stream_map(|n| n * 2);
+    ///
+    /// right.send(21).unwrap();
+    /// drop(right);
+    ///
+    /// assert_eq!(block_on(doubled.next()), Some(42));
+    /// assert_eq!(block_on(doubled.next()), None);
+    /// ```
+    pub fn into_stream_map<R2, F>(self, f: F) -> impl Stream<Item = R2>
+    where
+        F: FnMut(R) -> R2,
+    {
+        StreamMap { channel: self, f }
+    }
+}
+
+/// The stream returned by [`Channel::into_stream_map`].
+struct StreamMap<S, R, F> {
+    channel: Channel<S, R>,
+    f: F,
+}
+
+// `f` is only ever called through a plain `&mut`, never pinned.
+impl<S, R, F> Unpin for StreamMap<S, R, F> {}
+
+impl<S, R, R2, F> Stream for StreamMap<S, R, F>
+where
+    F: FnMut(R) -> R2,
+{
+    type Item = R2;
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<R2>> {
+        let this = self.get_mut();
+        this.channel.poll_recv(cx).map(|r| r.map(&mut this.f))
+    }
+}
+
+impl<R> RecvHalf<R> {
+    /// Attempts to receive a message from the counterpart without blocking, registering
+    /// the task to be woken if none is buffered. See [`Channel::poll_recv`].
+    pub fn poll_recv(&self, cx: &mut Context<'_>) -> Poll<Option<R>> {
+        self.receiver.poll_recv(cx)
+    }
+}
+
+/// Yields messages from the other side, ending once it has hung up.
+///
+/// # Examples
+///
+/// ```
+/// use futures::executor::block_on;
+/// use futures::StreamExt;
+///
+/// let (mut left, right) = bichannel::channel::<u8, u8>();
+///
+/// right.send(1).unwrap();
+/// drop(right);
+///
+/// assert_eq!(block_on(left.next()), Some(1));
+/// assert_eq!(block_on(left.next()), None);
+/// ```
+impl<S, R> Stream for Channel<S, R> {
+    type Item = R;
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<R>> {
+        self.poll_recv(cx)
+    }
+}
+
+/// Yields messages from the counterpart, ending once it has hung up.
+impl<R> Stream for RecvHalf<R> {
+    type Item = R;
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<R>> {
+        self.receiver.poll_recv(cx)
+    }
+}
+
+#[cfg(test)]
+mod examples {
+    use std::sync::Arc;
+    use std::task::{Context, Poll, Wake, Waker};
+    use std::thread::{self, Thread};
+    use std::time::Duration;
+
+    use futures::StreamExt;
+
+    /// Wakes a thread parked waiting for a poll to make progress.
+    struct Unpark(Thread);
+
+    impl Wake for Unpark {
+        fn wake(self: Arc<Self>) {
+            self.0.unpark();
+        }
+    }
+
+    #[tokio::test]
+    async fn test_stream_scenario() {
+        let (mut left, right) = crate::channel::<u8, u8>();
+
+        let handle = std::thread::spawn(move || {
+            for i in 1..=3 {
+                std::thread::sleep(std::time::Duration::from_millis(10));
+                right.send(i).unwrap();
+            }
+        });
+
+        assert_eq!(left.next().await, Some(1));
+        assert_eq!(left.next().await, Some(2));
+        assert_eq!(left.next().await, Some(3));
+        assert_eq!(left.next().await, None);
+
+        handle.join().unwrap();
+    }
+
+    #[tokio::test]
+    #[cfg(not(any(loom, feature = "crossbeam", feature = "flume")))]
+    async fn test_stream_from_parts() {
+        use std::sync::mpsc;
+
+        let (to_right, from_left) = mpsc::channel();
+        let mut right = crate::Channel::<u8, u8>::from_parts(mpsc::channel().0, from_left);
+
+        let handle = std::thread::spawn(move || {
+            std::thread::sleep(std::time::Duration::from_millis(10));
+            to_right.send(1).unwrap();
+        });
+
+        // Nothing wakes the task when `to_right` sends, so the stream must poll again.
+        assert_eq!(right.next().await, Some(1));
+        assert_eq!(right.next().await, None);
+
+        handle.join().unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_stream_end_runs_on_disconnect() {
+        use std::sync::atomic::{AtomicBool, Ordering};
+
+        let (mut left, right) = crate::channel::<u8, u8>();
+        let ran = Arc::new(AtomicBool::new(false));
+        let flag = ran.clone();
+        left.on_disconnect(move || flag.store(true, Ordering::SeqCst));
+
+        right.send(1).unwrap();
+        drop(right);
+        assert_eq!(left.next().await, Some(1));
+        assert!(!ran.load(Ordering::SeqCst));
+
+        assert_eq!(left.next().await, None);
+        assert!(ran.load(Ordering::SeqCst));
+    }
+
+    #[tokio::test]
+    async fn test_stream_map_converts_until_disconnect() {
+        let (left, right) = crate::channel::<(), u32>();
+        let mut lengths = left.into_stream_map(|n| n.to_string().len());
+
+        let handle = std::thread::spawn(move || {
+            for n in [7, 42, 1000] {
+                std::thread::sleep(Duration::from_millis(5));
+                right.send(n).unwrap();
+            }
+        });
+
+        assert_eq!(lengths.next().await, Some(1));
+        assert_eq!(lengths.next().await, Some(2));
+        assert_eq!(lengths.next().await, Some(4));
+        assert_eq!(lengths.next().await, None);
+
+        handle.join().unwrap();
+    }
+
+    #[test]
+    fn test_poll_recv_with_hand_rolled_executor() {
+        let (left, right) = crate::channel::<(), u8>();
+        let waker = Waker::from(Arc::new(Unpark(thread::current())));
+        let mut cx = Context::from_waker(&waker);
+
+        let handle = thread::spawn(move || {
+            thread::sleep(Duration::from_millis(20));
+            right.send(7).unwrap();
+        });
+
+        let mut polls = 0;
+        let value = loop {
+            polls += 1;
+            match left.poll_recv(&mut cx) {
+                Poll::Ready(value) => break value,
+                Poll::Pending => thread::park(),
+            }
+        };
+
+        assert_eq!(value, Some(7));
+        assert!(polls >= 2);
+        handle.join().unwrap();
+        assert_eq!(left.poll_recv(&mut cx), Poll::Ready(None));
+    }
+
+    #[test]
+    #[cfg(any(feature = "crossbeam", feature = "flume"))]
+    fn test_wakes_every_task_waiting_on_clones() {
+        use std::sync::mpsc;
+
+        use futures::executor::block_on;
+
+        let (left, right) = crate::channel::<(), u8>();
+        let (done, finished) = mpsc::channel();
+
+        // Each thread runs its own task, with its own waker, on a clone of the side.
+        let tasks: Vec<_> = (0..2)
+            .map(|_| {
+                let mut left = left.clone();
+                let done = done.clone();
+                thread::spawn(move || done.send(block_on(left.next())).unwrap())
+            })
+            .collect();
+
+        thread::sleep(Duration::from_millis(50));
+        right.send(1).unwrap();
+        right.send(2).unwrap();
+
+        let mut received = [
+            finished.recv_timeout(Duration::from_secs(5)).unwrap(),
+            finished.recv_timeout(Duration::from_secs(5)).unwrap(),
+        ];
+        received.sort_unstable();
+        assert_eq!(received, [Some(1), Some(2)]);
+
+        for task in tasks {
+            task.join().unwrap();
+        }
+    }
+
+    #[test]
+    #[cfg(loom)]
+    fn test_loom_wakes_a_task_waiting_to_receive() {
+        use loom::thread;
+
+        loom::model(|| {
+            let (left, right) = crate::channel::<u8, u8>();
+
+            // The side is handed back, so that only the send can wake the task.
+            let sender = thread::spawn(move || {
+                right.send(1).unwrap();
+                right
+            });
+
+            let received = loom::future::block_on(std::future::poll_fn(|cx| left.poll_recv(cx)));
+            assert_eq!(received, Some(1));
+            drop(sender.join().unwrap());
+        });
+    }
+}
@@ -0,0 +1,170 @@
+//! Sharing one side of a channel between threads by reference.
+
+use std::sync::PoisonError;
+use std::time::{Duration, Instant};
+
+use crate::alive::Peer;
+use crate::primitive::{Mutex, MutexGuard};
+use crate::{
+    Channel, RecvError, RecvHalf, RecvTimeoutError, SendError, SendHalf, TryRecvError, TrySendError,
+};
+
+/// One side of a bidirectional channel that can be shared between threads, for
+/// example through an [`Arc`](std::sync::Arc).
+///
+/// The receiving half is guarded by a lock, so concurrent receives on the same side
+/// take turns and each message goes to exactly one of them. Sending does not lock.
+///
+/// # Examples
+///
+/// ```
+/// use std::sync::Arc;
+///
+/// let (left, right) = bichannel::channel::<(), u8>();
+/// let left = Arc::new(bichannel::SyncChannel::new(left));
+///
+/// right.send(1).unwrap();
+/// right.send(2).unwrap();
+///
+/// let shared = left.clone();
+/// let handle = std::thread::spawn(move || shared.recv());
+///
+/// let mut received = [left.recv().unwrap(), handle.join().unwrap().unwrap()];
+/// received.sort();
+/// assert_eq!(received, [1, 2]);
+/// ```
+#[derive(Debug)]
+pub struct SyncChannel<S, R> {
+    sender: SendHalf<S>,
+    receiver: Mutex<RecvHalf<R>>,
+    /// Kept outside the lock, so checking for a disconnect never waits on a receive.
+    peer: Peer,
+}
+
+impl<S, R> SyncChannel<S, R> {
+    /// Wraps `channel` so that it can be shared between threads.
+    pub fn new(channel: Channel<S, R>) -> Self {
+        let (sender, receiver) = channel.split();
+
+        SyncChannel {
+            sender,
+            peer: receiver.peer.clone(),
+            receiver: Mutex::new(receiver),
+        }
+    }
+
+    /// Unwraps the shared channel.
+    pub fn into_inner(self) -> Channel<S, R> {
+        let receiver = self
+            .receiver
+            .into_inner()
+            .unwrap_or_else(PoisonError::into_inner);
+
+        Channel::join(self.sender, receiver)
+    }
+
+    fn lock(&self) -> MutexGuard<'_, RecvHalf<R>> {
+        self.receiver.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+
+    /// Attempts to send a value to the other side. See [`Channel::send`].
+    pub fn send(&self, s: S) -> Result<(), SendError<S>> {
+        self.sender.send(s)
+    }
+
+    /// Attempts to send a value to the other side without blocking. See
+    /// [`Channel::try_send`].
+    pub fn try_send(&self, s: S) -> Result<(), TrySendError<S>> {
+        self.sender.try_send(s)
+    }
+
+    /// Blocks until a message is received, waiting for any other thread receiving on
+    /// this side to finish first. See [`Channel::recv`].
+    pub fn recv(&self) -> Result<R, RecvError> {
+        self.lock().recv()
+    }
+
+    /// Attempts to receive a message without waiting for one, after waiting for any
+    /// other thread receiving on this side to finish. See [`Channel::try_recv`].
+    pub fn try_recv(&self) -> Result<R, TryRecvError> {
+        self.lock().try_recv()
+    }
+
+    /// Blocks until a message is received or `timeout` elapses, once any other thread
+    /// receiving on this side has finished. See [`Channel::recv_timeout`].
+    pub fn recv_timeout(&self, timeout: Duration) -> Result<R, RecvTimeoutError> {
+        self.lock().recv_timeout(timeout)
+    }
+
+    /// Blocks until a message is received or `deadline` is reached, once any other
+    /// thread receiving on this side has finished. See [`Channel::recv_deadline`].
+    pub fn recv_deadline(&self, deadline: Instant) -> Result<R, RecvTimeoutError> {
+        self.lock().recv_deadline(deadline)
+    }
+
+    /// Returns `true` if the other side has hung up. See [`Channel::is_disconnected`].
+    pub fn is_disconnected(&self) -> bool {
+        self.peer.hung_up()
+    }
+}
+
+impl<S, R> From<Channel<S, R>> for SyncChannel<S, R> {
+    fn from(channel: Channel<S, R>) -> Self {
+        SyncChannel::new(channel)
+    }
+}
+
+#[cfg(test)]
+mod examples {
+    #[test]
+    #[cfg(loom)]
+    fn test_loom_contending_receivers() {
+        use loom::sync::Arc;
+        use loom::thread;
+
+        use crate::SyncChannel;
+
+        loom::model(|| {
+            let (left, right) = crate::channel::<(), u8>();
+            right.send(1).unwrap();
+            right.send(2).unwrap();
+
+            let left = Arc::new(SyncChannel::new(left));
+            let other = {
+                let left = left.clone();
+                thread::spawn(move || left.recv().unwrap())
+            };
+
+            let mut received = [left.recv().unwrap(), other.join().unwrap()];
+            received.sort_unstable();
+            assert_eq!(received, [1, 2]);
+        });
+    }
+
+    #[test]
+    #[cfg(loom)]
+    fn test_loom_try_recv_never_duplicates() {
+        use loom::sync::Arc;
+        use loom::thread;
+
+        use crate::{SyncChannel, TryRecvError};
+
+        loom::model(|| {
+            let (left, right) = crate::channel::<(), u8>();
+            right.send(1).unwrap();
+
+            let left = Arc::new(SyncChannel::new(left));
+            let other = {
+                let left = left.clone();
+                thread::spawn(move || left.try_recv())
+            };
+
+            let mine = left.try_recv();
+            let theirs = other.join().unwrap();
+            match (mine, theirs) {
+                (Ok(1), Err(TryRecvError::Empty)) | (Err(TryRecvError::Empty), Ok(1)) => (),
+                outcome => panic!("unexpected outcome {:?}", outcome),
+            }
+        });
+    }
+}
@@ -0,0 +1,136 @@
+//! Mirroring the values received on a [`Channel`] to an observer.
+
+use std::fmt;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::Duration;
+
+use crate::{Channel, RecvError, RecvTimeoutError, SendHalf, TryRecvError};
+
+/// One side of a bidirectional channel that sends a copy of every value it receives to
+/// an observer, created by [`Channel::tee`].
+///
+/// The copy is sent just before the value is returned, so the observer sees values in
+/// the order they were received. Once the observer has hung up, values are no longer
+/// copied, and receiving carries on as before.
+pub struct Tee<S, R> {
+    channel: Channel<S, R>,
+    observer: SendHalf<R>,
+    mirroring: AtomicBool,
+}
+
+impl<S, R: Clone> Tee<S, R> {
+    fn mirror(&self, r: &R) {
+        if self.mirroring.load(Ordering::Relaxed) && self.observer.send(r.clone()).is_err() {
+            self.mirroring.store(false, Ordering::Relaxed);
+        }
+    }
+
+    /// Blocks until a value is received, copying it to the observer.
+    pub fn recv(&self) -> Result<R, RecvError> {
+        let r = self.channel.recv()?;
+        self.mirror(&r);
+        Ok(r)
+    }
+
+    /// Returns a pending value without blocking, copying it to the observer.
+    pub fn try_recv(&self) -> Result<R, TryRecvError> {
+        let r = self.channel.try_recv()?;
+        self.mirror(&r);
+        Ok(r)
+    }
+
+    /// Waits for at most `timeout` for a value, copying it to the observer.
+    pub fn recv_timeout(&self, timeout: Duration) -> Result<R, RecvTimeoutError> {
+        let r = self.channel.recv_timeout(timeout)?;
+        self.mirror(&r);
+        Ok(r)
+    }
+}
+
+impl<S, R> Tee<S, R> {
+    /// Returns a reference to the underlying channel, which sends as usual.
+    ///
+    /// Values received through it are not copied to the observer.
+    pub fn get_ref(&self) -> &Channel<S, R> {
+        &self.channel
+    }
+
+    /// Returns a mutable reference to the underlying channel.
+    ///
+    /// Values received through it are not copied to the observer.
+    pub fn get_mut(&mut self) -> &mut Channel<S, R> {
+        &mut self.channel
+    }
+
+    /// Unwraps this adapter, returning the underlying channel and the observer.
+    pub fn into_inner(self) -> (Channel<S, R>, SendHalf<R>) {
+        (self.channel, self.observer)
+    }
+}
+
+impl<S, R> fmt::Debug for Tee<S, R>
+where
+    S: fmt::Debug,
+    R: fmt::Debug,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Tee")
+            .field("channel", &self.channel)
+            .field("mirroring", &self.mirroring.load(Ordering::Relaxed))
+            .finish_non_exhaustive()
+    }
+}
+
+impl<S, R: Clone> Channel<S, R> {
+    /// Returns an adapter that sends a copy of every value received to `observer`,
+    /// for logging or auditing without changing how values are consumed.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// let (left, right) = bichannel::channel::<u8, u8>();
+    /// let (audit, log) = bichannel::channel::<u8, ()>();
+    ///
+    /// let left = left.tee(audit.clone_sender());
+    ///
+    /// right.send(7).unwrap();
+    /// assert_eq!(left.recv(), Ok(7));
+    /// assert_eq!(log.recv(), Ok(7));
+    /// ```
+    pub fn tee(self, observer: SendHalf<R>) -> Tee<S, R> {
+        Tee {
+            channel: self,
+            observer,
+            mirroring: AtomicBool::new(true),
+        }
+    }
+}
+
+#[cfg(test)]
+mod examples {
+    use std::time::Duration;
+
+    use crate::TryRecvError;
+
+    #[test]
+    fn test_observer_sees_every_value() {
+        let (consumer, producer) = crate::channel::<(), u32>();
+        let (audit, log) = crate::channel::<u32, ()>();
+        let (observer, _) = audit.split();
+
+        let consumer = consumer.tee(observer);
+        for i in 1..=3 {
+            producer.send(i).unwrap();
+        }
+
+        assert_eq!(consumer.recv(), Ok(1));
+        assert_eq!(consumer.try_recv(), Ok(2));
+        assert_eq!(consumer.recv_timeout(Duration::from_secs(1)), Ok(3));
+        assert_eq!(log.try_iter().collect::<Vec<_>>(), [1, 2, 3]);
+
+        drop(log);
+        producer.send(4).unwrap();
+        assert_eq!(consumer.recv(), Ok(4));
+        assert_eq!(consumer.try_recv(), Err(TryRecvError::Empty));
+    }
+}
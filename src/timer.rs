@@ -0,0 +1,244 @@
+//! A background thread, shared by every channel, that runs jobs at a deadline.
+
+use std::cmp::Ordering;
+use std::collections::BinaryHeap;
+use std::mem;
+use std::sync::{Arc, Condvar, Mutex, MutexGuard, OnceLock, PoisonError};
+use std::thread;
+use std::time::{Duration, Instant};
+
+use crate::alive::Peer;
+use crate::{Channel, SendError};
+
+type Job = Box<dyn FnOnce() + Send>;
+
+struct Entry {
+    deadline: Instant,
+    /// Breaks ties between equal deadlines, so that jobs run in the order scheduled.
+    id: u64,
+    /// The side a scheduled send is from, which cancels the job once it is closed.
+    owner: Option<Peer>,
+    job: Job,
+}
+
+impl PartialEq for Entry {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl Eq for Entry {}
+
+impl PartialOrd for Entry {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for Entry {
+    /// Reversed, so that the heap yields the earliest deadline first.
+    fn cmp(&self, other: &Self) -> Ordering {
+        (other.deadline, other.id).cmp(&(self.deadline, self.id))
+    }
+}
+
+#[derive(Default)]
+struct Queue {
+    entries: BinaryHeap<Entry>,
+    next_id: u64,
+}
+
+#[derive(Default)]
+struct Timer {
+    queue: Mutex<Queue>,
+    changed: Condvar,
+}
+
+impl Timer {
+    fn run(&self) {
+        let mut queue = lock(self);
+
+        loop {
+            let now = Instant::now();
+            queue = match queue.entries.peek().map(|entry| entry.deadline) {
+                None => self
+                    .changed
+                    .wait(queue)
+                    .unwrap_or_else(PoisonError::into_inner),
+                Some(deadline) if deadline > now => {
+                    self.changed
+                        .wait_timeout(queue, deadline - now)
+                        .unwrap_or_else(PoisonError::into_inner)
+                        .0
+                }
+                Some(_) => {
+                    let entry = queue.entries.pop().expect("peeked entry");
+                    drop(queue);
+                    (entry.job)();
+                    lock(self)
+                }
+            };
+        }
+    }
+}
+
+static TIMER: OnceLock<Arc<Timer>> = OnceLock::new();
+
+fn lock(timer: &Timer) -> MutexGuard<'_, Queue> {
+    timer.queue.lock().unwrap_or_else(PoisonError::into_inner)
+}
+
+/// Runs `job` on the timer thread once `deadline` has passed, starting the thread if
+/// this is the first job.
+pub(crate) fn schedule(deadline: Instant, job: impl FnOnce() + Send + 'static) {
+    push(deadline, None, Box::new(job));
+}
+
+fn push(deadline: Instant, owner: Option<Peer>, job: Job) {
+    let timer = TIMER.get_or_init(|| {
+        let timer = Arc::new(Timer::default());
+        let runner = timer.clone();
+        thread::Builder::new()
+            .name("bichannel-timer".into())
+            .spawn(move || runner.run())
+            .expect("failed to spawn the bichannel timer thread");
+        timer
+    });
+
+    let mut queue = lock(timer);
+    let id = queue.next_id;
+    queue.next_id += 1;
+    queue.entries.push(Entry {
+        deadline,
+        id,
+        owner,
+        job,
+    });
+    drop(queue);
+
+    timer.changed.notify_one();
+}
+
+/// Drops the pending sends of every side that has since been closed, along with the
+/// senders they hold, so that the other side observes the hang up.
+pub(crate) fn cancel() {
+    let timer = match TIMER.get() {
+        Some(timer) => timer,
+        None => return,
+    };
+
+    let mut queue = lock(timer);
+    let (cancelled, kept): (Vec<_>, Vec<_>) = mem::take(&mut queue.entries)
+        .into_iter()
+        .partition(|entry| entry.owner.as_ref().is_some_and(Peer::hung_up));
+    queue.entries = kept.into();
+    drop(queue);
+
+    drop(cancelled);
+}
+
+impl<S: Send + 'static, R> Channel<S, R> {
+    /// Sends a value to the other side once `delay` has elapsed, without blocking.
+    ///
+    /// Fails, handing back the value, if the sending half of this side has been
+    /// closed. A delay too long to be represented never elapses.
+    ///
+    /// Scheduled sends from every channel share one background thread, which never
+    /// waits for room in a bounded buffer: if the buffer is full when the delay
+    /// elapses, or the other side has dropped its receiving half, the value is
+    /// discarded, which is reported as a warning with the `tracing` feature. Pending
+    /// sends are cancelled once every handle of this side that can send has been
+    /// dropped or closed its sending half, so they do not keep the other side from
+    /// observing the hang up.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::time::Duration;
+    /// use bichannel::TryRecvError;
+    ///
+    /// let (left, right) = bichannel::channel::<&str, ()>();
+    ///
+    /// left.send_after("retry", Duration::from_millis(10)).unwrap();
+    ///
+    /// assert_eq!(right.try_recv(), Err(TryRecvError::Empty));
+    /// assert_eq!(right.recv(), Ok("retry"));
+    /// ```
+    pub fn send_after(&self, value: S, delay: Duration) -> Result<(), SendError<S>> {
+        if !self.alive.is_open() {
+            return Err(SendError(value));
+        }
+        let deadline = match crate::deadline(delay) {
+            Some(deadline) => deadline,
+            None => return Ok(()),
+        };
+
+        let sender = self.sender.clone_uncounted();
+        #[cfg(feature = "tracing")]
+        let name = self.name.clone();
+        let job = move || {
+            if sender.try_send(value).is_err() {
+                #[cfg(feature = "tracing")]
+                tracing::warn!(channel = name.as_deref(), "discarded a scheduled send");
+            }
+        };
+        push(deadline, Some(self.alive.schedule()), Box::new(job));
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod examples {
+    use std::time::{Duration, Instant};
+
+    use crate::{RecvError, SendError, TryRecvError};
+
+    #[test]
+    fn test_send_after_delay() {
+        let (left, right) = crate::channel::<u8, ()>();
+
+        let start = Instant::now();
+        left.send_after(2, Duration::from_millis(80)).unwrap();
+        left.send_after(1, Duration::from_millis(50)).unwrap();
+
+        assert_eq!(right.try_recv(), Err(TryRecvError::Empty));
+        assert_eq!(right.recv(), Ok(1));
+        assert!(start.elapsed() >= Duration::from_millis(50));
+        assert_eq!(right.recv(), Ok(2));
+    }
+
+    #[test]
+    fn test_dropped_side_cancels() {
+        let (left, right) = crate::channel::<u8, ()>();
+
+        left.send_after(1, Duration::from_secs(3600)).unwrap();
+        assert_eq!(left.sender_count(), 1);
+
+        // The other side observes the hang up at once, not when the delay elapses.
+        let start = Instant::now();
+        drop(left);
+        assert_eq!(right.recv(), Err(RecvError));
+        assert!(start.elapsed() < Duration::from_secs(1));
+
+        let (mut left, right) = crate::channel::<u8, ()>();
+        left.send_after(1, Duration::from_secs(3600)).unwrap();
+        left.close_sender();
+        assert_eq!(right.recv(), Err(RecvError));
+        assert_eq!(left.send_after(2, Duration::ZERO), Err(SendError(2)));
+    }
+
+    #[test]
+    fn test_full_buffer_discards_without_blocking() {
+        let (left, right) = crate::sync_channel::<u8, ()>(1);
+        left.send(1).unwrap();
+
+        // The first send finds the buffer full, and must not hold up the second.
+        left.send_after(2, Duration::from_millis(10)).unwrap();
+        let (other, other_right) = crate::channel::<u8, ()>();
+        other.send_after(3, Duration::from_millis(20)).unwrap();
+        assert_eq!(other_right.recv_timeout(Duration::from_secs(1)), Ok(3));
+
+        assert_eq!(right.recv(), Ok(1));
+        assert_eq!(right.try_recv(), Err(TryRecvError::Empty));
+    }
+}
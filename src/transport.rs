@@ -0,0 +1,1829 @@
+//! Bridging a [`Channel`] over a byte stream, enabled with the `serde` feature.
+//!
+//! Each message is serialized by a [`Codec`], the [`DefaultCodec`] unless another is
+//! configured with [`Transport::with_codec`], and written as one frame: a 4 byte big endian
+//! length followed by that many bytes of payload, unless the codec delimits frames
+//! some other way. Frames are read in full regardless
+//! of how the stream splits them, so a message may be larger than any single read.
+//! With [`Transport::byte_batch`], a frame instead holds a batch of such frames, and
+//! with `Transport::compressed`, the payload of every frame is compressed. With
+//! `Transport::checksummed`, every frame ends with a CRC32 of its length and the rest
+//! of its payload.
+//!
+//! A frame longer than [`Transport::max_frame_len`], 16 MiB by default, is rejected
+//! before it is read, so that a corrupt or hostile length cannot exhaust memory.
+
+use std::convert::TryFrom;
+use std::error;
+use std::io::{self, BufRead, BufReader, Read, Write};
+use std::net::{Shutdown, TcpStream};
+#[cfg(unix)]
+use std::os::unix::net::UnixStream;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::thread;
+use std::time::Duration;
+
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+
+use crate::{backend, Channel, OverflowPolicy, RecvHalf, RecvTimeoutError, SendHalf};
+
+/// Turns messages into the bytes of a frame and back, for a [`Transport`].
+///
+/// Implement this for formats other than [`JsonCodec`] and `BincodeCodec`. Decoding
+/// fails on frames that were not encoded by the same codec, which the transport
+/// treats like the stream failing: it stops reading, and the local side observes the
+/// other side hanging up. Messages that fail to encode are not sent.
+///
+/// A codec also decides how frames are delimited on the stream, by length prefix
+/// unless it overrides [`write_frame`] and [`read_frame`].
+///
+/// [`write_frame`]: Self::write_frame
+/// [`read_frame`]: Self::read_frame
+pub trait Codec: Clone + Send + 'static {
+    /// The error returned when a message cannot be encoded or decoded.
+    type Error: error::Error + Send + Sync + 'static;
+
+    /// Whether [`write_frame`] can delimit a payload of any bytes, as the default
+    /// length prefix does. Frames of [`Transport::byte_batch`] and of the `compression`
+    /// and `checksum` features are binary, so they are refused, with a panic, for a
+    /// codec that sets this to `false`.
+    ///
+    /// [`write_frame`]: Self::write_frame
+    const BINARY_FRAMES: bool = true;
+
+    /// Encodes one message as the payload of a frame.
+    fn encode<T: Serialize>(&self, message: &T) -> Result<Vec<u8>, Self::Error>;
+
+    /// Decodes one message from the payload of a frame.
+    fn decode<T: DeserializeOwned>(&self, payload: &[u8]) -> Result<T, Self::Error>;
+
+    /// Writes the payload of one frame to `writer`, by default after its length as 4
+    /// big endian bytes.
+    fn write_frame<Wr: Write>(&self, writer: &mut Wr, payload: &[u8]) -> io::Result<()> {
+        write_frame(writer, payload)
+    }
+
+    /// Reads the payload of the next frame written by [`write_frame`], returning `None`
+    /// if the stream ends cleanly before the frame starts.
+    ///
+    /// A frame longer than `max_len` bytes must be rejected with
+    /// [`io::ErrorKind::InvalidData`] without reading, or allocating room for, all of it.
+    ///
+    /// [`write_frame`]: Self::write_frame
+    fn read_frame<Rd: BufRead>(
+        &self,
+        reader: &mut Rd,
+        max_len: usize,
+    ) -> io::Result<Option<Vec<u8>>> {
+        read_frame(reader, max_len)
+    }
+}
+
+/// Encodes messages as JSON with `serde_json`, the [`DefaultCodec`] unless the `bincode`
+/// feature is enabled.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct JsonCodec;
+
+impl Codec for JsonCodec {
+    type Error = serde_json::Error;
+
+    fn encode<T: Serialize>(&self, message: &T) -> Result<Vec<u8>, serde_json::Error> {
+        serde_json::to_vec(message)
+    }
+
+    fn decode<T: DeserializeOwned>(&self, payload: &[u8]) -> Result<T, serde_json::Error> {
+        serde_json::from_slice(payload)
+    }
+}
+
+/// Encodes messages with `bincode`, the [`DefaultCodec`] with the `bincode` feature.
+///
+/// Frames are compact and fast to decode, but not self describing: both ends must
+/// agree on the types of the messages, as a frame decoded as another type either
+/// fails to decode or yields garbage. A frame that fails to decode disconnects the
+/// channel, as with any codec.
+///
+/// # Examples
+///
+/// ```
+/// use std::net::{TcpListener, TcpStream};
+/// use bichannel::transport::{BincodeCodec, Transport};
+///
+/// let listener = TcpListener::bind("127.0.0.1:0")?;
+/// let client = TcpStream::connect(listener.local_addr()?)?;
+/// let (server, _) = listener.accept()?;
+///
+/// let transport = Transport::new().with_codec(BincodeCodec);
+/// let client = transport.serve::<(u8, String), (), _>(client);
+/// let server = transport.serve::<(), (u8, String), _>(server);
+///
+/// client.send((1, "one".to_string())).unwrap();
+/// assert_eq!(server.recv().unwrap(), (1, "one".to_string()));
+/// # Ok::<(), std::io::Error>(())
+/// ```
+#[cfg(feature = "bincode")]
+#[derive(Debug, Clone, Copy, Default)]
+pub struct BincodeCodec;
+
+#[cfg(feature = "bincode")]
+impl Codec for BincodeCodec {
+    type Error = bincode::Error;
+
+    fn encode<T: Serialize>(&self, message: &T) -> Result<Vec<u8>, bincode::Error> {
+        bincode::serialize(message)
+    }
+
+    fn decode<T: DeserializeOwned>(&self, payload: &[u8]) -> Result<T, bincode::Error> {
+        bincode::deserialize(payload)
+    }
+}
+
+/// The codec used by [`serve`] and [`Transport::new`]: [`JsonCodec`], or
+/// `BincodeCodec` with the `bincode` feature.
+///
+/// Both ends of a stream must use the same codec, so a process built with the
+/// `bincode` feature cannot talk to one built without it through the defaults.
+#[cfg(not(feature = "bincode"))]
+pub type DefaultCodec = JsonCodec;
+
+/// The codec used by [`serve`] and [`Transport::new`]: [`JsonCodec`], or
+/// [`BincodeCodec`] with the `bincode` feature.
+///
+/// Both ends of a stream must use the same codec, so a process built with the
+/// `bincode` feature cannot talk to one built without it through the defaults.
+#[cfg(feature = "bincode")]
+pub type DefaultCodec = BincodeCodec;
+
+/// Encodes messages as newline delimited JSON, for interop with tools that speak
+/// JSON lines. Enabled with the `json` feature.
+///
+/// Every frame is one JSON value on a line of its own, ended by `\n`, with no length
+/// prefix. Newlines within strings are escaped by JSON, so only the end of a line
+/// ends a frame, and a line split across reads is reassembled before it is decoded.
+/// Options that turn frames into binary, [`Transport::byte_batch`] and those of the
+/// `compression` and `checksum` features, cannot be combined with it, as their frames
+/// are not lines of JSON: configuring a transport with both panics. A frame
+/// containing a newline is never written, as it would be split in two when read:
+/// writing stops instead, and later sends fail.
+///
+/// # Examples
+///
+/// ```
+/// use std::io::{BufRead, BufReader, Write};
+/// use std::net::{TcpListener, TcpStream};
+/// use bichannel::transport::{JsonLinesCodec, Transport};
+///
+/// let listener = TcpListener::bind("127.0.0.1:0")?;
+/// let mut tool = TcpStream::connect(listener.local_addr()?)?;
+/// let (server, _) = listener.accept()?;
+///
+/// let transport = Transport::new().with_codec(JsonLinesCodec);
+/// let server = transport.serve::<String, Vec<u32>, _>(server);
+///
+/// tool.write_all(b"[1,2]\n[3]\n")?;
+/// assert_eq!(server.recv().unwrap(), [1, 2]);
+/// assert_eq!(server.recv().unwrap(), [3]);
+///
+/// server.send("two\nlines".to_string()).unwrap();
+/// let mut line = String::new();
+/// BufReader::new(tool).read_line(&mut line)?;
+/// assert_eq!(line, "\"two\\nlines\"\n");
+/// # Ok::<(), std::io::Error>(())
+/// ```
+#[cfg(feature = "json")]
+#[derive(Debug, Clone, Copy, Default)]
+pub struct JsonLinesCodec;
+
+#[cfg(feature = "json")]
+impl Codec for JsonLinesCodec {
+    type Error = serde_json::Error;
+
+    const BINARY_FRAMES: bool = false;
+
+    fn encode<T: Serialize>(&self, message: &T) -> Result<Vec<u8>, serde_json::Error> {
+        // Compact JSON never contains a raw newline, which is escaped within strings.
+        serde_json::to_vec(message)
+    }
+
+    fn decode<T: DeserializeOwned>(&self, payload: &[u8]) -> Result<T, serde_json::Error> {
+        serde_json::from_slice(payload)
+    }
+
+    fn write_frame<Wr: Write>(&self, writer: &mut Wr, payload: &[u8]) -> io::Result<()> {
+        if payload.contains(&b'\n') {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "frame contains a newline",
+            ));
+        }
+
+        writer.write_all(payload)?;
+        writer.write_all(b"\n")?;
+        writer.flush()
+    }
+
+    fn read_frame<Rd: BufRead>(
+        &self,
+        reader: &mut Rd,
+        max_len: usize,
+    ) -> io::Result<Option<Vec<u8>>> {
+        // Reading one byte past the limit, for the newline, tells a line that is too
+        // long apart from one that fits exactly.
+        let limit = u64::try_from(max_len).map_or(u64::MAX, |len| len.saturating_add(1));
+        let mut line = Vec::new();
+        if reader.by_ref().take(limit).read_until(b'\n', &mut line)? == 0 {
+            return Ok(None);
+        }
+        if line.len() as u64 == limit && line.last() != Some(&b'\n') {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                "frame too large",
+            ));
+        }
+        if line.pop() != Some(b'\n') {
+            return Err(io::Error::new(
+                io::ErrorKind::UnexpectedEof,
+                "stream ended within a line",
+            ));
+        }
+        Ok(Some(line))
+    }
+}
+
+/// Connects a local channel to a stream, returning the local side.
+///
+/// Two background threads bridge the stream: one serializes every message sent on
+/// the returned side and writes it to the stream, the other reads messages from the
+/// stream and delivers them to the returned side. The stream is shared between the
+/// threads, so it is read and written through shared references, as `TcpStream` and
+/// `UnixStream` allow.
+///
+/// Once the stream reaches its end or fails, or a message cannot be deserialized,
+/// `recv` on the returned side fails after draining the messages already read. Once
+/// the returned side is dropped, the writing thread stops. Writing to a failed stream
+/// makes later sends fail.
+///
+/// # Examples
+///
+/// ```
+/// use std::net::{TcpListener, TcpStream};
+///
+/// let listener = TcpListener::bind("127.0.0.1:0")?;
+/// let client = TcpStream::connect(listener.local_addr()?)?;
+/// let (server, _) = listener.accept()?;
+///
+/// let client = bichannel::transport::serve::<String, usize, _>(client);
+/// let server = bichannel::transport::serve::<usize, String, _>(server);
+///
+/// client.send("hello".to_string()).unwrap();
+/// let request = server.recv().unwrap();
+/// server.send(request.len()).unwrap();
+///
+/// assert_eq!(client.recv(), Ok(5));
+/// # Ok::<(), std::io::Error>(())
+/// ```
+pub fn serve<T, U, S>(stream: S) -> Channel<T, U>
+where
+    T: Serialize + Send + 'static,
+    U: DeserializeOwned + Send + 'static,
+    S: Send + Sync + 'static,
+    for<'a> &'a S: Read + Write,
+{
+    Transport::new().serve(stream)
+}
+
+/// How long a batch waits for more messages before it is written, by default.
+const DEFAULT_FLUSH_INTERVAL: Duration = Duration::from_millis(1);
+/// The longest frame read, by default.
+const DEFAULT_MAX_FRAME_LEN: usize = 16 << 20;
+
+/// Configures how a channel is bridged over a stream, as an alternative to the
+/// [`serve`] shortcut.
+///
+/// The options change what is written to the stream, so both ends of a stream must
+/// be configured alike. By default, every message is written as a frame of its own.
+///
+/// # Examples
+///
+/// ```
+/// use std::net::{TcpListener, TcpStream};
+/// use bichannel::transport::Transport;
+///
+/// let listener = TcpListener::bind("127.0.0.1:0")?;
+/// let client = TcpStream::connect(listener.local_addr()?)?;
+/// let (server, _) = listener.accept()?;
+///
+/// let transport = Transport::new().byte_batch(4096);
+/// let client = transport.serve::<u32, (), _>(client);
+/// let server = transport.serve::<(), u32, _>(server);
+///
+/// client.send_all(0..100).unwrap();
+/// assert!(server.iter().take(100).eq(0..100));
+/// # Ok::<(), std::io::Error>(())
+/// ```
+#[derive(Debug, Clone)]
+pub struct Transport<C = DefaultCodec> {
+    codec: C,
+    batch: Option<usize>,
+    flush_interval: Duration,
+    max_frame_len: usize,
+    #[cfg(feature = "compression")]
+    compression: Option<u32>,
+    #[cfg(feature = "checksum")]
+    checksummed: bool,
+}
+
+impl Transport {
+    /// Creates a transport writing every message as a frame of its own, serialized
+    /// with the [`DefaultCodec`].
+    pub fn new() -> Self {
+        Transport {
+            codec: DefaultCodec::default(),
+            batch: None,
+            flush_interval: DEFAULT_FLUSH_INTERVAL,
+            max_frame_len: DEFAULT_MAX_FRAME_LEN,
+            #[cfg(feature = "compression")]
+            compression: None,
+            #[cfg(feature = "checksum")]
+            checksummed: false,
+        }
+    }
+}
+
+impl<C: Codec> Transport<C> {
+    /// Serializes messages with `codec` instead of the current one.
+    ///
+    /// # Panics
+    ///
+    /// Panics if an option writing binary frames is enabled and `codec` cannot
+    /// delimit them, as [`Codec::BINARY_FRAMES`] tells.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::net::{TcpListener, TcpStream};
+    /// use bichannel::transport::{Codec, Transport};
+    /// use serde::{de::DeserializeOwned, Serialize};
+    ///
+    /// /// JSON, pretty printed for anyone watching the wire.
+    /// #[derive(Clone)]
+    /// struct Pretty;
+    ///
+    /// impl Codec for Pretty {
+    ///     type Error = serde_json::Error;
+    ///
+    ///     fn encode<T: Serialize>(&self, message: &T) -> Result<Vec<u8>, Self::Error> {
+    ///         serde_json::to_vec_pretty(message)
+    ///     }
+    ///
+    ///     fn decode<T: DeserializeOwned>(&self, payload: &[u8]) -> Result<T, Self::Error> {
+    ///         serde_json::from_slice(payload)
+    ///     }
+    /// }
+    ///
+    /// let listener = TcpListener::bind("127.0.0.1:0")?;
+    /// let client = TcpStream::connect(listener.local_addr()?)?;
+    /// let (server, _) = listener.accept()?;
+    ///
+    /// let transport = Transport::new().with_codec(Pretty);
+    /// let client = transport.serve::<Vec<u8>, (), _>(client);
+    /// let server = transport.serve::<(), Vec<u8>, _>(server);
+    ///
+    /// client.send(vec![1, 2, 3]).unwrap();
+    /// assert_eq!(server.recv().unwrap(), [1, 2, 3]);
+    /// # Ok::<(), std::io::Error>(())
+    /// ```
+    pub fn with_codec<D: Codec>(self, codec: D) -> Transport<D> {
+        if self.binary_frames() {
+            assert_binary_frames::<D>();
+        }
+        Transport {
+            codec,
+            batch: self.batch,
+            flush_interval: self.flush_interval,
+            max_frame_len: self.max_frame_len,
+            #[cfg(feature = "compression")]
+            compression: self.compression,
+            #[cfg(feature = "checksum")]
+            checksummed: self.checksummed,
+        }
+    }
+
+    /// Coalesces messages into batches of about `budget` serialized bytes, each
+    /// written as one frame, to cut the overhead of writing many small messages.
+    ///
+    /// A batch is written once it holds at least `budget` bytes, or once the
+    /// [`flush_interval`] has elapsed since its first message was sent, whichever
+    /// comes first. A message larger than the budget is written as a batch of its own.
+    /// Batches are split back into messages as they are read, so batching is not
+    /// visible to either side's `send` and `recv`.
+    ///
+    /// [`flush_interval`]: Self::flush_interval
+    ///
+    /// # Panics
+    ///
+    /// Panics if the codec cannot delimit binary frames, as [`Codec::BINARY_FRAMES`]
+    /// tells.
+    pub fn byte_batch(mut self, budget: usize) -> Self {
+        assert_binary_frames::<C>();
+        self.batch = Some(budget);
+        self
+    }
+
+    /// Sets how long a batch waits for more messages before it is written, when
+    /// [`byte_batch`](Self::byte_batch) is enabled. Defaults to 1ms.
+    pub fn flush_interval(mut self, interval: Duration) -> Self {
+        self.flush_interval = interval;
+        self
+    }
+
+    /// Sets the longest frame read from the stream, in bytes. Defaults to 16 MiB.
+    ///
+    /// A longer frame is rejected as soon as its length is read, without allocating
+    /// room for it, which stops the transport reading as a corrupt frame would: the
+    /// local side observes the other side hanging up. The limit applies to frames as
+    /// written, so to whole batches with [`byte_batch`](Self::byte_batch), and counts
+    /// any compression and checksum. Frames written are not limited.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::net::{TcpListener, TcpStream};
+    /// use bichannel::transport::Transport;
+    ///
+    /// let listener = TcpListener::bind("127.0.0.1:0")?;
+    /// let client = TcpStream::connect(listener.local_addr()?)?;
+    /// let (server, _) = listener.accept()?;
+    ///
+    /// let client = Transport::new().serve::<String, (), _>(client);
+    /// let server = Transport::new().max_frame_len(64).serve::<(), String, _>(server);
+    ///
+    /// client.send("short".to_string()).unwrap();
+    /// client.send("long".repeat(100)).unwrap();
+    /// assert_eq!(server.recv().unwrap(), "short");
+    /// assert!(server.recv().is_err());
+    /// # Ok::<(), std::io::Error>(())
+    /// ```
+    pub fn max_frame_len(mut self, len: usize) -> Self {
+        self.max_frame_len = len;
+        self
+    }
+
+    /// Compresses everything written to the stream with deflate at `level`, from 0 for
+    /// none to 9 for the smallest output, and decompresses everything read. Enabled
+    /// with the `compression` feature.
+    ///
+    /// Frames keep their length prefix, and their payloads are compressed as one
+    /// stream, so that messages compress against those before them. The stream is
+    /// flushed after every frame, so that each message can be decompressed, exactly
+    /// as it was serialized, as soon as it arrives. Levels above 9 are treated as 9.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the codec cannot delimit binary frames, as [`Codec::BINARY_FRAMES`]
+    /// tells.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::net::{TcpListener, TcpStream};
+    /// use bichannel::transport::Transport;
+    ///
+    /// let listener = TcpListener::bind("127.0.0.1:0")?;
+    /// let client = TcpStream::connect(listener.local_addr()?)?;
+    /// let (server, _) = listener.accept()?;
+    ///
+    /// let transport = Transport::new().compressed(6);
+    /// let client = transport.serve::<String, (), _>(client);
+    /// let server = transport.serve::<(), String, _>(server);
+    ///
+    /// client.send("hello ".repeat(1000)).unwrap();
+    /// assert_eq!(server.recv().unwrap(), "hello ".repeat(1000));
+    /// # Ok::<(), std::io::Error>(())
+    /// ```
+    #[cfg(feature = "compression")]
+    pub fn compressed(mut self, level: u32) -> Self {
+        assert_binary_frames::<C>();
+        self.compression = Some(level);
+        self
+    }
+
+    /// Appends a CRC32 checksum to every frame written, and verifies it on every frame
+    /// read. Enabled with the `checksum` feature.
+    ///
+    /// A frame that fails verification, having been corrupted on the way, stops the
+    /// transport reading with a [`FrameIntegrityError`], and the local side observes
+    /// the other side hanging up, instead of receiving a corrupt message. The
+    /// checksum covers the frame as written, after any compression, along with its
+    /// length, so a corrupt length prefix is caught too. A length corrupted upwards
+    /// may instead exceed the [`max_frame_len`](Self::max_frame_len), which is
+    /// rejected before any of the frame is read.
+    ///
+    /// [`FrameIntegrityError`]: crate::FrameIntegrityError
+    ///
+    /// # Panics
+    ///
+    /// Panics if the codec cannot delimit binary frames, as [`Codec::BINARY_FRAMES`]
+    /// tells.
+    #[cfg(feature = "checksum")]
+    pub fn checksummed(mut self) -> Self {
+        assert_binary_frames::<C>();
+        self.checksummed = true;
+        self
+    }
+
+    /// Connects a local channel to a stream, returning the local side. See [`serve`]
+    /// for how messages are bridged.
+    pub fn serve<T, U, S>(&self, stream: S) -> Channel<T, U>
+    where
+        T: Serialize + Send + 'static,
+        U: DeserializeOwned + Send + 'static,
+        S: Send + Sync + 'static,
+        for<'a> &'a S: Read + Write,
+    {
+        bridge(self.clone(), stream, |_| ())
+    }
+}
+
+impl<C> Transport<C> {
+    /// Returns `true` if any option writing binary frames is enabled.
+    fn binary_frames(&self) -> bool {
+        #[cfg(feature = "compression")]
+        if self.compression.is_some() {
+            return true;
+        }
+        #[cfg(feature = "checksum")]
+        if self.checksummed {
+            return true;
+        }
+        self.batch.is_some()
+    }
+}
+
+/// Panics unless `C` can write the binary frames of the option being enabled.
+#[track_caller]
+fn assert_binary_frames<C: Codec>() {
+    assert!(
+        C::BINARY_FRAMES,
+        "the codec cannot delimit binary frames, so byte batches, compression and checksums cannot be used with it"
+    );
+}
+
+impl Default for Transport {
+    fn default() -> Self {
+        Transport::new()
+    }
+}
+
+/// Spawns the threads that bridge `stream`, calling `close` once the writing thread
+/// stops.
+fn bridge<C, T, U, S, F>(transport: Transport<C>, stream: S, close: F) -> Channel<T, U>
+where
+    C: Codec,
+    T: Serialize + Send + 'static,
+    U: DeserializeOwned + Send + 'static,
+    S: Send + Sync + 'static,
+    for<'a> &'a S: Read + Write,
+    F: FnOnce(&S) + Send + 'static,
+{
+    let (local, bridge) = crate::channel();
+    let (incoming, outgoing) = bridge.split();
+
+    let reader = Arc::new(stream);
+    let writer = reader.clone();
+    let reading = transport.clone();
+
+    thread::spawn(move || reading.read(&*reader, incoming));
+    thread::spawn(move || {
+        transport.write(&*writer, outgoing);
+        close(&writer);
+    });
+
+    local
+}
+
+impl<C: Codec> Transport<C> {
+    /// Delivers the messages read from `reader`, undoing the configured options.
+    fn read<U: DeserializeOwned, Rd: Read>(&self, reader: Rd, incoming: SendHalf<U>) {
+        read_messages(
+            FrameReader::new(self, reader),
+            incoming,
+            self.batch.is_some(),
+        )
+    }
+
+    /// Writes the messages sent to `writer`, applying the configured options.
+    fn write<T: Serialize, Wr: Write>(&self, writer: Wr, outgoing: RecvHalf<T>) {
+        let frames = FrameWriter::new(self, writer);
+        match self.batch {
+            Some(budget) => write_batches(frames, outgoing, budget, self.flush_interval),
+            None => write_messages(frames, outgoing),
+        }
+    }
+}
+
+/// Writes frames to a stream, compressing and checksumming them if configured.
+struct FrameWriter<C, Wr> {
+    codec: C,
+    writer: Wr,
+    #[cfg(feature = "checksum")]
+    checksummed: bool,
+    /// Compresses every payload as part of one stream, so that each compresses
+    /// against those before it, collecting the output to be framed.
+    #[cfg(feature = "compression")]
+    compressor: Option<flate2::write::DeflateEncoder<Vec<u8>>>,
+}
+
+impl<C: Codec, Wr: Write> FrameWriter<C, Wr> {
+    fn new(transport: &Transport<C>, writer: Wr) -> Self {
+        FrameWriter {
+            codec: transport.codec.clone(),
+            writer,
+            #[cfg(feature = "checksum")]
+            checksummed: transport.checksummed,
+            #[cfg(feature = "compression")]
+            compressor: transport.compression.map(|level| {
+                let level = flate2::Compression::new(level.min(9));
+                flate2::write::DeflateEncoder::new(Vec::new(), level)
+            }),
+        }
+    }
+
+    fn write(&mut self, payload: &[u8]) -> io::Result<()> {
+        #[cfg(feature = "compression")]
+        if let Some(compressor) = &mut self.compressor {
+            // Flushing ends the compressed output on a byte boundary, so that it can be
+            // decompressed in full without waiting for the next frame.
+            compressor.write_all(payload)?;
+            compressor.flush()?;
+            let compressed = std::mem::take(compressor.get_mut());
+            return self.write_checked(&compressed);
+        }
+
+        self.write_checked(payload)
+    }
+
+    fn write_checked(&mut self, frame: &[u8]) -> io::Result<()> {
+        #[cfg(feature = "checksum")]
+        if self.checksummed {
+            let mut checked = Vec::with_capacity(frame.len() + 4);
+            checked.extend_from_slice(frame);
+            checked.extend_from_slice(&checksum(frame).to_be_bytes());
+            return self.codec.write_frame(&mut self.writer, &checked);
+        }
+
+        self.codec.write_frame(&mut self.writer, frame)
+    }
+}
+
+/// Reads frames from a stream, verifying and decompressing them if configured.
+struct FrameReader<C, Rd> {
+    codec: C,
+    reader: BufReader<Rd>,
+    max_len: usize,
+    #[cfg(feature = "checksum")]
+    checksummed: bool,
+    #[cfg(feature = "compression")]
+    decompressor: Option<flate2::write::DeflateDecoder<Vec<u8>>>,
+}
+
+impl<C: Codec, Rd: Read> FrameReader<C, Rd> {
+    fn new(transport: &Transport<C>, reader: Rd) -> Self {
+        FrameReader {
+            codec: transport.codec.clone(),
+            reader: BufReader::new(reader),
+            max_len: transport.max_frame_len,
+            #[cfg(feature = "checksum")]
+            checksummed: transport.checksummed,
+            #[cfg(feature = "compression")]
+            decompressor: transport
+                .compression
+                .map(|_| flate2::write::DeflateDecoder::new(Vec::new())),
+        }
+    }
+
+    /// Reads the payload of the next frame, returning `None` once the stream ends.
+    fn read(&mut self) -> io::Result<Option<Vec<u8>>> {
+        let frame = match self.codec.read_frame(&mut self.reader, self.max_len)? {
+            Some(frame) => self.verify(frame)?,
+            None => return Ok(None),
+        };
+
+        #[cfg(feature = "compression")]
+        if let Some(decompressor) = &mut self.decompressor {
+            decompressor.write_all(&frame)?;
+            decompressor.flush()?;
+            return Ok(Some(std::mem::take(decompressor.get_mut())));
+        }
+
+        Ok(Some(frame))
+    }
+
+    /// Strips the checksum from a frame, failing if it does not match.
+    #[cfg(feature = "checksum")]
+    fn verify(&self, mut frame: Vec<u8>) -> io::Result<Vec<u8>> {
+        if !self.checksummed {
+            return Ok(frame);
+        }
+
+        let split = frame.len().checked_sub(4).ok_or_else(|| {
+            io::Error::new(io::ErrorKind::InvalidData, "frame too short for a checksum")
+        })?;
+        let mut expected = [0; 4];
+        expected.copy_from_slice(&frame[split..]);
+        frame.truncate(split);
+
+        let expected = u32::from_be_bytes(expected);
+        let found = checksum(&frame);
+        if expected != found {
+            let err = crate::FrameIntegrityError { expected, found };
+            return Err(io::Error::new(io::ErrorKind::InvalidData, err));
+        }
+        Ok(frame)
+    }
+
+    #[cfg(not(feature = "checksum"))]
+    fn verify(&self, frame: Vec<u8>) -> io::Result<Vec<u8>> {
+        Ok(frame)
+    }
+}
+
+impl<T, U> Channel<T, U>
+where
+    T: Serialize + Send + 'static,
+    U: DeserializeOwned + Send + 'static,
+{
+    /// Connects a local channel to a TCP connection, returning the local side. See
+    /// [`serve`] for how messages are bridged.
+    ///
+    /// Once the returned side is dropped, or writing to the connection fails, the
+    /// connection is shut down in both directions, which stops the reading thread and
+    /// lets the peer observe the disconnect. If the peer closes the connection, `recv`
+    /// fails once the messages already read have been received.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::net::{TcpListener, TcpStream};
+    /// use bichannel::Channel;
+    ///
+    /// let listener = TcpListener::bind("127.0.0.1:0")?;
+    /// let client = Channel::<u32, u32>::over_tcp(TcpStream::connect(listener.local_addr()?)?)?;
+    /// let server = Channel::<u32, u32>::over_tcp(listener.accept()?.0)?;
+    ///
+    /// client.send(1).unwrap();
+    /// assert_eq!(server.recv(), Ok(1));
+    ///
+    /// drop(client);
+    /// assert!(server.recv().is_err());
+    /// # Ok::<(), std::io::Error>(())
+    /// ```
+    pub fn over_tcp(stream: TcpStream) -> io::Result<Self> {
+        // Messages are written a frame at a time, so waiting to coalesce them only adds
+        // latency.
+        stream.set_nodelay(true)?;
+
+        Ok(bridge(Transport::new(), stream, |stream| {
+            let _ = stream.shutdown(Shutdown::Both);
+        }))
+    }
+}
+
+#[cfg(unix)]
+impl<T, U> Channel<T, U>
+where
+    T: Serialize + Send + 'static,
+    U: DeserializeOwned + Send + 'static,
+{
+    /// Connects a local channel to a Unix domain socket, returning the local side. Only
+    /// available on Unix platforms.
+    ///
+    /// This behaves like [`over_tcp`](Channel::over_tcp): dropping the returned side
+    /// shuts the socket down, and the peer closing it makes `recv` fail once the
+    /// messages already read have been received.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::os::unix::net::UnixStream;
+    /// use bichannel::Channel;
+    ///
+    /// let (a, b) = UnixStream::pair()?;
+    /// let a = Channel::<String, String>::over_unix(a)?;
+    /// let b = Channel::<String, String>::over_unix(b)?;
+    ///
+    /// a.send("hello".to_string()).unwrap();
+    /// assert_eq!(b.recv().unwrap(), "hello");
+    /// # Ok::<(), std::io::Error>(())
+    /// ```
+    pub fn over_unix(stream: UnixStream) -> io::Result<Self> {
+        Ok(bridge(Transport::new(), stream, |stream| {
+            let _ = stream.shutdown(Shutdown::Both);
+        }))
+    }
+}
+
+/// The first delay before retrying a failed connection attempt, doubled on every
+/// further failure.
+const MIN_BACKOFF: Duration = Duration::from_millis(10);
+/// The longest delay between connection attempts.
+const MAX_BACKOFF: Duration = Duration::from_secs(1);
+/// How often an idle writer checks whether the connection has broken.
+const POLL_INTERVAL: Duration = Duration::from_millis(10);
+
+/// Connects a local channel to a TCP connection that is re-established whenever it
+/// drops, returning the local side. See [`serve`] for how messages are bridged.
+///
+/// `connect` is called to establish the connection, and again whenever it ends or
+/// fails, waiting between failed attempts with exponential backoff. A drop is never
+/// surfaced: while disconnected, messages sent on the returned side are buffered
+/// without bound and written once a connection is back, and `recv` waits for the
+/// next connection to deliver. Use [`reconnecting_bounded`] to bound the buffer.
+/// Messages already written to a connection as it drops may be lost, along with
+/// those the peer sent but did not get to write. Dropping the returned side closes
+/// the connection and stops reconnecting.
+///
+/// # Examples
+///
+/// ```
+/// use std::net::{TcpListener, TcpStream};
+/// use bichannel::Channel;
+///
+/// let listener = TcpListener::bind("127.0.0.1:0")?;
+/// let addr = listener.local_addr()?;
+///
+/// let client = bichannel::transport::reconnecting::<u32, u32, _>(move || {
+///     TcpStream::connect(addr)
+/// });
+/// let server = Channel::<u32, u32>::over_tcp(listener.accept()?.0)?;
+///
+/// client.send(1).unwrap();
+/// assert_eq!(server.recv(), Ok(1));
+/// # Ok::<(), std::io::Error>(())
+/// ```
+pub fn reconnecting<T, U, F>(connect: F) -> Channel<T, U>
+where
+    T: Serialize + Send + 'static,
+    U: DeserializeOwned + Send + 'static,
+    F: FnMut() -> io::Result<TcpStream> + Send + 'static,
+{
+    let (local, bridge) = crate::channel();
+    let (incoming, outgoing) = bridge.split();
+
+    thread::spawn(move || reconnect(connect, incoming, outgoing));
+
+    local
+}
+
+/// Connects a local channel to a TCP connection that is re-established whenever it
+/// drops, like [`reconnecting`], but buffering at most `bound` messages sent on the
+/// returned side while they wait for a connection.
+///
+/// Once the buffer is full, sending handles the message according to `policy`, as
+/// for [`bounded_with`](crate::bounded_with): with [`OverflowPolicy::Block`], a send
+/// waits for a connection to take messages from the buffer. Messages received are
+/// buffered without bound, as they are read as fast as the local side receives them.
+///
+/// # Examples
+///
+/// ```
+/// use std::io;
+/// use bichannel::{OverflowPolicy, SendError};
+///
+/// // Never connects, so messages stay buffered.
+/// let client = bichannel::transport::reconnecting_bounded::<u32, u32, _>(
+///     || Err(io::ErrorKind::ConnectionRefused.into()),
+///     2,
+///     OverflowPolicy::Error,
+/// );
+///
+/// client.send(1).unwrap();
+/// client.send(2).unwrap();
+/// assert_eq!(client.send(3), Err(SendError(3)));
+/// ```
+pub fn reconnecting_bounded<T, U, F>(
+    connect: F,
+    bound: usize,
+    policy: OverflowPolicy,
+) -> Channel<T, U>
+where
+    T: Serialize + Send + 'static,
+    U: DeserializeOwned + Send + 'static,
+    F: FnMut() -> io::Result<TcpStream> + Send + 'static,
+{
+    let (local, bridge) = crate::pair(backend::bounded_with(bound, policy), backend::unbounded());
+    let (incoming, outgoing) = bridge.split();
+
+    thread::spawn(move || reconnect(connect, incoming, outgoing));
+
+    local
+}
+
+/// Keeps a connection up, bridging messages over it, until the local side hangs up.
+fn reconnect<T, U, F>(mut connect: F, incoming: SendHalf<U>, outgoing: RecvHalf<T>)
+where
+    T: Serialize,
+    U: DeserializeOwned + Send + 'static,
+    F: FnMut() -> io::Result<TcpStream>,
+{
+    let transport = Transport::new();
+    let mut backoff = MIN_BACKOFF;
+    // A frame whose write failed, to be written again on the next connection.
+    let mut unsent = None;
+
+    while !outgoing.is_disconnected() {
+        let stream = match connect() {
+            Ok(stream) => Arc::new(stream),
+            Err(_) => {
+                thread::sleep(backoff);
+                backoff = (backoff * 2).min(MAX_BACKOFF);
+                continue;
+            }
+        };
+        backoff = MIN_BACKOFF;
+        let _ = stream.set_nodelay(true);
+
+        let broken = Arc::new(AtomicBool::new(false));
+        let (reader, incoming, reader_broken) = (stream.clone(), incoming.clone(), broken.clone());
+        let reading = transport.clone();
+        thread::spawn(move || {
+            let frames = FrameReader::new(&reading, &*reader);
+            read_messages(frames, incoming, false);
+            reader_broken.store(true, Ordering::Relaxed);
+        });
+
+        while !broken.load(Ordering::Relaxed) {
+            let frame = match unsent.take() {
+                Some(frame) => frame,
+                None => match outgoing.recv_timeout(POLL_INTERVAL) {
+                    Ok(message) => match transport.codec.encode(&message) {
+                        Ok(frame) => frame,
+                        Err(_) => continue,
+                    },
+                    Err(RecvTimeoutError::Timeout) => continue,
+                    Err(RecvTimeoutError::Disconnected) => break,
+                },
+            };
+
+            if transport.codec.write_frame(&mut &*stream, &frame).is_err() {
+                unsent = Some(frame);
+                break;
+            }
+        }
+
+        let _ = stream.shutdown(Shutdown::Both);
+    }
+}
+
+/// Delivers messages read from `frames` until the stream ends or fails, or the local
+/// side hangs up. If `batched`, each frame holds a batch of frames, one per message.
+fn read_messages<C, U, Rd>(mut frames: FrameReader<C, Rd>, incoming: SendHalf<U>, batched: bool)
+where
+    C: Codec,
+    U: DeserializeOwned,
+    Rd: Read,
+{
+    while let Ok(Some(frame)) = frames.read() {
+        let delivered = if batched {
+            deliver_batch(&frames.codec, &frame, &incoming)
+        } else {
+            deliver(&frames.codec, &frame, &incoming)
+        };
+        if delivered.is_err() {
+            break;
+        }
+    }
+}
+
+/// Deserializes one message and delivers it, failing if it cannot be deserialized or
+/// the local side has hung up.
+fn deliver<C, U>(codec: &C, frame: &[u8], incoming: &SendHalf<U>) -> Result<(), ()>
+where
+    C: Codec,
+    U: DeserializeOwned,
+{
+    let message = codec.decode(frame).map_err(|_| ())?;
+    incoming.send(message).map_err(|_| ())
+}
+
+/// Delivers every message of a batch, in order, failing at the first that cannot be
+/// delivered, or if the batch is truncated.
+fn deliver_batch<C, U>(codec: &C, mut batch: &[u8], incoming: &SendHalf<U>) -> Result<(), ()>
+where
+    C: Codec,
+    U: DeserializeOwned,
+{
+    // No frame within a batch is longer than the batch itself.
+    let max_len = batch.len();
+    while let Some(frame) = read_frame(&mut batch, max_len).map_err(|_| ())? {
+        deliver(codec, &frame, incoming)?;
+    }
+    Ok(())
+}
+
+/// The CRC32 of a frame's length and bytes, so that a corrupt length, which would
+/// frame different bytes, is caught along with corrupt bytes.
+#[cfg(feature = "checksum")]
+fn checksum(frame: &[u8]) -> u32 {
+    let mut hasher = crc32fast::Hasher::new();
+    hasher.update(&(frame.len() as u64).to_be_bytes());
+    hasher.update(frame);
+    hasher.finalize()
+}
+
+/// Writes messages to `frames` until the local side hangs up, or writing fails.
+fn write_messages<C, T, Wr>(mut frames: FrameWriter<C, Wr>, outgoing: RecvHalf<T>)
+where
+    C: Codec,
+    T: Serialize,
+    Wr: Write,
+{
+    for message in outgoing.iter() {
+        let written = frames
+            .codec
+            .encode(&message)
+            .map_err(|err| io::Error::new(io::ErrorKind::InvalidInput, err))
+            .and_then(|frame| frames.write(&frame));
+        if written.is_err() {
+            break;
+        }
+    }
+}
+
+/// Writes messages to `frames` in batches of at least `budget` bytes, or whatever was
+/// sent within `flush_interval` of a batch's first message, until the local side
+/// hangs up, or writing fails.
+fn write_batches<C, T, Wr>(
+    mut frames: FrameWriter<C, Wr>,
+    outgoing: RecvHalf<T>,
+    budget: usize,
+    flush_interval: Duration,
+) where
+    C: Codec,
+    T: Serialize,
+    Wr: Write,
+{
+    let mut batch = Vec::new();
+    while let Ok(mut message) = outgoing.recv() {
+        let deadline = crate::deadline(flush_interval);
+        let mut disconnected = false;
+        loop {
+            let appended = frames
+                .codec
+                .encode(&message)
+                .map_err(|err| io::Error::new(io::ErrorKind::InvalidInput, err))
+                .and_then(|frame| write_frame(&mut batch, &frame));
+            if appended.is_err() {
+                return;
+            }
+            if batch.len() >= budget {
+                break;
+            }
+            match outgoing.receiver.recv_until(deadline) {
+                Ok(next) => message = next,
+                Err(RecvTimeoutError::Timeout) => break,
+                Err(RecvTimeoutError::Disconnected) => {
+                    disconnected = true;
+                    break;
+                }
+            }
+        }
+
+        if frames.write(&batch).is_err() || disconnected {
+            return;
+        }
+        batch.clear();
+    }
+}
+
+/// Writes `payload` as one length prefixed frame.
+pub(crate) fn write_frame<Wr: Write>(mut writer: Wr, payload: &[u8]) -> io::Result<()> {
+    let len = u32::try_from(payload.len())
+        .map_err(|_| io::Error::new(io::ErrorKind::InvalidInput, "frame too large"))?;
+
+    writer.write_all(&len.to_be_bytes())?;
+    writer.write_all(payload)?;
+    writer.flush()
+}
+
+/// Reads one length prefixed frame, returning `None` if the stream ends cleanly before
+/// the frame starts, and failing without reading it if it is longer than `max_len`.
+pub(crate) fn read_frame<Rd: Read>(mut reader: Rd, max_len: usize) -> io::Result<Option<Vec<u8>>> {
+    let mut len = [0; 4];
+    match reader.read_exact(&mut len) {
+        Ok(()) => (),
+        Err(err) if err.kind() == io::ErrorKind::UnexpectedEof => return Ok(None),
+        Err(err) => return Err(err),
+    }
+
+    let len = usize::try_from(u32::from_be_bytes(len)).unwrap_or(usize::MAX);
+    if len > max_len {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidData,
+            "frame too large",
+        ));
+    }
+
+    let mut payload = vec![0; len];
+    reader.read_exact(&mut payload)?;
+    Ok(Some(payload))
+}
+
+#[cfg(test)]
+mod examples {
+    use std::io::{Read, Write};
+    use std::net::{Shutdown, TcpListener, TcpStream};
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::Arc;
+
+    use serde::{Deserialize, Serialize};
+
+    #[derive(Serialize, Deserialize, Debug, PartialEq, Clone)]
+    struct Request {
+        id: u32,
+        body: String,
+    }
+
+    /// What has been written to a [`Counting`] stream.
+    #[derive(Default)]
+    struct Counts {
+        bytes: AtomicUsize,
+        /// The times the stream was flushed, which is once per frame.
+        flushes: AtomicUsize,
+    }
+
+    /// Wraps a stream to count what is written to it.
+    struct Counting {
+        stream: TcpStream,
+        counts: Arc<Counts>,
+    }
+
+    impl Counting {
+        fn new(stream: TcpStream) -> (Self, Arc<Counts>) {
+            let counts = Arc::new(Counts::default());
+            let counting = Counting {
+                stream,
+                counts: counts.clone(),
+            };
+            (counting, counts)
+        }
+    }
+
+    impl Read for &Counting {
+        fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+            (&self.stream).read(buf)
+        }
+    }
+
+    impl Write for &Counting {
+        fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+            let written = (&self.stream).write(buf)?;
+            self.counts.bytes.fetch_add(written, Ordering::Relaxed);
+            Ok(written)
+        }
+
+        fn flush(&mut self) -> std::io::Result<()> {
+            self.counts.flushes.fetch_add(1, Ordering::Relaxed);
+            (&self.stream).flush()
+        }
+    }
+
+    #[test]
+    fn test_loopback_over_tcp() {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let client = TcpStream::connect(listener.local_addr().unwrap()).unwrap();
+        let (server, _) = listener.accept().unwrap();
+        let server_stream = server.try_clone().unwrap();
+
+        let client = crate::transport::serve::<Request, u32, _>(client);
+        let server = crate::transport::serve::<u32, Request, _>(server);
+
+        let body = "x".repeat(1 << 20);
+        for id in 0..3 {
+            client
+                .send(Request {
+                    id,
+                    body: body.clone(),
+                })
+                .unwrap();
+        }
+
+        for id in 0..3 {
+            let request = server.recv().unwrap();
+            assert_eq!(
+                request,
+                Request {
+                    id,
+                    body: body.clone()
+                }
+            );
+            server.send(request.id).unwrap();
+        }
+        assert_eq!(client.iter().take(3).collect::<Vec<_>>(), [0, 1, 2]);
+
+        server_stream.shutdown(Shutdown::Both).unwrap();
+        assert_eq!(client.recv(), Err(crate::RecvError));
+    }
+
+    #[test]
+    fn test_over_tcp_propagates_disconnect() {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let connect = TcpStream::connect(listener.local_addr().unwrap()).unwrap();
+        let client = crate::Channel::<Request, u32>::over_tcp(connect).unwrap();
+        let server =
+            crate::Channel::<u32, Request>::over_tcp(listener.accept().unwrap().0).unwrap();
+
+        let request = Request {
+            id: 7,
+            body: "ping".to_string(),
+        };
+        client
+            .send(Request {
+                id: 7,
+                body: "ping".to_string(),
+            })
+            .unwrap();
+        assert_eq!(server.recv(), Ok(request));
+
+        server.send(7).unwrap();
+        assert_eq!(client.recv(), Ok(7));
+
+        drop(server);
+        assert_eq!(client.recv(), Err(crate::RecvError));
+    }
+
+    #[test]
+    #[cfg(unix)]
+    fn test_over_unix_both_ways() {
+        use std::os::unix::net::UnixStream;
+
+        let (a, b) = UnixStream::pair().unwrap();
+        let a = crate::Channel::<u32, Request>::over_unix(a).unwrap();
+        let b = crate::Channel::<Request, u32>::over_unix(b).unwrap();
+
+        for id in 0..10 {
+            b.send(Request {
+                id,
+                body: id.to_string(),
+            })
+            .unwrap();
+            a.send(id).unwrap();
+        }
+
+        for id in 0..10 {
+            assert_eq!(a.recv().unwrap().body, id.to_string());
+            assert_eq!(b.recv(), Ok(id));
+        }
+
+        drop(a);
+        assert_eq!(b.recv(), Err(crate::RecvError));
+    }
+
+    #[test]
+    fn test_reconnects_after_drop() {
+        use std::time::Duration;
+
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let addr = listener.local_addr().unwrap();
+        let client =
+            crate::transport::reconnecting::<u32, u32, _>(move || TcpStream::connect(addr));
+
+        let server = crate::Channel::<u32, u32>::over_tcp(listener.accept().unwrap().0).unwrap();
+        client.send(1).unwrap();
+        assert_eq!(server.recv(), Ok(1));
+        server.send(10).unwrap();
+        assert_eq!(client.recv(), Ok(10));
+
+        // Take the server down entirely, and give the client time to notice.
+        drop(server);
+        drop(listener);
+        std::thread::sleep(Duration::from_millis(100));
+
+        client.send(2).unwrap();
+        assert_eq!(
+            client.recv_timeout(Duration::from_millis(50)),
+            Err(crate::RecvTimeoutError::Timeout)
+        );
+
+        let listener = TcpListener::bind(addr).unwrap();
+        let server = crate::Channel::<u32, u32>::over_tcp(listener.accept().unwrap().0).unwrap();
+        assert_eq!(server.recv(), Ok(2));
+        server.send(20).unwrap();
+        assert_eq!(client.recv(), Ok(20));
+
+        drop(client);
+        assert_eq!(server.recv(), Err(crate::RecvError));
+    }
+
+    #[test]
+    fn test_reconnecting_buffer_is_bounded() {
+        use std::io;
+        use std::sync::atomic::{AtomicBool, Ordering};
+        use std::sync::Arc;
+
+        use crate::OverflowPolicy;
+
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let addr = listener.local_addr().unwrap();
+        let up = Arc::new(AtomicBool::new(false));
+        let connecting = up.clone();
+        let client = crate::transport::reconnecting_bounded::<u32, u32, _>(
+            move || {
+                if connecting.load(Ordering::SeqCst) {
+                    TcpStream::connect(addr)
+                } else {
+                    Err(io::ErrorKind::ConnectionRefused.into())
+                }
+            },
+            2,
+            OverflowPolicy::DropOldest,
+        );
+
+        // Only the latest two messages are kept while there is no connection.
+        for n in 0..5 {
+            client.send(n).unwrap();
+        }
+
+        up.store(true, Ordering::SeqCst);
+        let server = crate::Channel::<u32, u32>::over_tcp(listener.accept().unwrap().0).unwrap();
+        assert_eq!(server.recv(), Ok(3));
+        assert_eq!(server.recv(), Ok(4));
+
+        // Once connected, the buffer is drained as messages are sent.
+        for n in 5..10 {
+            client.send(n).unwrap();
+            assert_eq!(server.recv(), Ok(n));
+        }
+    }
+
+    #[test]
+    fn test_byte_batches_are_transparent() {
+        use std::time::Duration;
+
+        use crate::transport::Transport;
+
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let client = TcpStream::connect(listener.local_addr().unwrap()).unwrap();
+        let (server, _) = listener.accept().unwrap();
+        let (client, counts) = Counting::new(client);
+
+        let transport = Transport::new()
+            .byte_batch(1024)
+            .flush_interval(Duration::from_millis(50));
+        let client = transport.serve::<u32, u32, _>(client);
+        let server = transport.serve::<u32, u32, _>(server);
+
+        client.send_all(0..1000).unwrap();
+        assert!(server.iter().take(1000).eq(0..1000));
+        let frames = counts.flushes.load(Ordering::Relaxed);
+        assert!(frames < 100, "{} frames for 1000 messages", frames);
+
+        // A lone message is written once the flush interval elapses.
+        server.send(7).unwrap();
+        assert_eq!(client.recv_timeout(Duration::from_secs(5)), Ok(7));
+    }
+
+    #[test]
+    fn test_byte_batches_without_a_flush_interval() {
+        use std::time::Duration;
+
+        use crate::transport::Transport;
+
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let client = TcpStream::connect(listener.local_addr().unwrap()).unwrap();
+        let (server, _) = listener.accept().unwrap();
+
+        // Batches are only written once full, or once the local side hangs up.
+        let transport = Transport::new()
+            .byte_batch(64)
+            .flush_interval(Duration::MAX);
+        let client = transport.serve::<u32, (), _>(client);
+        let server = transport.serve::<(), u32, _>(server);
+
+        client.send_all(0..100).unwrap();
+        drop(client);
+        assert!(server.iter().take(100).eq(0..100));
+    }
+
+    #[test]
+    #[cfg(feature = "compression")]
+    fn test_compressed_round_trip() {
+        use crate::transport::{Codec, DefaultCodec, Transport};
+
+        #[derive(Serialize, Deserialize, Debug, PartialEq, Clone)]
+        struct Report {
+            requests: Vec<Request>,
+            summary: String,
+        }
+
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let client = TcpStream::connect(listener.local_addr().unwrap()).unwrap();
+        let (server, _) = listener.accept().unwrap();
+        let (client, counts) = Counting::new(client);
+
+        let transport = Transport::new().compressed(6);
+        let client = transport.serve::<Report, u32, _>(client);
+        let server = transport.serve::<u32, Report, _>(server);
+
+        let report = Report {
+            requests: (0..1000)
+                .map(|id| Request {
+                    id,
+                    body: "the same body every time".to_string(),
+                })
+                .collect(),
+            summary: "x".repeat(1 << 16),
+        };
+        let serialized = DefaultCodec::default().encode(&report).unwrap().len();
+
+        for _ in 0..3 {
+            client.send(report.clone()).unwrap();
+        }
+        client
+            .send(Report {
+                requests: Vec::new(),
+                summary: String::new(),
+            })
+            .unwrap();
+
+        for _ in 0..3 {
+            assert_eq!(server.recv().unwrap(), report);
+        }
+        assert!(server.recv().unwrap().requests.is_empty());
+        let written = counts.bytes.load(Ordering::Relaxed);
+        assert!(written < serialized, "{} bytes written", written);
+
+        server.send(1).unwrap();
+        assert_eq!(client.recv(), Ok(1));
+    }
+
+    #[cfg(feature = "checksum")]
+    #[test]
+    fn test_corrupt_frames_are_rejected() {
+        use crate::transport::{Codec, DefaultCodec, FrameReader, FrameWriter, Transport};
+        use crate::FrameIntegrityError;
+
+        let transport = Transport::new().checksummed();
+        let mut written = Vec::new();
+        let mut frames = FrameWriter::new(&transport, &mut written);
+        frames.write(b"first").unwrap();
+        frames.write(b"second").unwrap();
+
+        let mut frames = FrameReader::new(&transport, &written[..]);
+        assert_eq!(frames.read().unwrap().unwrap(), b"first");
+        assert_eq!(frames.read().unwrap().unwrap(), b"second");
+        assert!(frames.read().unwrap().is_none());
+
+        // Flip a bit in the payload of the second frame.
+        let second = written.len() - 4 - 1;
+        written[second] ^= 1;
+        let mut frames = FrameReader::new(&transport, &written[..]);
+        assert_eq!(frames.read().unwrap().unwrap(), b"first");
+        let err = frames.read().unwrap_err();
+        assert_eq!(err.kind(), std::io::ErrorKind::InvalidData);
+        let err = err.into_inner().unwrap();
+        let err = err.downcast_ref::<FrameIntegrityError>().unwrap();
+        assert_ne!(err.expected, err.found);
+
+        // Restore the payload and corrupt the length prefix of the second frame instead,
+        // shortening it by a byte.
+        written[second] ^= 1;
+        let second = written.len() - (4 + "second".len() + 4);
+        written[second + 3] -= 1;
+        let mut frames = FrameReader::new(&transport, &written[..]);
+        assert_eq!(frames.read().unwrap().unwrap(), b"first");
+        let err = frames.read().unwrap_err();
+        let err = err.into_inner().unwrap();
+        assert!(err.downcast_ref::<FrameIntegrityError>().is_some());
+
+        // A length corrupted upwards is rejected before it is read.
+        written[second + 3] += 1;
+        written[second] ^= 0x80;
+        let mut frames = FrameReader::new(&transport, &written[..]);
+        assert_eq!(frames.read().unwrap().unwrap(), b"first");
+        assert_eq!(
+            frames.read().unwrap_err().kind(),
+            std::io::ErrorKind::InvalidData
+        );
+
+        // A corrupt frame disconnects the channel rather than delivering a message.
+        let request = Request {
+            id: 1,
+            body: "intact".to_string(),
+        };
+        let mut written = Vec::new();
+        let mut frames = FrameWriter::new(&transport, &mut written);
+        let payload = DefaultCodec::default().encode(&request).unwrap();
+        frames.write(&payload).unwrap();
+        frames.write(&payload).unwrap();
+        let second = written.len() - 4 - 1;
+        written[second] ^= 1;
+
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let mut client = TcpStream::connect(listener.local_addr().unwrap()).unwrap();
+        let (server, _) = listener.accept().unwrap();
+        let server = transport.serve::<(), Request, _>(server);
+        std::io::Write::write_all(&mut client, &written).unwrap();
+
+        assert_eq!(server.recv(), Ok(request));
+        assert_eq!(server.recv(), Err(crate::RecvError));
+    }
+
+    #[test]
+    fn test_custom_codec_round_trip() {
+        use crate::transport::{Codec, JsonCodec, Transport};
+        use serde::de::DeserializeOwned;
+
+        #[derive(Serialize, Deserialize, Debug, PartialEq)]
+        struct Batch {
+            owner: String,
+            requests: Vec<Request>,
+            parent: Option<Box<Batch>>,
+        }
+
+        /// JSON with every byte inverted, so that frames are not valid JSON.
+        #[derive(Clone)]
+        struct Inverted;
+
+        impl Codec for Inverted {
+            type Error = serde_json::Error;
+
+            fn encode<T: Serialize>(&self, message: &T) -> Result<Vec<u8>, Self::Error> {
+                let mut payload = JsonCodec.encode(message)?;
+                payload.iter_mut().for_each(|byte| *byte = !*byte);
+                Ok(payload)
+            }
+
+            fn decode<T: DeserializeOwned>(&self, payload: &[u8]) -> Result<T, Self::Error> {
+                let payload: Vec<u8> = payload.iter().map(|byte| !byte).collect();
+                JsonCodec.decode(&payload)
+            }
+        }
+
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let client = TcpStream::connect(listener.local_addr().unwrap()).unwrap();
+        let (server, _) = listener.accept().unwrap();
+
+        let transport = Transport::new().with_codec(Inverted);
+        let client = transport.serve::<Batch, (), _>(client);
+        let server = transport.serve::<(), Batch, _>(server);
+
+        let batch = Batch {
+            owner: "child".to_string(),
+            requests: vec![Request {
+                id: 2,
+                body: "nested".to_string(),
+            }],
+            parent: Some(Box::new(Batch {
+                owner: "parent".to_string(),
+                requests: Vec::new(),
+                parent: None,
+            })),
+        };
+        client.send(batch).unwrap();
+        let received = server.recv().unwrap();
+        assert_eq!(received.parent.unwrap().owner, "parent");
+        assert_eq!(received.requests[0].body, "nested");
+
+        // Frames another codec cannot decode disconnect the channel.
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let client = TcpStream::connect(listener.local_addr().unwrap()).unwrap();
+        let (server, _) = listener.accept().unwrap();
+        let client = transport.serve::<Request, (), _>(client);
+        let server = Transport::new().serve::<(), Request, _>(server);
+
+        client
+            .send(Request {
+                id: 3,
+                body: String::new(),
+            })
+            .unwrap();
+        assert_eq!(server.recv(), Err(crate::RecvError));
+    }
+
+    #[cfg(feature = "bincode")]
+    #[test]
+    fn test_bincode_round_trip() {
+        use std::collections::BTreeMap;
+
+        use crate::transport::{BincodeCodec, Codec, JsonCodec, Transport};
+
+        #[derive(Serialize, Deserialize, Debug, PartialEq, Clone)]
+        enum Status {
+            Pending,
+            Done { code: i32 },
+        }
+
+        #[derive(Serialize, Deserialize, Debug, PartialEq, Clone)]
+        struct Job {
+            request: Request,
+            status: Status,
+            children: Vec<Job>,
+            tags: BTreeMap<String, Option<f64>>,
+        }
+
+        let leaf = Job {
+            request: Request {
+                id: 2,
+                body: "leaf".to_string(),
+            },
+            status: Status::Done { code: -1 },
+            children: Vec::new(),
+            tags: vec![("weight".to_string(), Some(0.5))]
+                .into_iter()
+                .collect(),
+        };
+        let job = Job {
+            request: Request {
+                id: 1,
+                body: "root\nwith a newline".to_string(),
+            },
+            status: Status::Pending,
+            children: vec![leaf.clone(), leaf],
+            tags: vec![("none".to_string(), None)].into_iter().collect(),
+        };
+
+        // The default codec, used by `serve`.
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let client = TcpStream::connect(listener.local_addr().unwrap()).unwrap();
+        let (server, _) = listener.accept().unwrap();
+        let client = crate::transport::serve::<Job, Job, _>(client);
+        let server = crate::transport::serve::<Job, Job, _>(server);
+
+        client.send(job.clone()).unwrap();
+        let received = server.recv().unwrap();
+        assert_eq!(received, job);
+        server.send(received).unwrap();
+        assert_eq!(client.recv(), Ok(job.clone()));
+
+        let encoded = BincodeCodec.encode(&job).unwrap();
+        assert!(encoded.len() < JsonCodec.encode(&job).unwrap().len());
+
+        // A frame that fails to decode disconnects the channel.
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let client = TcpStream::connect(listener.local_addr().unwrap()).unwrap();
+        let (server, _) = listener.accept().unwrap();
+        let client = Transport::new().serve::<u8, (), _>(client);
+        let server = Transport::new().serve::<(), Job, _>(server);
+
+        client.send(7).unwrap();
+        assert_eq!(server.recv(), Err(crate::RecvError));
+    }
+
+    #[cfg(feature = "json")]
+    #[test]
+    #[should_panic(expected = "cannot delimit binary frames")]
+    fn test_json_lines_refuse_byte_batches() {
+        use crate::transport::{JsonLinesCodec, Transport};
+
+        let _ = Transport::new().with_codec(JsonLinesCodec).byte_batch(1024);
+    }
+
+    #[cfg(feature = "json")]
+    #[test]
+    #[should_panic(expected = "cannot delimit binary frames")]
+    fn test_json_lines_refuse_existing_byte_batches() {
+        use crate::transport::{JsonLinesCodec, Transport};
+
+        let _ = Transport::new().byte_batch(1024).with_codec(JsonLinesCodec);
+    }
+
+    #[cfg(all(feature = "json", feature = "checksum"))]
+    #[test]
+    #[should_panic(expected = "cannot delimit binary frames")]
+    fn test_json_lines_refuse_checksums() {
+        use crate::transport::{JsonLinesCodec, Transport};
+
+        let _ = Transport::new().with_codec(JsonLinesCodec).checksummed();
+    }
+
+    #[cfg(feature = "json")]
+    #[test]
+    fn test_json_lines_split_across_reads() {
+        use std::collections::VecDeque;
+        use std::io::Read;
+
+        use crate::transport::{Codec, FrameReader, JsonLinesCodec, Transport};
+
+        /// Returns one chunk per read, however much room there is.
+        struct Chunks(VecDeque<&'static [u8]>);
+
+        impl Read for Chunks {
+            fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+                match self.0.pop_front() {
+                    Some(chunk) => {
+                        buf[..chunk.len()].copy_from_slice(chunk);
+                        Ok(chunk.len())
+                    }
+                    None => Ok(0),
+                }
+            }
+        }
+
+        let chunks = Chunks(VecDeque::from(vec![
+            &b"{\"id\":1,\"body\":\"one\"}\n{\"id\":2,\"bo"[..],
+            &b"dy\":\"two\\nlines\"}\n"[..],
+            &b"{\"id\":3,\"body\":\"\"}\n"[..],
+        ]));
+        let transport = Transport::new().with_codec(JsonLinesCodec);
+        let mut frames = FrameReader::new(&transport, chunks);
+
+        let mut requests = Vec::new();
+        while let Some(frame) = frames.read().unwrap() {
+            requests.push(JsonLinesCodec.decode::<Request>(&frame).unwrap());
+        }
+        assert_eq!(
+            requests,
+            [
+                Request {
+                    id: 1,
+                    body: "one".to_string()
+                },
+                Request {
+                    id: 2,
+                    body: "two\nlines".to_string()
+                },
+                Request {
+                    id: 3,
+                    body: String::new()
+                },
+            ]
+        );
+
+        // A line cut off by the end of the stream is not a frame.
+        let chunks = Chunks(VecDeque::from(vec![&b"{\"id\":4"[..]]));
+        let mut frames = FrameReader::new(&transport, chunks);
+        assert!(frames.read().is_err());
+    }
+
+    #[test]
+    fn test_frames_survive_partial_reads() {
+        struct OneByte<'a>(&'a [u8]);
+
+        impl std::io::Read for OneByte<'_> {
+            fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+                match (self.0.split_first(), buf.first_mut()) {
+                    (Some((&byte, rest)), Some(slot)) => {
+                        *slot = byte;
+                        self.0 = rest;
+                        Ok(1)
+                    }
+                    _ => Ok(0),
+                }
+            }
+        }
+
+        let mut bytes = Vec::new();
+        super::write_frame(&mut bytes, b"first").unwrap();
+        super::write_frame(&mut bytes, b"").unwrap();
+        super::write_frame(&mut bytes, b"second").unwrap();
+
+        let mut reader = OneByte(&bytes);
+        assert_eq!(
+            super::read_frame(&mut reader, 6).unwrap().unwrap(),
+            b"first"
+        );
+        assert_eq!(super::read_frame(&mut reader, 6).unwrap().unwrap(), b"");
+        assert_eq!(
+            super::read_frame(&mut reader, 6).unwrap().unwrap(),
+            b"second"
+        );
+        assert!(super::read_frame(&mut reader, 6).unwrap().is_none());
+    }
+
+    #[test]
+    fn test_oversized_frames_are_rejected() {
+        use std::io::ErrorKind;
+
+        use crate::transport::{FrameReader, Transport};
+
+        // A length claiming 4 GiB, with nothing behind it, fails before allocating.
+        let mut bytes = &[0xff, 0xff, 0xff, 0xff][..];
+        let err = super::read_frame(&mut bytes, 1 << 20).unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::InvalidData);
+
+        let mut bytes = Vec::new();
+        super::write_frame(&mut bytes, b"fits").unwrap();
+        super::write_frame(&mut bytes, b"too long").unwrap();
+
+        let transport = Transport::new().max_frame_len(4);
+        let mut frames = FrameReader::new(&transport, &bytes[..]);
+        assert_eq!(frames.read().unwrap().unwrap(), b"fits");
+        assert_eq!(frames.read().unwrap_err().kind(), ErrorKind::InvalidData);
+    }
+
+    #[cfg(feature = "json")]
+    #[test]
+    fn test_oversized_json_lines_are_rejected() {
+        use std::io::ErrorKind;
+
+        use crate::transport::{FrameReader, JsonLinesCodec, Transport};
+
+        let transport = Transport::new().with_codec(JsonLinesCodec).max_frame_len(4);
+        let mut frames = FrameReader::new(&transport, &b"1234\n12345\n"[..]);
+        assert_eq!(frames.read().unwrap().unwrap(), b"1234");
+        assert_eq!(frames.read().unwrap_err().kind(), ErrorKind::InvalidData);
+    }
+}
@@ -0,0 +1,166 @@
+//! Discarding messages that waited too long to be received.
+
+use std::time::{Duration, Instant};
+
+use crate::{Channel, RecvError, RecvTimeoutError, SendError, TryRecvError, TrySendError};
+
+/// One side of a bidirectional channel that stamps every message it sends, and
+/// discards received messages sent longer ago than its time to live, created by
+/// [`ttl_channel`].
+///
+/// The age is checked on receipt, so time spent waiting in the queue counts against
+/// it. Expired messages are skipped silently, as if they had never been sent.
+#[derive(Debug)]
+pub struct TtlChannel<S, R> {
+    channel: Channel<(Instant, S), (Instant, R)>,
+    ttl: Duration,
+}
+
+impl<S, R> TtlChannel<S, R> {
+    /// Wraps a channel carrying timestamped messages, discarding received messages older
+    /// than `ttl`.
+    pub fn new(channel: Channel<(Instant, S), (Instant, R)>, ttl: Duration) -> Self {
+        TtlChannel { channel, ttl }
+    }
+
+    /// Returns the time to live of received messages.
+    pub fn ttl(&self) -> Duration {
+        self.ttl
+    }
+
+    /// Stamps a value with the current time and sends it like [`Channel::send`].
+    pub fn send(&self, s: S) -> Result<(), SendError<S>> {
+        self.channel
+            .send((Instant::now(), s))
+            .map_err(|SendError((_, s))| SendError(s))
+    }
+
+    /// Stamps a value with the current time and sends it without blocking.
+    pub fn try_send(&self, s: S) -> Result<(), TrySendError<S>> {
+        self.channel
+            .try_send((Instant::now(), s))
+            .map_err(|err| match err {
+                TrySendError::Full((_, s)) => TrySendError::Full(s),
+                TrySendError::Disconnected((_, s)) => TrySendError::Disconnected(s),
+            })
+    }
+
+    fn fresh(&self, (sent, r): (Instant, R)) -> Option<R> {
+        if sent.elapsed() <= self.ttl {
+            Some(r)
+        } else {
+            None
+        }
+    }
+
+    /// Blocks until a value arrives that has not expired, skipping any that have.
+    pub fn recv(&self) -> Result<R, RecvError> {
+        loop {
+            if let Some(r) = self.fresh(self.channel.recv()?) {
+                return Ok(r);
+            }
+        }
+    }
+
+    /// Returns the first pending value that has not expired without blocking,
+    /// skipping any before it that have.
+    pub fn try_recv(&self) -> Result<R, TryRecvError> {
+        loop {
+            if let Some(r) = self.fresh(self.channel.try_recv()?) {
+                return Ok(r);
+            }
+        }
+    }
+
+    /// Waits for at most `timeout` for a value that has not expired, skipping any
+    /// that have.
+    pub fn recv_timeout(&self, timeout: Duration) -> Result<R, RecvTimeoutError> {
+        let deadline = crate::deadline(timeout);
+        loop {
+            if let Some(r) = self.fresh(self.channel.recv_until(deadline)?) {
+                return Ok(r);
+            }
+        }
+    }
+
+    /// Returns a reference to the underlying channel.
+    ///
+    /// Messages go through it with their timestamps, and expired ones are not
+    /// skipped.
+    pub fn get_ref(&self) -> &Channel<(Instant, S), (Instant, R)> {
+        &self.channel
+    }
+
+    /// Returns a mutable reference to the underlying channel.
+    ///
+    /// Messages go through it with their timestamps, and expired ones are not
+    /// skipped.
+    pub fn get_mut(&mut self) -> &mut Channel<(Instant, S), (Instant, R)> {
+        &mut self.channel
+    }
+
+    /// Unwraps this adapter, returning the underlying channel.
+    pub fn into_inner(self) -> Channel<(Instant, S), (Instant, R)> {
+        self.channel
+    }
+}
+
+/// Creates a bidirectional channel whose sides discard messages that waited longer
+/// than `ttl` to be received, returning the left and right sides.
+///
+/// # Examples
+///
+/// ```
+/// use std::thread;
+/// use std::time::Duration;
+///
+/// let (left, right) = bichannel::ttl_channel::<&str, ()>(Duration::from_millis(20));
+///
+/// left.send("stale").unwrap();
+/// thread::sleep(Duration::from_millis(40));
+/// left.send("fresh").unwrap();
+///
+/// assert_eq!(right.recv(), Ok("fresh"));
+/// ```
+pub fn ttl_channel<T, U>(ttl: Duration) -> (TtlChannel<T, U>, TtlChannel<U, T>) {
+    let (left, right) = crate::channel();
+    (TtlChannel::new(left, ttl), TtlChannel::new(right, ttl))
+}
+
+#[cfg(test)]
+mod examples {
+    use std::thread;
+    use std::time::Duration;
+
+    use crate::{RecvTimeoutError, TryRecvError};
+
+    #[test]
+    fn test_skips_expired() {
+        let (left, right) = crate::ttl_channel::<u8, ()>(Duration::from_millis(30));
+
+        left.send(1).unwrap();
+        left.send(2).unwrap();
+        thread::sleep(Duration::from_millis(60));
+        left.send(3).unwrap();
+
+        assert_eq!(right.recv(), Ok(3));
+        assert_eq!(right.try_recv(), Err(TryRecvError::Empty));
+
+        left.send(4).unwrap();
+        assert_eq!(right.recv_timeout(Duration::MAX), Ok(4));
+    }
+
+    #[test]
+    fn test_expired_only_times_out() {
+        let (left, right) = crate::ttl_channel::<u8, ()>(Duration::from_millis(10));
+
+        left.send(1).unwrap();
+        thread::sleep(Duration::from_millis(30));
+
+        assert_eq!(right.try_recv(), Err(TryRecvError::Empty));
+        assert_eq!(
+            right.recv_timeout(Duration::from_millis(20)),
+            Err(RecvTimeoutError::Timeout)
+        );
+    }
+}
@@ -0,0 +1,121 @@
+//! Warning about receives on a [`Channel`] that block for longer than expected.
+
+use std::time::Duration;
+
+use crate::{Channel, RecvError, RecvTimeoutError};
+
+impl<S, R> Channel<S, R> {
+    /// Waits for a value from the other side like [`recv`], warning if it has been
+    /// blocked for more than `warn_after`.
+    ///
+    /// This is a diagnostic aid for spotting protocols that deadlock, not a timeout:
+    /// after warning, it carries on waiting for as long as it takes. The warning is
+    /// emitted as a `tracing` event at the `WARN` level, naming this side if it has a
+    /// name. Without the `tracing` feature, use [`recv_watchdog_with`] to handle it.
+    ///
+    /// [`recv`]: Self::recv
+    /// [`recv_watchdog_with`]: Self::recv_watchdog_with
+    #[cfg(feature = "tracing")]
+    pub fn recv_watchdog(&self, warn_after: Duration) -> Result<R, RecvError> {
+        self.recv_watchdog_with(warn_after, |blocked| warn(self.name(), blocked))
+    }
+
+    /// Waits for a value from the other side like [`recv`], calling `on_stall` with how
+    /// long this has been blocked once `warn_after` has elapsed.
+    ///
+    /// This is a diagnostic aid for spotting protocols that deadlock, not a timeout:
+    /// after calling `on_stall`, it carries on waiting for as long as it takes.
+    /// `on_stall` is called at most once, on the thread blocked receiving. It is not
+    /// called if a value arrives, or the other side hangs up, in time.
+    ///
+    /// [`recv`]: Self::recv
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::thread;
+    /// use std::time::Duration;
+    ///
+    /// let (left, right) = bichannel::channel::<u8, u8>();
+    ///
+    /// let late = thread::spawn(move || {
+    ///     thread::sleep(Duration::from_millis(20));
+    ///     right.send(1).unwrap();
+    /// });
+    ///
+    /// let mut stalled = None;
+    /// let received = left.recv_watchdog_with(Duration::from_millis(5), |blocked| {
+    ///     stalled = Some(blocked);
+    /// });
+    ///
+    /// assert_eq!(received, Ok(1));
+    /// assert!(stalled.unwrap() >= Duration::from_millis(5));
+    /// late.join().unwrap();
+    /// ```
+    pub fn recv_watchdog_with<F>(&self, warn_after: Duration, on_stall: F) -> Result<R, RecvError>
+    where
+        F: FnOnce(Duration),
+    {
+        match self.recv_timeout(warn_after) {
+            Ok(r) => Ok(r),
+            Err(RecvTimeoutError::Disconnected) => Err(RecvError),
+            Err(RecvTimeoutError::Timeout) => {
+                on_stall(warn_after);
+                self.recv()
+            }
+        }
+    }
+}
+
+#[cfg(feature = "tracing")]
+fn warn(name: Option<&str>, blocked: Duration) {
+    tracing::warn!(
+        channel = name,
+        ?blocked,
+        "recv blocked longer than expected"
+    );
+}
+
+#[cfg(test)]
+mod examples {
+    use std::cell::Cell;
+    use std::thread;
+    use std::time::Duration;
+
+    use crate::RecvError;
+
+    #[test]
+    fn test_watchdog_warns_and_keeps_waiting() {
+        let (left, right) = crate::channel::<u8, u8>();
+        let stalls = Cell::new(0);
+
+        let late = thread::spawn(move || {
+            thread::sleep(Duration::from_millis(50));
+            right.send(1).unwrap();
+            right
+        });
+
+        let received = left.recv_watchdog_with(Duration::from_millis(10), |_| {
+            stalls.set(stalls.get() + 1);
+        });
+        assert_eq!(received, Ok(1));
+        assert_eq!(stalls.get(), 1);
+
+        let right = late.join().unwrap();
+        right.send(2).unwrap();
+        let received = left.recv_watchdog_with(Duration::from_secs(1), |_| {
+            stalls.set(stalls.get() + 1);
+        });
+        assert_eq!(received, Ok(2));
+        assert_eq!(stalls.get(), 1);
+
+        drop(right);
+        let received = left.recv_watchdog_with(Duration::from_secs(1), |_| {
+            stalls.set(stalls.get() + 1);
+        });
+        assert_eq!(received, Err(RecvError));
+        assert_eq!(stalls.get(), 1);
+        #[cfg(feature = "tracing")]
+        assert_eq!(left.recv_watchdog(Duration::from_secs(1)), Err(RecvError));
+    }
+}
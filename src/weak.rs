@@ -0,0 +1,193 @@
+//! Handles to a side of a [`Channel`] that do not keep it open, enabled with the
+//! `crossbeam` or `flume` feature.
+
+use std::fmt;
+use std::sync::{Arc, Mutex, OnceLock, PoisonError, Weak};
+
+use crate::alive::{Alive, Peer};
+use crate::{Backend, Channel, DefaultBackend};
+
+/// Copies of a side's halves, shared by every clone of the side and dropped along with
+/// the last of them, from which a [`WeakChannel`] upgrades.
+///
+/// The halves are not counted by [`Channel::sender_count`] or
+/// [`Channel::receiver_count`]. The sender is retired by [`Channel::close_sender`], so
+/// that a closed direction cannot be reopened by upgrading.
+pub(crate) struct Link<S, R, B: Backend = DefaultBackend> {
+    sender: Option<B::Sender<S>>,
+    receiver: B::Receiver<R>,
+    /// Not counted as open, as the sender is not counted either.
+    alive: Alive,
+    peer: Peer,
+    name: Option<Arc<str>>,
+}
+
+/// The halves are only touched to clone them, but are locked so that sides stay
+/// `Send` whatever their payloads, as a pending value of a sender is not `Sync`.
+pub(crate) type SharedLink<S, R, B> = Arc<OnceLock<Mutex<Link<S, R, B>>>>;
+
+/// A handle to one side of a bidirectional channel that does not keep it open,
+/// created by [`Channel::downgrade`].
+///
+/// Holding a weak handle neither counts as a sender or receiver nor keeps this side
+/// alive, so the other side observes the hang up once every strong handle has been
+/// dropped. While one remains, [`upgrade`] returns a new strong handle, as a clone
+/// of the side would be.
+///
+/// [`upgrade`]: Self::upgrade
+pub struct WeakChannel<S, R> {
+    link: Weak<OnceLock<Mutex<Link<S, R>>>>,
+}
+
+impl<S, R> WeakChannel<S, R> {
+    /// Returns a strong handle to the side, or [`None`] if every strong handle has
+    /// been dropped.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// let (left, right) = bichannel::channel::<u8, u8>();
+    /// let weak = left.downgrade();
+    ///
+    /// let upgraded = weak.upgrade().unwrap();
+    /// upgraded.send(1).unwrap();
+    /// assert_eq!(right.recv(), Ok(1));
+    ///
+    /// drop((left, upgraded));
+    /// assert!(weak.upgrade().is_none());
+    /// assert!(right.is_disconnected());
+    /// ```
+    pub fn upgrade(&self) -> Option<Channel<S, R>> {
+        let shared = self.link.upgrade()?;
+        let link = shared.get()?.lock().unwrap_or_else(PoisonError::into_inner);
+        let (sender, alive) = match &link.sender {
+            Some(sender) => (sender.clone(), link.alive.reopened()),
+            None => (crate::backend::unbounded().0, link.alive.closed()),
+        };
+        let mut channel =
+            Channel::assemble(sender, link.receiver.clone(), alive, link.peer.clone());
+        channel.name = link.name.clone();
+        channel.link = shared.clone();
+        Some(channel)
+    }
+}
+
+impl<S, R> Clone for WeakChannel<S, R> {
+    fn clone(&self) -> Self {
+        WeakChannel {
+            link: self.link.clone(),
+        }
+    }
+}
+
+impl<S, R> fmt::Debug for WeakChannel<S, R> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("WeakChannel").finish_non_exhaustive()
+    }
+}
+
+impl<S, R, B: Backend> Channel<S, R, B> {
+    /// Drops the copy of the sender set aside for upgrading, if this side has been
+    /// downgraded.
+    pub(crate) fn retire_link_sender(&self) {
+        if let Some(link) = self.link.get() {
+            link.lock().unwrap_or_else(PoisonError::into_inner).sender = None;
+        }
+    }
+}
+
+impl<S, R> Channel<S, R> {
+    /// Creates a [`WeakChannel`] handle to this side, which does not keep it open.
+    ///
+    /// The first downgrade sets aside copies of this side's halves for upgrading,
+    /// and shares them with every clone. They are dropped with the last strong
+    /// handle, and do not keep the other side from observing the hang up. Once any
+    /// handle calls [`close_sender`], the copy of the sender is retired too, so
+    /// handles upgraded after that have their sender closed, as if they had called
+    /// it themselves. Upgraded handles take the name this side had when it was first
+    /// downgraded.
+    ///
+    /// [`close_sender`]: Self::close_sender
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use bichannel::RecvError;
+    ///
+    /// let (left, right) = bichannel::channel::<u8, u8>();
+    ///
+    /// let weak = left.downgrade();
+    /// assert_eq!(left.sender_count(), 1);
+    ///
+    /// drop(left);
+    /// assert_eq!(right.recv(), Err(RecvError));
+    /// assert!(weak.upgrade().is_none());
+    /// ```
+    pub fn downgrade(&self) -> WeakChannel<S, R> {
+        self.link.get_or_init(|| {
+            Mutex::new(Link {
+                sender: Some(self.sender.clone_uncounted()),
+                receiver: self.receiver.clone_uncounted(),
+                alive: self.alive.closed(),
+                peer: self.peer.clone(),
+                name: self.name.clone(),
+            })
+        });
+
+        WeakChannel {
+            link: Arc::downgrade(&self.link),
+        }
+    }
+}
+
+#[cfg(test)]
+mod examples {
+    use std::time::Duration;
+
+    use crate::{RecvError, RecvTimeoutError, SendError, TryRecvError};
+
+    #[test]
+    fn test_weak_channel_scenario() {
+        let (left, right) = crate::channel::<u8, u8>();
+        let clone = left.clone();
+        let weak = clone.downgrade();
+
+        let upgraded = weak.upgrade().unwrap();
+        assert_eq!(upgraded, left);
+        assert_eq!(left.sender_count(), 3);
+        assert_eq!(left.receiver_count(), 3);
+
+        upgraded.send(1).unwrap();
+        right.send(2).unwrap();
+        assert_eq!(right.recv(), Ok(1));
+        assert_eq!(left.recv(), Ok(2));
+
+        drop((left, clone, upgraded));
+        assert!(weak.upgrade().is_none());
+        assert!(weak.clone().upgrade().is_none());
+        assert!(right.is_disconnected());
+        assert_eq!(right.recv(), Err(RecvError));
+        assert_eq!(right.send(3), Err(SendError(3)));
+    }
+
+    #[test]
+    fn test_close_sender_after_downgrade() {
+        let (mut left, right) = crate::channel::<u8, u8>();
+        let weak = left.downgrade();
+
+        left.close_sender();
+        assert_eq!(
+            right.recv_timeout(Duration::from_millis(50)),
+            Err(RecvTimeoutError::Disconnected)
+        );
+
+        // The closed direction stays closed for upgraded handles.
+        let upgraded = weak.upgrade().unwrap();
+        assert_eq!(upgraded.send(9), Err(SendError(9)));
+        assert_eq!(right.try_recv(), Err(TryRecvError::Disconnected));
+
+        // The other direction is still open.
+        right.send(1).unwrap();
+        assert_eq!(upgraded.recv(), Ok(1));
+    }
+}
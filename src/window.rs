@@ -0,0 +1,165 @@
+//! Receiving the messages of a [`Channel`] in batches.
+
+use std::time::Duration;
+
+use crate::{Channel, IntoIter};
+
+/// An owning iterator over messages received on a [`Channel`] in batches of a fixed
+/// size, created by [`Channel::windows`].
+///
+/// Each batch blocks until it is full. Once the other side hangs up, the messages
+/// left over are yielded as one last, shorter batch.
+#[derive(Debug)]
+pub struct Windows<R> {
+    messages: IntoIter<R>,
+    n: usize,
+}
+
+impl<R> Iterator for Windows<R> {
+    type Item = Vec<R>;
+
+    fn next(&mut self) -> Option<Vec<R>> {
+        let batch: Vec<R> = self.messages.by_ref().take(self.n).collect();
+        if batch.is_empty() {
+            None
+        } else {
+            Some(batch)
+        }
+    }
+}
+
+/// An owning iterator over messages received on a [`Channel`] in batches spanning a
+/// fixed time, created by [`Channel::time_windows`].
+///
+/// A window opens when a message arrives, and the batch holds every message received
+/// until it closes, so batches are never empty. Once the other side hangs up, the
+/// open window closes early.
+#[derive(Debug)]
+pub struct TimeWindows<R> {
+    messages: IntoIter<R>,
+    window: Duration,
+}
+
+impl<R> Iterator for TimeWindows<R> {
+    type Item = Vec<R>;
+
+    fn next(&mut self) -> Option<Vec<R>> {
+        let first = self.messages.next()?;
+        let deadline = crate::deadline(self.window);
+
+        let mut batch = vec![first];
+        while let Ok(r) = self.messages.receiver.recv_until(deadline) {
+            batch.push(r);
+        }
+        Some(batch)
+    }
+}
+
+impl<S, R> Channel<S, R> {
+    /// Consumes this side, dropping its sender, and returns an iterator over the
+    /// remaining messages from the other side in batches of `n`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `n` is zero.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// let (left, right) = bichannel::channel::<(), u8>();
+    ///
+    /// right.send_all(0..5).unwrap();
+    /// drop(right);
+    ///
+    /// let batches: Vec<_> = left.windows(2).collect();
+    /// assert_eq!(batches, [vec![0, 1], vec![2, 3], vec![4]]);
+    /// ```
+    pub fn windows(self, n: usize) -> Windows<R> {
+        assert!(n > 0, "window size must be non-zero");
+
+        Windows {
+            messages: self.into_iter(),
+            n,
+        }
+    }
+
+    /// Consumes this side, dropping its sender, and returns an iterator over the
+    /// remaining messages from the other side in batches, each holding the messages
+    /// received within `window` of the first.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::time::Duration;
+    ///
+    /// let (left, right) = bichannel::channel::<(), u8>();
+    ///
+    /// right.send_all(0..3).unwrap();
+    /// drop(right);
+    ///
+    /// let batches: Vec<_> = left.time_windows(Duration::from_millis(10)).collect();
+    /// assert_eq!(batches, [vec![0, 1, 2]]);
+    /// ```
+    pub fn time_windows(self, window: Duration) -> TimeWindows<R> {
+        TimeWindows {
+            messages: self.into_iter(),
+            window,
+        }
+    }
+}
+
+#[cfg(test)]
+mod examples {
+    use std::thread;
+    use std::time::Duration;
+
+    #[test]
+    fn test_partial_final_window() {
+        let (left, right) = crate::channel::<(), u8>();
+
+        let sender = thread::spawn(move || {
+            for i in 0..7 {
+                right.send(i).unwrap();
+            }
+        });
+
+        let sizes: Vec<_> = left.windows(3).map(|batch| batch.len()).collect();
+        assert_eq!(sizes, [3, 3, 1]);
+
+        sender.join().unwrap();
+    }
+
+    #[test]
+    fn test_time_windows() {
+        let (left, right) = crate::channel::<(), u8>();
+
+        let sender = thread::spawn(move || {
+            // Nothing is sent for a while, which must not produce empty batches.
+            thread::sleep(Duration::from_millis(120));
+            for i in 0..3 {
+                right.send(i).unwrap();
+                thread::sleep(Duration::from_millis(5));
+            }
+            thread::sleep(Duration::from_millis(150));
+            right.send(3).unwrap();
+        });
+
+        let mut batches = left.time_windows(Duration::from_millis(50));
+        assert_eq!(batches.next(), Some(vec![0, 1, 2]));
+        assert_eq!(batches.next(), Some(vec![3]));
+        assert_eq!(batches.next(), None);
+
+        sender.join().unwrap();
+    }
+
+    #[test]
+    fn test_unbounded_time_window() {
+        let (left, right) = crate::channel::<(), u8>();
+
+        right.send_all(0..3).unwrap();
+        drop(right);
+
+        let batches: Vec<_> = left.time_windows(Duration::MAX).collect();
+        assert_eq!(batches, [vec![0, 1, 2]]);
+    }
+}
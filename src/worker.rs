@@ -0,0 +1,127 @@
+//! Serving requests from a [`Channel`] on a background thread.
+
+use std::fmt;
+use std::ops::Deref;
+use std::thread::{self, JoinHandle};
+
+use crate::Channel;
+
+/// The side of a channel served by a background thread, created by [`spawn_worker`].
+///
+/// It derefs to the [`Channel`] that sends the requests and receives the responses.
+/// Dropping it closes that channel's sending half and waits for the thread to answer
+/// the requests already sent and exit, so the worker has finished, and dropped its
+/// closure, by the time the drop returns. Clones and sending halves taken from the
+/// channel keep the thread running, and so the drop waiting, until they are dropped.
+pub struct Worker<Req, Resp> {
+    channel: Channel<Req, Resp>,
+    thread: Option<JoinHandle<()>>,
+}
+
+impl<Req, Resp> Deref for Worker<Req, Resp> {
+    type Target = Channel<Req, Resp>;
+
+    fn deref(&self) -> &Channel<Req, Resp> {
+        &self.channel
+    }
+}
+
+impl<Req, Resp> Drop for Worker<Req, Resp> {
+    fn drop(&mut self) {
+        self.channel.close_sender();
+        if let Some(thread) = self.thread.take() {
+            // A panic in the worker has already been reported on its own thread.
+            let _ = thread.join();
+        }
+    }
+}
+
+impl<Req, Resp> fmt::Debug for Worker<Req, Resp> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Worker")
+            .field("channel", &self.channel)
+            .finish_non_exhaustive()
+    }
+}
+
+/// Spawns a background thread that answers every request with `f`, returning the
+/// [`Worker`] that sends the requests and receives the responses.
+///
+/// Requests are handled one at a time, in the order they were sent. Dropping the
+/// returned worker joins the thread, once the requests already sent have been
+/// answered.
+///
+/// # Panics
+///
+/// Panics if the thread cannot be spawned.
+///
+/// # Examples
+///
+/// ```
+/// let doubler = bichannel::spawn_worker(|n: u32| n * 2);
+///
+/// assert_eq!(doubler.call(21), Ok(42));
+/// ```
+pub fn spawn_worker<Req, Resp, F>(mut f: F) -> Worker<Req, Resp>
+where
+    Req: Send + 'static,
+    Resp: Send + 'static,
+    F: FnMut(Req) -> Resp + Send + 'static,
+{
+    let (client, server) = crate::channel();
+
+    let thread = thread::Builder::new()
+        .name("bichannel-worker".into())
+        .spawn(move || {
+            for request in server.iter() {
+                if server.send(f(request)).is_err() {
+                    break;
+                }
+            }
+        })
+        .expect("failed to spawn the bichannel worker thread");
+
+    Worker {
+        channel: client,
+        thread: Some(thread),
+    }
+}
+
+#[cfg(test)]
+mod examples {
+    use std::sync::mpsc;
+
+    #[test]
+    fn test_worker_exits_on_drop() {
+        let (exited, on_exit) = mpsc::channel::<()>();
+
+        let mut handled = 0;
+        let worker = crate::spawn_worker(move |n: u32| {
+            // Dropped along with the closure once the worker thread exits.
+            let _exited = &exited;
+            handled += 1;
+            (n * 2, handled)
+        });
+
+        assert_eq!(worker.call(21), Ok((42, 1)));
+        assert_eq!(worker.call(1), Ok((2, 2)));
+
+        // The thread has been joined, and so has dropped the closure, by the time the
+        // drop returns.
+        drop(worker);
+        assert_eq!(on_exit.try_recv(), Err(mpsc::TryRecvError::Disconnected));
+    }
+
+    #[test]
+    fn test_drop_waits_for_sent_requests() {
+        let (handled, on_handled) = mpsc::channel();
+        let worker = crate::spawn_worker(move |n: u32| {
+            std::thread::sleep(std::time::Duration::from_millis(10));
+            handled.send(n).unwrap();
+        });
+
+        worker.send_all(0..3).unwrap();
+        drop(worker);
+        assert_eq!(on_handled.try_iter().collect::<Vec<_>>(), [0, 1, 2]);
+    }
+}
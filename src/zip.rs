@@ -0,0 +1,107 @@
+//! Receiving from two [`Channel`]s in lockstep, as pairs of messages.
+
+use std::fmt;
+
+use crate::Channel;
+
+/// Combines two channels into a single blocking iterator over pairs of messages, one
+/// from each, like [`Iterator::zip`].
+///
+/// The i-th message received on `a` is paired with the i-th message received on `b`.
+/// Each pair waits for `a` first, then for `b`. The iterator ends as soon as either
+/// counterpart has hung up with nothing left to receive, dropping a message already
+/// received from `a` for that pair, along with both channels.
+///
+/// # Examples
+///
+/// ```
+/// let (a, a_peer) = bichannel::channel::<(), u8>();
+/// let (b, b_peer) = bichannel::channel::<(), char>();
+///
+/// a_peer.send_all(vec![1, 2, 3]).unwrap();
+/// b_peer.send_all(vec!['a', 'b']).unwrap();
+/// drop((a_peer, b_peer));
+///
+/// let pairs: Vec<_> = bichannel::zip(a, b).collect();
+/// assert_eq!(pairs, [(1, 'a'), (2, 'b')]);
+/// ```
+pub fn zip<S1, A, S2, B>(a: Channel<S1, A>, b: Channel<S2, B>) -> Zip<S1, A, S2, B> {
+    Zip {
+        sources: Some((a, b)),
+    }
+}
+
+/// An iterator over pairs of messages received on two channels, created by [`zip`].
+pub struct Zip<S1, A, S2, B> {
+    /// Dropped once either source hangs up.
+    sources: Option<(Channel<S1, A>, Channel<S2, B>)>,
+}
+
+impl<S1, A, S2, B> Iterator for Zip<S1, A, S2, B> {
+    type Item = (A, B);
+
+    fn next(&mut self) -> Option<(A, B)> {
+        let (a, b) = self.sources.as_ref()?;
+        let pair = match a.recv() {
+            Ok(a_message) => b.recv().ok().map(|b_message| (a_message, b_message)),
+            Err(_) => None,
+        };
+
+        if pair.is_none() {
+            self.sources = None;
+        }
+        pair
+    }
+}
+
+impl<S1, A, S2, B> fmt::Debug for Zip<S1, A, S2, B> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Zip")
+            .field("ended", &self.sources.is_none())
+            .finish()
+    }
+}
+
+#[cfg(test)]
+mod examples {
+    use std::thread;
+    use std::time::Duration;
+
+    #[test]
+    fn test_pairs_in_order() {
+        let (a, a_peer) = crate::channel::<(), u8>();
+        let (b, b_peer) = crate::channel::<(), char>();
+
+        let producer = thread::spawn(move || {
+            for c in ['a', 'b', 'c'] {
+                thread::sleep(Duration::from_millis(5));
+                b_peer.send(c).unwrap();
+            }
+        });
+        a_peer.send_all(vec![1, 2, 3]).unwrap();
+        drop(a_peer);
+
+        let pairs: Vec<_> = crate::zip(a, b).collect();
+        assert_eq!(pairs, [(1, 'a'), (2, 'b'), (3, 'c')]);
+
+        producer.join().unwrap();
+    }
+
+    #[test]
+    fn test_ends_when_either_hangs_up() {
+        let (a, a_peer) = crate::channel::<(), u8>();
+        let (b, b_peer) = crate::channel::<(), u8>();
+
+        a_peer.send(1).unwrap();
+        b_peer.send_all(vec![2, 3]).unwrap();
+        drop(a_peer);
+
+        let mut zipped = crate::zip(a, b);
+        assert_eq!(zipped.next(), Some((1, 2)));
+        assert_eq!(zipped.next(), None);
+
+        // The sources are dropped with the end of the iterator.
+        assert!(b_peer.is_disconnected());
+        assert_eq!(zipped.next(), None);
+    }
+}